@@ -0,0 +1,6 @@
+use trivalibs::painter::app::CanvasApp;
+use wasm_graphics_test::depth_bias::DepthBiasApp;
+
+fn main() {
+    DepthBiasApp::create().start();
+}
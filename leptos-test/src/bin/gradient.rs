@@ -0,0 +1,6 @@
+use trivalibs::painter::app::CanvasApp;
+use wasm_graphics_test::gradient::GradientApp;
+
+fn main() {
+    GradientApp::create().start();
+}
@@ -0,0 +1,6 @@
+use trivalibs::painter::app::CanvasApp;
+use wasm_graphics_test::instanced::InstancedApp;
+
+fn main() {
+    InstancedApp::create().start();
+}
@@ -0,0 +1,6 @@
+use trivalibs::painter::app::CanvasApp;
+use wasm_graphics_test::interleaved::InterleavedApp;
+
+fn main() {
+    InterleavedApp::create().start();
+}
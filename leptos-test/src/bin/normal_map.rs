@@ -0,0 +1,6 @@
+use trivalibs::painter::app::CanvasApp;
+use wasm_graphics_test::normal_map::NormalMapApp;
+
+fn main() {
+    NormalMapApp::create().start();
+}
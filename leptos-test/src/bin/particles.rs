@@ -0,0 +1,6 @@
+use trivalibs::painter::app::CanvasApp;
+use wasm_graphics_test::particles::ParticlesApp;
+
+fn main() {
+    ParticlesApp::create().start();
+}
@@ -0,0 +1,6 @@
+use trivalibs::painter::app::CanvasApp;
+use wasm_graphics_test::separate_buffers::SeparateBuffersApp;
+
+fn main() {
+    SeparateBuffersApp::create().start();
+}
@@ -1,7 +1,54 @@
-use trivalibs::painter::app::CanvasApp;
-use wasm_graphics_test::render::SimpleApp;
+use trivalibs::painter::app::{AppConfig, CanvasApp};
+use trivalibs::utils::default;
+use wasm_graphics_test::gpu::native_features;
+use wasm_graphics_test::render::{AppEvent, SimpleApp};
+
+// Usage: `cargo run --bin simple [--present-mode fifo|mailbox|immediate]
+// [--max-fps N]`. Mailbox and immediate don't wait for vsync, to measure the
+// uncapped frame rate, or to test the frame rate cap without it.
+fn parse_present_mode(mode: &str) -> Option<wgpu::PresentMode> {
+    match mode {
+        "fifo" => Some(wgpu::PresentMode::Fifo),
+        "mailbox" => Some(wgpu::PresentMode::Mailbox),
+        "immediate" => Some(wgpu::PresentMode::Immediate),
+        _ => {
+            log::warn!("Unknown present mode {mode:?}, expected fifo, mailbox or immediate");
+            None
+        }
+    }
+}
 
 fn main() {
+    // The adapter and backend are logged during init
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let mut present_mode = None;
+    let mut max_fps = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--present-mode", Some(mode)) => present_mode = parse_present_mode(&mode),
+            ("--max-fps", Some(fps)) => {
+                max_fps = fps.parse::<f32>().ok().filter(|f| *f > 0.0);
+                if max_fps.is_none() {
+                    log::warn!("Ignoring invalid --max-fps {fps:?}");
+                }
+            }
+            _ => log::warn!("Ignoring unknown argument {arg:?}"),
+        }
+    }
+
     // Create and start the app - trivalibs handles everything
-    SimpleApp::create().start();
+    let app = SimpleApp::create();
+    if let Some(mode) = present_mode {
+        let _ = app.get_handle().send_event(AppEvent::PresentMode(mode));
+    }
+    if max_fps.is_some() {
+        let _ = app.get_handle().send_event(AppEvent::MaxFps(max_fps));
+    }
+    app.config(AppConfig {
+        features: Some(native_features()),
+        ..default()
+    })
+    .start();
 }
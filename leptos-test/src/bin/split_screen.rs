@@ -0,0 +1,6 @@
+use trivalibs::painter::app::CanvasApp;
+use wasm_graphics_test::split_screen::SplitScreenApp;
+
+fn main() {
+    SplitScreenApp::create().start();
+}
@@ -0,0 +1,6 @@
+use trivalibs::painter::app::CanvasApp;
+use wasm_graphics_test::textured_quad::TexturedQuadApp;
+
+fn main() {
+    TexturedQuadApp::create().start();
+}
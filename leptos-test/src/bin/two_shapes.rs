@@ -0,0 +1,6 @@
+use trivalibs::painter::app::CanvasApp;
+use wasm_graphics_test::two_shapes::TwoShapesApp;
+
+fn main() {
+    TwoShapesApp::create().start();
+}
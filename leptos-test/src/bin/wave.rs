@@ -0,0 +1,6 @@
+use trivalibs::painter::app::CanvasApp;
+use wasm_graphics_test::wave::WaveApp;
+
+fn main() {
+    WaveApp::create().start();
+}
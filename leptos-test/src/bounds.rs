@@ -0,0 +1,134 @@
+use crate::gpu::{Gpu, Mesh};
+use trivalibs::prelude::*;
+
+// Axis aligned box containing a mesh, in the space of its positions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    // Empty point sets give a zero sized box at the origin.
+    pub fn from_points(points: &[Vec3]) -> Self {
+        if points.is_empty() {
+            return Self {
+                min: Vec3::ZERO,
+                max: Vec3::ZERO,
+            };
+        }
+        points.iter().fold(
+            Self {
+                min: Vec3::splat(f32::INFINITY),
+                max: Vec3::splat(f32::NEG_INFINITY),
+            },
+            |b, &p| Self {
+                min: b.min.min(p),
+                max: b.max.max(p),
+            },
+        )
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn size(&self) -> Vec3 {
+        self.max - self.min
+    }
+}
+
+// Sphere containing a mesh, in the space of its positions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    // Centered on the axis aligned bounds of the points. Not the smallest
+    // enclosing sphere, but close to it for most meshes and cheap to compute.
+    pub fn from_points(points: &[Vec3]) -> Self {
+        let center = Aabb::from_points(points).center();
+        let radius = points
+            .iter()
+            .map(|p| p.distance(center))
+            .fold(0.0, f32::max);
+        Self { center, radius }
+    }
+
+    // The sphere transformed by `mat`. The radius grows with the largest axis
+    // scale, so under non-uniform scale the sphere is larger than needed, but
+    // still contains the transformed mesh.
+    pub fn transformed(&self, mat: Mat4) -> Self {
+        let scale = mat
+            .x_axis
+            .truncate()
+            .length()
+            .max(mat.y_axis.truncate().length())
+            .max(mat.z_axis.truncate().length());
+        Self {
+            center: mat.transform_point3(self.center),
+            radius: self.radius * scale,
+        }
+    }
+
+    // The smallest sphere containing both spheres.
+    pub fn union(&self, other: &Self) -> Self {
+        let offset = other.center - self.center;
+        let distance = offset.length();
+        if distance + other.radius <= self.radius {
+            return *self;
+        }
+        if distance + self.radius <= other.radius {
+            return *other;
+        }
+        let radius = (distance + self.radius + other.radius) * 0.5;
+        Self {
+            center: self.center + offset * ((radius - self.radius) / distance),
+            radius,
+        }
+    }
+}
+
+// Both bounds of a mesh, computed together from its positions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub aabb: Aabb,
+    pub sphere: BoundingSphere,
+}
+
+impl Bounds {
+    pub fn from_points(points: &[Vec3]) -> Self {
+        Self {
+            aabb: Aabb::from_points(points),
+            sphere: BoundingSphere::from_points(points),
+        }
+    }
+}
+
+// A mesh together with the bounds of its positions. Meshes only hold vertex
+// bytes, so the positions are passed separately.
+#[derive(Clone)]
+pub struct BoundedMesh {
+    pub mesh: Mesh,
+    bounds: Bounds,
+}
+
+impl BoundedMesh {
+    pub fn new<T: bytemuck::Pod>(
+        gpu: &Gpu,
+        vertices: &[T],
+        indices: &[u32],
+        positions: &[Vec3],
+    ) -> Self {
+        Self {
+            mesh: Mesh::new(gpu, vertices).with_indices(gpu, indices),
+            bounds: Bounds::from_points(positions),
+        }
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        self.bounds
+    }
+}
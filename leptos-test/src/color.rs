@@ -0,0 +1,107 @@
+// Color conversions for the UI controls. Hue is in degrees [0, 360),
+// saturation, value and the rgb channels are in [0, 1].
+//
+// Colors picked in the UI are sRGB encoded, like all CSS colors. Shaders
+// work with linear colors and write them to an sRGB surface, which encodes
+// them in hardware. So UI colors are converted with `srgb_to_linear` before
+// being sent to the GPU.
+
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+pub fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (f32, f32, f32) {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let c = v * s;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let m = v - c;
+
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    (r + m, g + m, b + m)
+}
+
+// Hue is undefined for grays and saturation for black. In that case the
+// given previous values are kept, so that the sliders don't jump when the
+// color passes through gray or black.
+pub fn rgb_to_hsv(r: f32, g: f32, b: f32, prev_h: f32, prev_s: f32) -> (f32, f32, f32) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    let v = max;
+
+    if max <= f32::EPSILON {
+        return (prev_h, prev_s, v);
+    }
+    let s = delta / max;
+
+    if delta <= f32::EPSILON {
+        return (prev_h, s, v);
+    }
+    let h = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (h, s, v)
+}
+
+// Parses a `#rrggbb` color, as produced by `<input type="color">`.
+pub fn hex_to_rgb(hex: &str) -> Option<(f32, f32, f32)> {
+    let hex = hex.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| {
+        u8::from_str_radix(hex.get(i..i + 2)?, 16)
+            .ok()
+            .map(|c| c as f32 / 255.0)
+    };
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Encodes like the sRGB surface: linear to sRGB, rounded to 8 bits.
+    fn encoded_byte(linear: f32) -> u8 {
+        (linear_to_srgb(linear) * 255.0).round() as u8
+    }
+
+    #[test]
+    fn mid_gray_output_encodes_to_188() {
+        assert_eq!(encoded_byte(0.5), 188);
+        assert_eq!(encoded_byte(0.0), 0);
+        assert_eq!(encoded_byte(1.0), 255);
+    }
+
+    #[test]
+    fn ui_colors_survive_linear_output() {
+        // A UI color converted for the shader comes out of the surface as the
+        // same byte, e.g. #808080 as 128.
+        for byte in 0..=255u8 {
+            assert_eq!(encoded_byte(srgb_to_linear(byte as f32 / 255.0)), byte);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::gpu::Gpu;
+use crate::texture::{upload_rgba, RgbaImage};
+use std::fmt;
+use trivalibs::prelude::*;
+
+// Cube map layout
+//
+// A cube map is 6 square images of the same size, one per face, in the order
+// +X, -X, +Y, -Y, +Z, -Z, which is the layer order wgpu (like Vulkan and D3D)
+// samples them in. Each face is stored as seen from inside the cube, rows top
+// to bottom, with the directions of its pixel columns and rows given by
+// `face_dir`:
+//
+// | face | right | down |
+// |------|-------|------|
+// | +X   | -Z    | -Y   |
+// | -X   | +Z    | -Y   |
+// | +Y   | +X    | +Z   |
+// | -Y   | +X    | -Z   |
+// | +Z   | +X    | -Y   |
+// | -Z   | -X    | -Y   |
+//
+// Sampling filters across face edges without seams, WebGPU requires seamless
+// cube filtering. Images still need to match along their shared edges, which
+// faces generated per direction with `cubemap_from_fn` always do.
+
+pub const FACE_COUNT: usize = 6;
+
+// Direction from the cube center through the center of pixel (x, y) of
+// `face`, not normalized.
+pub fn face_dir(face: usize, x: u32, y: u32, size: u32) -> Vec3 {
+    let u = (x as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+    let v = (y as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+    match face {
+        0 => vec3(1.0, -v, -u),
+        1 => vec3(-1.0, -v, u),
+        2 => vec3(u, 1.0, v),
+        3 => vec3(u, -1.0, -v),
+        4 => vec3(u, -v, 1.0),
+        5 => vec3(-u, -v, -1.0),
+        _ => panic!("cube maps have {FACE_COUNT} faces, got face {face}"),
+    }
+}
+
+#[derive(Debug)]
+pub enum CubemapError {
+    NotSquare { face: usize },
+    SizeMismatch { face: usize },
+}
+
+impl fmt::Display for CubemapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CubemapError::NotSquare { face } => write!(f, "cube map face {face} is not square"),
+            CubemapError::SizeMismatch { face } => {
+                write!(f, "cube map face {face} differs in size from face 0")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CubemapError {}
+
+// Uploads the faces of a cube map of sRGB colors, and returns the cube view
+// to bind with `CUBE_TEXTURE_FRAG`.
+pub fn cubemap_from_rgba(gpu: &Gpu, size: u32, faces: [&[u8]; FACE_COUNT]) -> wgpu::TextureView {
+    upload_rgba(
+        gpu,
+        (size, size, FACE_COUNT as u32),
+        &faces.concat(),
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+        wgpu::TextureViewDimension::Cube,
+    )
+}
+
+// Creates a cube map texture from 6 decoded face images, in the order and
+// orientation described at the top of this file.
+pub fn cubemap_from_images(
+    gpu: &Gpu,
+    faces: &[RgbaImage; FACE_COUNT],
+) -> Result<wgpu::TextureView, CubemapError> {
+    let size = faces[0].width;
+    for (face, image) in faces.iter().enumerate() {
+        if image.width != image.height {
+            return Err(CubemapError::NotSquare { face });
+        }
+        if image.width != size {
+            return Err(CubemapError::SizeMismatch { face });
+        }
+    }
+    Ok(cubemap_from_rgba(gpu, size, faces.each_ref().map(|image| image.data.as_slice())))
+}
+
+// Creates a cube map with `size` pixels per face edge, colored by `f` with
+// the normalized direction of each pixel.
+pub fn cubemap_from_fn(
+    gpu: &Gpu,
+    size: u32,
+    f: impl Fn(Vec3) -> [u8; 4],
+) -> wgpu::TextureView {
+    let faces: [Vec<u8>; FACE_COUNT] = std::array::from_fn(|face| {
+        (0..size)
+            .flat_map(|y| (0..size).map(move |x| (x, y)))
+            .flat_map(|(x, y)| f(face_dir(face, x, y, size).normalize()))
+            .collect()
+    });
+    cubemap_from_rgba(gpu, size, faces.each_ref().map(Vec::as_slice))
+}
@@ -0,0 +1,107 @@
+// Holds back a stream of values until it settles: a value is only passed on
+// once no newer one arrived for `interval` seconds, and then only the latest.
+// Times are in seconds from any fixed start, so it runs the same with real
+// and simulated clocks.
+pub struct Debounce<T> {
+    interval: f64,
+    pending: Option<(T, f64)>,
+}
+
+impl<T> Debounce<T> {
+    pub fn new(interval: f64) -> Self {
+        Self {
+            interval,
+            pending: None,
+        }
+    }
+
+    // Replaces any value still waiting and restarts the interval.
+    pub fn push(&mut self, value: T, now: f64) {
+        self.pending = Some((value, now));
+    }
+
+    // Takes the latest value, once `interval` passed since it was pushed.
+    pub fn poll(&mut self, now: f64) -> Option<T> {
+        match self.pending {
+            // Compared like `due`, so polling at that time always succeeds.
+            Some((_, at)) if now >= at + self.interval => self.pending.take().map(|(v, _)| v),
+            _ => None,
+        }
+    }
+
+    // Whether a value is waiting, so the caller keeps polling until it is
+    // passed on, even when nothing else happens.
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    // When the waiting value can be taken, to poll with a timer instead of
+    // every frame.
+    pub fn due(&self) -> Option<f64> {
+        self.pending.as_ref().map(|(_, at)| at + self.interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A burst of resizes, like dragging the window edge, is coalesced into a
+    // single one with the final size, passed on only after the events
+    // settled, and a later single resize still gets through.
+    #[test]
+    fn burst_is_coalesced_into_final_value() {
+        const INTERVAL: f64 = 0.1;
+        // 60 fps frames, polling each one.
+        const FRAME: f64 = 1.0 / 60.0;
+
+        let mut resizes = Debounce::new(INTERVAL);
+        let mut applied = Vec::new();
+        let mut now = 0.0;
+
+        // One resize per frame for half a second, growing by 10 pixels each.
+        let mut width = 800;
+        while now < 0.5 {
+            width += 10;
+            resizes.push((width, 600), now);
+            applied.extend(resizes.poll(now));
+            now += FRAME;
+        }
+        let burst_end = now - FRAME;
+        assert!(applied.is_empty());
+
+        // Polling goes on after the events stopped, while a size is pending.
+        while resizes.is_pending() {
+            if let Some(size) = resizes.poll(now) {
+                applied.push(size);
+                assert!(now - burst_end >= INTERVAL, "applied after {}s", now - burst_end);
+            }
+            now += FRAME;
+        }
+
+        resizes.push((640, 480), now);
+        applied.extend(resizes.poll(now + INTERVAL));
+        assert_eq!(applied, [(width, 600), (640, 480)]);
+    }
+
+    // Polling with one timer per push, at the time `due` returns then, like
+    // the canvas resizes in main.rs: only the timer of the last push passes a
+    // value on, the earlier ones find it not due yet.
+    #[test]
+    fn timer_per_push_applies_last_value_once() {
+        const INTERVAL: f64 = 0.25;
+
+        let mut resizes = Debounce::new(INTERVAL);
+        let mut timers = Vec::new();
+        for (i, now) in [0.0, 0.0625, 0.125, 0.25, 0.375].into_iter().enumerate() {
+            resizes.push(i, now);
+            timers.extend(resizes.due());
+        }
+        assert_eq!(resizes.due(), Some(0.375 + INTERVAL));
+
+        let applied: Vec<_> = timers.into_iter().filter_map(|at| resizes.poll(at)).collect();
+        assert_eq!(applied, [4]);
+        assert!(!resizes.is_pending());
+        assert_eq!(resizes.due(), None);
+    }
+}
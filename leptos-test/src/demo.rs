@@ -0,0 +1,320 @@
+use crate::depth_bias::DepthBiasApp;
+use crate::feedback::{self, Feedback};
+use crate::gradient::GradientApp;
+use crate::instanced::{self, InstancedApp};
+use crate::interleaved::InterleavedApp;
+use crate::normal_map::NormalMapApp;
+use crate::particles::ParticlesApp;
+use crate::reflection::ReflectionApp;
+use crate::render::{AppEvent, SimpleApp};
+use crate::separate_buffers::SeparateBuffersApp;
+use crate::split_screen::SplitScreenApp;
+use crate::surface::{render_recovering, set_alpha_mode};
+use crate::textured_quad::TexturedQuadApp;
+use crate::trails::TrailsApp;
+use crate::two_shapes::TwoShapesApp;
+use crate::wave::WaveApp;
+use std::cell::Cell;
+use std::mem::discriminant;
+use trivalibs::painter::app::Event;
+use trivalibs::painter::prelude::*;
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Demo {
+    Simple,
+    TwoShapes,
+    Wave,
+    Gradient,
+    Instanced,
+    TexturedQuad,
+    Particles,
+    Trails,
+    NormalMap,
+    Reflection,
+    SplitScreen,
+    DepthBias,
+    Interleaved,
+    SeparateBuffers,
+}
+
+impl Demo {
+    pub const ALL: [Demo; 14] = [
+        Demo::Simple,
+        Demo::TwoShapes,
+        Demo::Wave,
+        Demo::Gradient,
+        Demo::Instanced,
+        Demo::TexturedQuad,
+        Demo::Particles,
+        Demo::Trails,
+        Demo::NormalMap,
+        Demo::Reflection,
+        Demo::SplitScreen,
+        Demo::DepthBias,
+        Demo::Interleaved,
+        Demo::SeparateBuffers,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Demo::Simple => "Lit cube",
+            Demo::TwoShapes => "Two shapes",
+            Demo::Wave => "Wave",
+            Demo::Gradient => "Gradient",
+            Demo::Instanced => "Instanced",
+            Demo::TexturedQuad => "Textured quad",
+            Demo::Particles => "Particles",
+            Demo::Trails => "Trails",
+            Demo::NormalMap => "Normal map",
+            Demo::Reflection => "Reflection",
+            Demo::SplitScreen => "Split screen",
+            Demo::DepthBias => "Depth bias",
+            Demo::Interleaved => "Vertex layout",
+            Demo::SeparateBuffers => "Separate buffers",
+        }
+    }
+
+    // Demos using storage buffers or compute shaders.
+    pub fn needs_storage(self) -> bool {
+        matches!(self, Demo::Instanced | Demo::Particles)
+    }
+}
+
+// WebGL2 has neither, so both are missing if the app runner picked the GL
+// backend.
+fn storage_supported(p: &Painter) -> bool {
+    let compute = p
+        .adapter
+        .get_downlevel_capabilities()
+        .flags
+        .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS);
+    compute && p.device.limits().max_storage_buffers_per_shader_stage > 0
+}
+
+#[derive(Debug, Clone)]
+pub enum DemoEvent {
+    Switch(Demo),
+    // Events for `SimpleApp`, ignored while another demo is active.
+    Simple(AppEvent),
+    // Seed of the instance layout of `InstancedApp`.
+    InstanceSeed(u64),
+    // How the canvas is composited with the page, for all demos.
+    AlphaMode(wgpu::CompositeAlphaMode),
+    // Whether the page is visible, from the Page Visibility API, and whether
+    // the window has focus. Forwarded to `SimpleApp`, the other demos only
+    // animate while frames are requested, which browsers throttle for hidden
+    // pages anyway.
+    Visible(bool),
+    Focus(bool),
+}
+
+impl From<AppEvent> for DemoEvent {
+    fn from(e: AppEvent) -> Self {
+        DemoEvent::Simple(e)
+    }
+}
+
+// Only one app is alive at a time, so the size of the largest doesn't matter.
+#[allow(clippy::large_enum_variant)]
+enum ActiveApp {
+    Simple(SimpleApp),
+    TwoShapes(TwoShapesApp),
+    Wave(WaveApp),
+    Gradient(GradientApp),
+    Instanced(InstancedApp),
+    TexturedQuad(TexturedQuadApp),
+    Particles(ParticlesApp),
+    Trails(TrailsApp),
+    NormalMap(NormalMapApp),
+    Reflection(ReflectionApp),
+    SplitScreen(SplitScreenApp),
+    DepthBias(DepthBiasApp),
+    Interleaved(InterleavedApp),
+    SeparateBuffers(SeparateBuffersApp),
+}
+
+// Runs `$body` with `$app` bound to the active app, whatever its type.
+macro_rules! with_active {
+    ($active:expr, $app:ident => $body:expr) => {
+        match $active {
+            ActiveApp::Simple($app) => $body,
+            ActiveApp::TwoShapes($app) => $body,
+            ActiveApp::Wave($app) => $body,
+            ActiveApp::Gradient($app) => $body,
+            ActiveApp::Instanced($app) => $body,
+            ActiveApp::TexturedQuad($app) => $body,
+            ActiveApp::Particles($app) => $body,
+            ActiveApp::Trails($app) => $body,
+            ActiveApp::NormalMap($app) => $body,
+            ActiveApp::Reflection($app) => $body,
+            ActiveApp::SplitScreen($app) => $body,
+            ActiveApp::DepthBias($app) => $body,
+            ActiveApp::Interleaved($app) => $body,
+            ActiveApp::SeparateBuffers($app) => $body,
+        }
+    };
+}
+
+impl ActiveApp {
+    fn init(demo: Demo, p: &mut Painter) -> Self {
+        match demo {
+            Demo::Simple => ActiveApp::Simple(SimpleApp::init(p)),
+            Demo::TwoShapes => ActiveApp::TwoShapes(TwoShapesApp::init(p)),
+            Demo::Wave => ActiveApp::Wave(WaveApp::init(p)),
+            Demo::Gradient => ActiveApp::Gradient(GradientApp::init(p)),
+            Demo::Instanced => ActiveApp::Instanced(InstancedApp::init(p)),
+            Demo::TexturedQuad => ActiveApp::TexturedQuad(TexturedQuadApp::init(p)),
+            Demo::Particles => ActiveApp::Particles(ParticlesApp::init(p)),
+            Demo::Trails => ActiveApp::Trails(TrailsApp::init(p)),
+            Demo::NormalMap => ActiveApp::NormalMap(NormalMapApp::init(p)),
+            Demo::Reflection => ActiveApp::Reflection(ReflectionApp::init(p)),
+            Demo::SplitScreen => ActiveApp::SplitScreen(SplitScreenApp::init(p)),
+            Demo::DepthBias => ActiveApp::DepthBias(DepthBiasApp::init(p)),
+            Demo::Interleaved => ActiveApp::Interleaved(InterleavedApp::init(p)),
+            Demo::SeparateBuffers => ActiveApp::SeparateBuffers(SeparateBuffersApp::init(p)),
+        }
+    }
+}
+
+// Hosts one of the demo apps on the canvas and switches between them at
+// runtime. Switching drops the previous app with its GPU resources, keeping
+// surface and device, and initializes the next one on the same painter.
+pub struct DemoApp {
+    demo: Demo,
+    active: ActiveApp,
+    // The size the active demo was last resized to.
+    size: Option<(u32, u32)>,
+    storage_supported: bool,
+    // Latest state changing events for `SimpleApp`, replayed when switching
+    // back to it, so it matches the UI controls again.
+    simple_state: Vec<AppEvent>,
+    instance_seed: u64,
+    // Set after a surface error that can't be recovered from. Nothing is
+    // rendered anymore.
+    stopped: Cell<bool>,
+}
+
+impl DemoApp {
+    fn switch(&mut self, demo: Demo, p: &mut Painter) {
+        if demo == self.demo {
+            return;
+        }
+        if demo.needs_storage() && !self.storage_supported {
+            log::warn!("{} needs storage buffers, which this device lacks", demo.label());
+            return;
+        }
+        log::info!("Switching to {}", demo.label());
+
+        self.demo = demo;
+        self.active = ActiveApp::init(demo, p);
+
+        match &mut self.active {
+            ActiveApp::Simple(app) => {
+                for e in &self.simple_state {
+                    app.event(Event::UserEvent(e.clone()), p);
+                }
+            }
+            ActiveApp::Instanced(app) => app.set_seed(self.instance_seed),
+            _ => {}
+        }
+        if let Some((width, height)) = self.size {
+            with_active!(&mut self.active, app => app.resize(p, width, height));
+        }
+        p.request_next_frame();
+    }
+
+    // Camera events are not kept, the camera starts from its default position
+    // like after a reset.
+    fn remember(&mut self, e: &AppEvent) {
+        if matches!(
+            e,
+            AppEvent::ResetCamera
+                | AppEvent::FrameAll
+                | AppEvent::RotateStep(_)
+                | AppEvent::Camera(_)
+                | AppEvent::Screenshot
+        ) {
+            return;
+        }
+        self.simple_state
+            .retain(|s| discriminant(s) != discriminant(e));
+        self.simple_state.push(e.clone());
+    }
+}
+
+impl CanvasApp<DemoEvent> for DemoApp {
+    fn init(p: &mut Painter) -> Self {
+        let storage_supported = storage_supported(p);
+        feedback::send(Feedback::StorageSupported(storage_supported));
+        Self {
+            demo: Demo::Simple,
+            active: ActiveApp::init(Demo::Simple, p),
+            size: None,
+            storage_supported,
+            simple_state: Vec::new(),
+            instance_seed: instanced::DEFAULT_SEED,
+            stopped: Cell::new(false),
+        }
+    }
+
+    // Resizes arrive debounced on wasm, where main.rs only changes the
+    // canvas size once the page layout settled.
+    fn resize(&mut self, p: &mut Painter, width: u32, height: u32) {
+        self.size = Some((width, height));
+        with_active!(&mut self.active, app => app.resize(p, width, height));
+    }
+
+    fn update(&mut self, p: &mut Painter, tpf: f32) {
+        with_active!(&mut self.active, app => app.update(p, tpf));
+    }
+
+    fn render(&self, p: &mut Painter) -> Result<(), SurfaceError> {
+        if self.stopped.get() {
+            return Ok(());
+        }
+        let result = render_recovering(p, |p| with_active!(&self.active, app => app.render(p)));
+        if let Err(e) = &result {
+            log::error!("Stopping rendering after surface error: {e}");
+            self.stopped.set(true);
+        }
+        result
+    }
+
+    fn event(&mut self, e: Event<DemoEvent>, p: &mut Painter) {
+        match e {
+            Event::UserEvent(DemoEvent::Switch(demo)) => self.switch(demo, p),
+            Event::UserEvent(DemoEvent::Simple(e)) => {
+                self.remember(&e);
+                if let ActiveApp::Simple(app) = &mut self.active {
+                    app.event(Event::UserEvent(e), p);
+                }
+            }
+            Event::UserEvent(DemoEvent::Visible(visible)) => {
+                if let ActiveApp::Simple(app) = &mut self.active {
+                    app.event(Event::UserEvent(AppEvent::Visible(visible)), p);
+                }
+            }
+            Event::UserEvent(DemoEvent::Focus(focused)) => {
+                if let ActiveApp::Simple(app) = &mut self.active {
+                    app.event(Event::UserEvent(AppEvent::Focus(focused)), p);
+                }
+            }
+            Event::UserEvent(DemoEvent::AlphaMode(mode)) => {
+                set_alpha_mode(p, mode);
+                p.request_next_frame();
+            }
+            Event::UserEvent(DemoEvent::InstanceSeed(seed)) => {
+                self.instance_seed = seed;
+                if let ActiveApp::Instanced(app) = &mut self.active {
+                    app.set_seed(seed);
+                    p.request_next_frame();
+                }
+            }
+            Event::WindowEvent(e) => {
+                with_active!(&mut self.active, app => app.event(Event::WindowEvent(e), p))
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use crate::gpu::{shader, DrawCall, Gpu, Mesh, PipelineState, Program, Uniform, UNIFORM_VERT};
+use crate::msaa::{msaa_samples, MSAA_SAMPLES};
+use crate::primitives;
+use crate::surface::{self, Frame};
+use crate::target::Target;
+use trivalibs::painter::app::Event;
+use trivalibs::painter::prelude::*;
+use trivalibs::prelude::*;
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: Vec3,
+    color: Vec3,
+}
+
+const PLANE_SIZE: f32 = 20.0;
+const DECAL_SIZE: f32 = 6.0;
+const DECAL_OFFSET: f32 = 4.5;
+const PLANE_COLOR: Vec3 = vec3(0.4, 0.4, 0.45);
+const DECAL_COLOR: Vec3 = vec3(0.9, 0.3, 0.2);
+
+// Pulls the decal toward the camera by 2 steps of the depth format, plus one
+// per unit of depth slope, which keeps it on top at grazing angles too.
+// Negative, as depth tests with `Less` and smaller is nearer.
+const DECAL_DEPTH_BIAS: i32 = -2;
+const DECAL_DEPTH_BIAS_SLOPE: f32 = -1.0;
+
+const CAM_FOV: f32 = 0.8;
+const CAM_DISTANCE: f32 = 22.0;
+// Low, so the plane is seen at a flat angle, where z-fighting is worst.
+const CAM_HEIGHT: f32 = 4.0;
+// Radians per second.
+const CAM_SPEED: f32 = 0.2;
+// A far plane much further than needed, spreading the depth precision thin.
+const CAM_NEAR: f32 = 0.1;
+const CAM_FAR: f32 = 1000.0;
+
+// Two decals lying on a plane, all three in the same plane. The left decal
+// has no depth bias and z-fights with the plane, showing up in flickering
+// stripes. The right one is drawn with a depth bias in its pipeline state and
+// stays cleanly on top.
+pub struct DepthBiasApp {
+    gpu: Gpu,
+    vp_mat: Uniform<Mat4>,
+    aspect_ratio: f32,
+    yaw: f32,
+
+    canvas: Target,
+    // The plane first, so the decals are tested against its depth.
+    draws: [DrawCall; 3],
+}
+
+// A square in the y = 0 plane, facing up.
+fn quad(center: Vec3, size: f32, color: Vec3) -> (Vec<Vertex>, Vec<u32>) {
+    let (positions, indices) = primitives::quad(size);
+    let vertices = positions
+        .into_iter()
+        .map(|corner| Vertex {
+            position: center + corner,
+            color,
+        })
+        .collect();
+    (vertices, indices)
+}
+
+impl CanvasApp<()> for DepthBiasApp {
+    fn init(p: &mut Painter) -> Self {
+        let gpu = Gpu::new(p);
+        let program = Program::build(&gpu, shader!("colored_vertex"))
+            .with_fragment(shader!("colored_fragment"))
+            .with_vertex(&[Float32x3, Float32x3])
+            .with_bindings(&[UNIFORM_VERT, UNIFORM_VERT])
+            .create();
+
+        let vp_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let model_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+
+        let canvas = Target::canvas(surface::view_format(p))
+            .with_samples(msaa_samples(p, MSAA_SAMPLES))
+            .with_depth()
+            .create(&gpu, 1, 1);
+
+        let draw = |center: Vec3, size: f32, color: Vec3, state: PipelineState| {
+            let (vertices, indices) = quad(center, size, color);
+            let mesh = Mesh::new(&gpu, &vertices).with_indices(&gpu, &indices);
+            let bind_group =
+                program.bind_group(&gpu, 0, &[vp_mat.binding(), model_mat.binding()]);
+            DrawCall::new(&gpu, &program, &mesh, vec![bind_group], state)
+        };
+        let state = canvas.pipeline_state();
+        let biased = PipelineState {
+            depth_bias: wgpu::DepthBiasState {
+                constant: DECAL_DEPTH_BIAS,
+                slope_scale: DECAL_DEPTH_BIAS_SLOPE,
+                clamp: 0.0,
+            },
+            ..state
+        };
+        let draws = [
+            draw(Vec3::ZERO, PLANE_SIZE, PLANE_COLOR, state),
+            draw(vec3(-DECAL_OFFSET, 0.0, 0.0), DECAL_SIZE, DECAL_COLOR, state),
+            draw(vec3(DECAL_OFFSET, 0.0, 0.0), DECAL_SIZE, DECAL_COLOR, biased),
+        ];
+
+        Self {
+            gpu,
+            vp_mat,
+            aspect_ratio: 1.0,
+            yaw: 0.0,
+
+            canvas,
+            draws,
+        }
+    }
+
+    fn resize(&mut self, _p: &mut Painter, width: u32, height: u32) {
+        self.aspect_ratio = width.max(1) as f32 / height.max(1) as f32;
+        self.canvas.resize(&self.gpu, width, height);
+        self.update_cam();
+    }
+
+    fn update(&mut self, p: &mut Painter, tpf: f32) {
+        self.yaw += tpf * CAM_SPEED;
+        self.update_cam();
+        p.request_next_frame();
+    }
+
+    fn render(&self, p: &mut Painter) -> Result<(), SurfaceError> {
+        let Some(frame) = Frame::acquire(p)? else {
+            return Ok(());
+        };
+        let mut encoder = self.gpu.encoder("Depth Bias Encoder");
+        {
+            let mut pass = self.canvas.pass(&mut encoder, Some(&frame.view));
+            for draw in &self.draws {
+                draw.draw(&mut pass, self.canvas.size());
+            }
+        }
+        self.gpu.queue.submit([encoder.finish()]);
+        frame.present();
+        Ok(())
+    }
+
+    fn event(&mut self, _e: Event<()>, _p: &mut Painter) {}
+}
+
+impl DepthBiasApp {
+    fn update_cam(&self) {
+        let eye = vec3(
+            self.yaw.sin() * CAM_DISTANCE,
+            CAM_HEIGHT,
+            self.yaw.cos() * CAM_DISTANCE,
+        );
+        let view = Mat4::look_at_rh(eye, Vec3::ZERO, Vec3::Y);
+        let proj = Mat4::perspective_rh(CAM_FOV, self.aspect_ratio, CAM_NEAR, CAM_FAR);
+        self.vp_mat.update(&self.gpu, proj * view);
+    }
+}
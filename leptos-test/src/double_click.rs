@@ -0,0 +1,78 @@
+use trivalibs::prelude::*;
+
+// Two clicks form a double click if the second follows within this many
+// seconds, and lands within this many pixels of the first.
+pub const DOUBLE_CLICK_TIME: f64 = 0.3;
+pub const DOUBLE_CLICK_DISTANCE: f32 = 8.0;
+
+// Detects double clicks, or double taps, from single clicks. Only clicks are
+// registered, not the release at the end of a drag, which `cancel`s instead,
+// so dragging and releasing twice in a row doesn't count.
+#[derive(Default)]
+pub struct DoubleClick {
+    last: Option<(Vec2, f64)>,
+}
+
+impl DoubleClick {
+    // Registers a click at `pos`, `now` in seconds, and returns whether it
+    // completes a double click. A third click starts a new pair.
+    pub fn click(&mut self, pos: Vec2, now: f64) -> bool {
+        let double = self.last.take().is_some_and(|(last_pos, at)| {
+            now - at <= DOUBLE_CLICK_TIME && pos.distance(last_pos) <= DOUBLE_CLICK_DISTANCE
+        });
+        if !double {
+            self.last = Some((pos, now));
+        }
+        double
+    }
+
+    pub fn cancel(&mut self) {
+        self.last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POS: Vec2 = vec2(100.0, 100.0);
+
+    // Clicks at the given times and offsets from `POS`, `None` for a drag
+    // release, and returns which clicks completed a double click.
+    fn run(clicks: &[Option<(f64, f32)>]) -> Vec<bool> {
+        let mut double_click = DoubleClick::default();
+        clicks
+            .iter()
+            .map(|click| match *click {
+                Some((time, offset)) => double_click.click(POS + vec2(offset, 0.0), time),
+                None => {
+                    double_click.cancel();
+                    false
+                }
+            })
+            .collect()
+    }
+
+    // Two quick clicks at the same spot count, slow or distant ones and drags
+    // in between don't.
+    #[test]
+    fn only_quick_near_clicks_count() {
+        let quick = DOUBLE_CLICK_TIME * 0.5;
+        let slow = DOUBLE_CLICK_TIME * 1.5;
+        let near = DOUBLE_CLICK_DISTANCE * 0.5;
+        let far = DOUBLE_CLICK_DISTANCE * 2.0;
+
+        assert_eq!(run(&[Some((0.0, 0.0)), Some((quick, near))]), [false, true]);
+        assert_eq!(run(&[Some((0.0, 0.0)), Some((slow, 0.0))]), [false, false]);
+        assert_eq!(run(&[Some((0.0, 0.0)), Some((quick, far))]), [false, false]);
+        assert_eq!(
+            run(&[Some((0.0, 0.0)), None, Some((quick, 0.0))]),
+            [false, false, false]
+        );
+        // The third click starts a new pair.
+        assert_eq!(
+            run(&[Some((0.0, 0.0)), Some((quick, 0.0)), Some((quick * 2.0, 0.0))]),
+            [false, true, false]
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use crate::gpu::RenderStats;
+use crate::fps::FpsStats;
+use crate::webgpu::StartError;
+use std::cell::RefCell;
+
+// Messages sent from the running app back to the UI. This is the reverse
+// direction of `CanvasHandle::send_event`.
+#[derive(Debug, Clone)]
+pub enum Feedback {
+    Fps(FpsStats),
+    // GPU time of a frame in milliseconds, `None` if it can't be measured.
+    GpuTime(Option<f32>),
+    Renderer(String),
+    WireframeSupported(bool),
+    // Whether the device has storage buffers and compute shaders, which the
+    // WebGL2 fallback lacks. Demos needing them can't be switched to without.
+    StorageSupported(bool),
+    // Sent whenever the orbit camera comes to rest.
+    Camera { yaw: f32, pitch: f32, distance: f32 },
+    // Name of the object clicked on, `None` when clicking the background.
+    Picked(Option<&'static str>),
+    // Scene objects drawn and skipped by frustum culling in the last frame.
+    Culling { drawn: u32, culled: u32 },
+    // Draw calls, triangles and bind groups of the last frame of the lit
+    // cube, sent with the fps.
+    RenderStats(RenderStats),
+    // Sent instead of starting the app.
+    StartFailed(StartError),
+}
+
+type Listener = Box<dyn Fn(Feedback)>;
+
+thread_local! {
+    static LISTENER: RefCell<Option<Listener>> = RefCell::new(None);
+}
+
+// Registers the UI callback receiving all feedback. Replaces any previous
+// listener. On wasm, app and UI share the main thread, so the callback can
+// update Leptos signals directly.
+pub fn listen(f: impl Fn(Feedback) + 'static) {
+    LISTENER.with(|l| *l.borrow_mut() = Some(Box::new(f)));
+}
+
+// Sends feedback to the UI. Does nothing if no listener is registered, e.g.
+// when running the native binary.
+pub fn send(msg: Feedback) {
+    LISTENER.with(|l| {
+        if let Some(f) = l.borrow().as_ref() {
+            f(msg);
+        }
+    });
+}
@@ -0,0 +1,44 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{DragEvent, File, FileReader};
+
+// Progress of a file dropped onto the canvas, shown below the controls.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileLoad {
+    // File name.
+    Loading(String),
+    Loaded(String),
+    // Message including the file name.
+    Failed(String),
+}
+
+// The first file of a drop, `None` if only text or links were dropped.
+pub fn dropped_file(ev: &DragEvent) -> Option<File> {
+    ev.data_transfer()?.files()?.get(0)
+}
+
+// Reads the whole file with a `FileReader` and passes its bytes to `done`,
+// once the browser is done reading. `done` is called exactly once, also if
+// reading failed.
+pub fn read_file(file: &File, done: impl FnOnce(Result<Vec<u8>, String>) + 'static) {
+    let reader = match FileReader::new() {
+        Ok(reader) => reader,
+        Err(e) => return done(Err(format!("{e:?}"))),
+    };
+    // `loadend` follows both `load` and `error`. The closure frees itself
+    // after the call.
+    let on_load_end = {
+        let reader = reader.clone();
+        Closure::once_into_js(move || {
+            let result = reader.result().ok().filter(|r| r.is_instance_of::<js_sys::ArrayBuffer>());
+            done(match result {
+                Some(buffer) => Ok(js_sys::Uint8Array::new(&buffer).to_vec()),
+                None => Err("could not read the file".to_string()),
+            });
+        })
+    };
+    reader.set_onloadend(Some(on_load_end.unchecked_ref()));
+    if let Err(e) = reader.read_as_array_buffer(file) {
+        log::error!("Failed to start reading {}: {e:?}", file.name());
+    }
+}
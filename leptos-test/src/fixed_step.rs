@@ -0,0 +1,90 @@
+// Turns variable frame times into a whole number of fixed time steps, so an
+// animation advanced per step runs the same at any frame rate. Real time is
+// accumulated, every full `dt` of it is one step, and the rest carries over
+// to the next frame.
+pub struct FixedStep {
+    dt: f32,
+    max_steps: u32,
+    accumulator: f32,
+}
+
+impl FixedStep {
+    pub fn new(dt: f32, max_steps: u32) -> Self {
+        Self {
+            dt,
+            max_steps,
+            accumulator: 0.0,
+        }
+    }
+
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+
+    // Adds the frame time and returns the number of steps to run this frame.
+    // At most `max_steps`: if the steps take longer than the time they
+    // simulate, catching up would need even more steps the next frame, and
+    // the frame rate spirals down. Time beyond that is dropped, so the
+    // animation slows down instead.
+    pub fn advance(&mut self, tpf: f32) -> u32 {
+        self.accumulator += tpf;
+        let steps = (self.accumulator / self.dt) as u32;
+        if steps > self.max_steps {
+            self.accumulator %= self.dt;
+            return self.max_steps;
+        }
+        self.accumulator -= steps as f32 * self.dt;
+        steps
+    }
+
+    // How far the leftover time is into the next step, from 0 to 1, to
+    // interpolate between the last two steps.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.dt).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::SplitMix64;
+
+    const DT: f32 = 1.0 / 60.0;
+    const MAX_STEPS: u32 = 8;
+
+    // Over the same duration, frames at 30, 60 and 144 fps with jittered
+    // frame times run the same number of steps, give or take the one still
+    // accumulating.
+    #[test]
+    fn steps_match_simulated_time_at_any_frame_rate() {
+        const DURATION: f32 = 10.0;
+        // Frame times vary by up to this fraction.
+        const JITTER: f32 = 0.3;
+
+        let expected = (DURATION / DT).round() as i64;
+        for fps in [30.0, 60.0, 144.0] {
+            let mut fixed_step = FixedStep::new(DT, MAX_STEPS);
+            let mut rng = SplitMix64::new(fps as u64);
+            let mut elapsed = 0.0;
+            let mut steps = 0;
+            while elapsed < DURATION {
+                let jitter = rng.range(1.0 - JITTER, 1.0 + JITTER);
+                let tpf = (1.0 / fps * jitter).min(DURATION - elapsed);
+                elapsed += tpf;
+                steps += fixed_step.advance(tpf) as i64;
+                let alpha = fixed_step.alpha();
+                assert!((0.0..=1.0).contains(&alpha), "{fps} fps: alpha {alpha}");
+            }
+            assert!((steps - expected).abs() <= 1, "{fps} fps: {steps} steps, expected {expected}");
+        }
+    }
+
+    #[test]
+    fn long_frame_runs_capped_steps() {
+        let mut fixed_step = FixedStep::new(DT, MAX_STEPS);
+        assert_eq!(fixed_step.advance(1.0), MAX_STEPS);
+        assert!(fixed_step.alpha() < 1.0);
+        // The dropped time is not caught up on later.
+        assert!(fixed_step.advance(DT) <= 1);
+    }
+}
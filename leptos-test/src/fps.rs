@@ -0,0 +1,126 @@
+use std::collections::VecDeque;
+
+// Frame rates over the window of an `FpsCounter`: the average, and those of
+// the slowest and the fastest frame in it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FpsStats {
+    pub avg: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+// Averages the frame rate over a sliding time window.
+pub struct FpsCounter {
+    window: f32,
+    frame_times: VecDeque<f32>,
+    total_time: f32,
+}
+
+impl FpsCounter {
+    pub fn new(window: f32) -> Self {
+        Self {
+            window,
+            frame_times: VecDeque::new(),
+            total_time: 0.0,
+        }
+    }
+
+    // Changes the window in seconds. 0 keeps only the latest frame, giving
+    // the instantaneous frame rate. Frames beyond a smaller window are
+    // dropped right away, a larger one fills up with the next frames.
+    pub fn set_window(&mut self, window: f32) {
+        self.window = window.max(0.0);
+        self.trim();
+    }
+
+    pub fn push(&mut self, tpf: f32) {
+        self.frame_times.push_back(tpf);
+        self.total_time += tpf;
+        self.trim();
+    }
+
+    // Always keeps the latest frame, even if it alone exceeds the window.
+    fn trim(&mut self) {
+        while self.frame_times.len() > 1 && self.total_time > self.window {
+            if let Some(t) = self.frame_times.pop_front() {
+                self.total_time -= t;
+            }
+        }
+    }
+
+    // Frames per second over the window. Returns 0 until a measurable amount
+    // of time has passed, instead of dividing by a (near) zero first frame.
+    // Until the window is filled, e.g. right after starting, only the frames
+    // so far are averaged.
+    pub fn fps(&self) -> f32 {
+        if self.total_time < 0.001 {
+            return 0.0;
+        }
+        self.frame_times.len() as f32 / self.total_time
+    }
+
+    // Like `fps`, with the rates of the slowest and fastest frame. Frames too
+    // short to measure don't count for the maximum.
+    pub fn stats(&self) -> FpsStats {
+        let rate = |t: f32| if t < 0.001 { 0.0 } else { 1.0 / t };
+        let slowest = self.frame_times.iter().copied().fold(0.0, f32::max);
+        let fastest = self
+            .frame_times
+            .iter()
+            .copied()
+            .filter(|&t| t >= 0.001)
+            .fold(f32::INFINITY, f32::min);
+        FpsStats {
+            avg: self.fps(),
+            min: rate(slowest),
+            max: rate(fastest),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(fps: &FpsCounter, avg: f32, min: f32, max: f32) {
+        let stats = fps.stats();
+        let close = |a: f32, b: f32| (a - b).abs() < 0.01;
+        assert!(
+            close(stats.avg, avg) && close(stats.min, min) && close(stats.max, max),
+            "expected avg {avg}, min {min}, max {max}, got {stats:?}"
+        );
+    }
+
+    // Before a window is filled only the frames so far are averaged, and min
+    // and max come from the slowest and fastest frame in the window.
+    #[test]
+    fn window_averages_frames_in_it() {
+        let mut fps = FpsCounter::new(1.0);
+        assert_close(&fps, 0.0, 0.0, 0.0);
+
+        // Two frames into a 1 s window.
+        fps.push(0.02);
+        fps.push(0.05);
+        assert_close(&fps, 2.0 / 0.07, 20.0, 50.0);
+
+        // A second of 100 fps pushes the slow frames out.
+        for _ in 0..100 {
+            fps.push(0.01);
+        }
+        assert_close(&fps, 100.0, 100.0, 100.0);
+
+        // A spike shows in min, the average barely moves.
+        fps.push(0.1);
+        assert_close(&fps, 91.0, 10.0, 100.0);
+    }
+
+    // A window of 0 gives the rate of the latest frame.
+    #[test]
+    fn zero_window_gives_latest_frame() {
+        let mut fps = FpsCounter::new(1.0);
+        fps.push(0.01);
+        fps.push(0.1);
+        fps.set_window(0.0);
+        assert_close(&fps, 10.0, 10.0, 10.0);
+    }
+}
@@ -0,0 +1,117 @@
+// Timing of the current frame, for animations driven by the total time and
+// for things done every N frames.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameContext {
+    // Seconds since the previous frame, as passed to `CanvasApp::update`.
+    pub tpf: f32,
+    // Seconds since the first frame.
+    pub elapsed: f64,
+    // Frames since start, 0 for the first one.
+    pub frame: u64,
+}
+
+// Builds the `FrameContext` from the `tpf` of each `update` call. Apps keep
+// one and tick it once per frame.
+#[derive(Debug, Default)]
+pub struct FrameClock {
+    last: Option<FrameContext>,
+}
+
+impl FrameClock {
+    pub fn tick(&mut self, tpf: f32) -> FrameContext {
+        let ctx = match self.last {
+            None => FrameContext {
+                tpf,
+                elapsed: 0.0,
+                frame: 0,
+            },
+            Some(last) => FrameContext {
+                tpf,
+                elapsed: last.elapsed + tpf as f64,
+                frame: last.frame + 1,
+            },
+        };
+        self.last = Some(ctx);
+        ctx
+    }
+}
+
+// Caps the frame rate by skipping frames instead of waiting, so it works
+// the same with `requestAnimationFrame` on wasm, which must not be blocked.
+// The `tpf` of every frame accumulates until it reaches the interval, then the
+// frame is rendered and the interval taken off, so a frame coming late makes
+// the next one due earlier and the average interval doesn't drift. Falling
+// behind by more than one interval, e.g. after the window was hidden,
+// restarts it instead of rendering a burst of frames.
+#[derive(Debug)]
+pub struct FrameLimiter {
+    interval: f32,
+    accumulated: f32,
+    // Since the last rendered frame, what that frame advances by.
+    since_rendered: f32,
+}
+
+impl FrameLimiter {
+    pub fn new(max_fps: f32) -> Self {
+        Self {
+            interval: 1.0 / max_fps,
+            accumulated: 0.0,
+            since_rendered: 0.0,
+        }
+    }
+
+    // The seconds to advance by if the frame is due, `None` to skip it. Skipped
+    // frames still have to request the next one, to keep counting.
+    pub fn tick(&mut self, tpf: f32) -> Option<f32> {
+        self.accumulated += tpf;
+        self.since_rendered += tpf;
+        if self.accumulated < self.interval {
+            return None;
+        }
+        self.accumulated -= self.interval;
+        if self.accumulated >= self.interval {
+            self.accumulated = 0.0;
+        }
+        Some(std::mem::take(&mut self.since_rendered))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_frames_and_time_since_first() {
+        let mut clock = FrameClock::default();
+        let frames: Vec<_> = [0.5, 0.25, 0.125].map(|tpf| clock.tick(tpf)).into();
+        assert_eq!(frames.iter().map(|f| f.frame).collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(frames.iter().map(|f| f.elapsed).collect::<Vec<_>>(), [0.0, 0.25, 0.375]);
+        assert_eq!(frames[2].tpf, 0.125);
+    }
+
+    // 144 Hz frames capped to 60 fps: 60 frames are rendered per second, at
+    // intervals of 2 or 3 display frames, advancing by the time since the
+    // last rendered one.
+    #[test]
+    fn limiter_keeps_average_interval() {
+        const REFRESH: f32 = 1.0 / 144.0;
+        let mut limiter = FrameLimiter::new(60.0);
+        let rendered: Vec<f32> = (0..144 * 10).filter_map(|_| limiter.tick(REFRESH)).collect();
+        assert!((rendered.len() as i32 - 600).abs() <= 1, "{} frames", rendered.len());
+        for tpf in &rendered {
+            assert!([2.0, 3.0].iter().any(|n| (tpf - n * REFRESH).abs() < 1e-4), "{tpf}");
+        }
+        let average = rendered.iter().sum::<f32>() / rendered.len() as f32;
+        assert!((average - 1.0 / 60.0).abs() < 1e-4, "{average}");
+    }
+
+    #[test]
+    fn limiter_restarts_when_far_behind() {
+        let mut limiter = FrameLimiter::new(4.0);
+        assert_eq!(limiter.tick(0.125), None);
+        assert_eq!(limiter.tick(2.0), Some(2.125));
+        // No burst of frames catching up.
+        assert_eq!(limiter.tick(0.125), None);
+        assert_eq!(limiter.tick(0.125), Some(0.25));
+    }
+}
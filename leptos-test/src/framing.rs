@@ -0,0 +1,78 @@
+use crate::bounds::Bounds;
+use trivalibs::prelude::*;
+
+// Distance to the bounding sphere relative to the closest one at which it
+// fills the view, so the object keeps a small border.
+pub const FRAME_MARGIN: f32 = 1.1;
+
+// Distance from the center of a sphere of `radius` at which it fits into the
+// view, touching the narrower side, before the margin. `fov` is vertical, in
+// radians, like `Mat4::perspective_rh`.
+pub fn framing_distance(radius: f32, fov: f32, aspect_ratio: f32) -> f32 {
+    let half_fov_y = fov * 0.5;
+    let half_fov_x = ((fov * 0.5).tan() * aspect_ratio).atan();
+    radius / half_fov_y.min(half_fov_x).sin()
+}
+
+// The view projection of a camera looking at the center of the bounds from
+// the direction given by `yaw` and `pitch`, like `Orbit`, far enough away to
+// frame the whole bounding sphere with `FRAME_MARGIN`. Near and far planes are
+// fitted to the sphere, so the depth precision is spent on the object.
+pub fn look_at_bounds(
+    bounds: &Bounds,
+    fov: f32,
+    aspect_ratio: f32,
+    yaw: f32,
+    pitch: f32,
+) -> Mat4 {
+    let sphere = bounds.sphere;
+    let radius = sphere.radius.max(f32::EPSILON);
+    let distance = framing_distance(radius, fov, aspect_ratio) * FRAME_MARGIN;
+    let dir = vec3(yaw.sin() * pitch.cos(), pitch.sin(), yaw.cos() * pitch.cos());
+
+    let view = Mat4::look_at_rh(sphere.center + dir * distance, sphere.center, Vec3::Y);
+    let near = (distance - radius) * 0.5;
+    let far = (distance + radius) * 2.0;
+    Mat4::perspective_rh(fov, aspect_ratio, near, far) * view
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::cube;
+
+    // From several directions and aspect ratios, every corner of a cube
+    // projects inside the view and between the near and far planes, and the
+    // cube fills most of the view, leaving only a small margin.
+    #[test]
+    fn look_at_bounds_frames_cube() {
+        const FOV: f32 = 0.8;
+        // Largest corner coordinate in normalized device coordinates, over x
+        // and y. Below 1 the cube is inside, above the lower limit it isn't
+        // lost in the middle of the view.
+        const MIN_EXTENT: f32 = 0.5;
+        const MAX_EXTENT: f32 = 0.95;
+        const VIEWS: [(f32, f32, f32); 5] = [
+            // yaw, pitch, aspect ratio
+            (0.0, 0.0, 1.0),
+            (0.7, 0.4, 1.5),
+            (-2.0, -0.6, 0.6),
+            (3.0, 1.2, 2.0),
+            (1.0, 0.0, 0.3),
+        ];
+
+        let (positions, _) = cube(1.0);
+        // Off the origin, so framing has to aim at the center.
+        let positions: Vec<Vec3> = positions.iter().map(|&p| p + vec3(2.0, -1.0, 3.0)).collect();
+        let bounds = Bounds::from_points(&positions);
+
+        for (yaw, pitch, aspect_ratio) in VIEWS {
+            let view_proj = look_at_bounds(&bounds, FOV, aspect_ratio, yaw, pitch);
+            let ndc: Vec<Vec3> = positions.iter().map(|&p| view_proj.project_point3(p)).collect();
+            let extent = ndc.iter().map(|p| p.x.abs().max(p.y.abs())).fold(0.0, f32::max);
+            let view = format!("yaw {yaw}, pitch {pitch}, aspect {aspect_ratio}");
+            assert!((MIN_EXTENT..=MAX_EXTENT).contains(&extent), "{view}: extent {extent}");
+            assert!(ndc.iter().all(|p| (0.0..=1.0).contains(&p.z)), "{view}: depth {ndc:?}");
+        }
+    }
+}
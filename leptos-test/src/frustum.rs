@@ -0,0 +1,73 @@
+use crate::bounds::BoundingSphere;
+use trivalibs::prelude::*;
+
+// The six planes of a camera's view volume in world space, each as
+// `normal.xyz, distance` with the normal pointing inwards and normalized.
+//
+// Extracted from the rows of the view projection matrix (Gribb & Hartmann),
+// for wgpu's clip space depth range of 0 to 1. Works for perspective and
+// orthographic projections alike.
+#[derive(Debug, Clone, Copy)]
+pub struct Frustum {
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    pub fn from_view_proj(view_proj: Mat4) -> Self {
+        let [x, y, z, w] = [0, 1, 2, 3].map(|i| view_proj.row(i));
+        let planes = [w + x, w - x, w + y, w - y, z, w - z]
+            .map(|plane| plane / plane.truncate().length());
+        Self { planes }
+    }
+
+    // Whether any part of the sphere may be inside. Spheres near the corners
+    // outside of two planes at once can pass, which only costs a draw call.
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(sphere.center) + plane.w >= -sphere.radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::cube;
+
+    // A cube in front of the camera passes, is culled once moved out of view
+    // to the side, behind the camera or beyond the far plane, and passes again
+    // when moved back.
+    #[test]
+    fn culls_cube_moved_out_of_view() {
+        const CAM_DISTANCE: f32 = 20.0;
+        const FAR: f32 = 100.0;
+        // Cube positions in order, each with whether the cube is in view there.
+        const MOVES: [(Vec3, bool); 6] = [
+            (vec3(0.0, 0.0, 0.0), true),
+            (vec3(100.0, 0.0, 0.0), false),
+            (vec3(0.0, 0.0, 0.0), true),
+            (vec3(0.0, 0.0, 40.0), false),
+            (vec3(0.0, -3.0, -200.0), false),
+            (vec3(3.0, 2.0, -10.0), true),
+        ];
+
+        let (positions, _) = cube(2.0);
+        let bounds = BoundingSphere::from_points(&positions);
+
+        let view = Mat4::look_at_rh(vec3(0.0, 0.0, CAM_DISTANCE), Vec3::ZERO, Vec3::Y);
+        let projections = [
+            ("perspective", Mat4::perspective_rh(0.8, 1.5, 0.1, FAR)),
+            (
+                "orthographic",
+                Mat4::orthographic_rh(-12.0, 12.0, -8.0, 8.0, 0.1, FAR),
+            ),
+        ];
+        for (name, proj) in projections {
+            let frustum = Frustum::from_view_proj(proj * view);
+            for (pos, in_view) in MOVES {
+                let sphere = bounds.transformed(Mat4::from_translation(pos));
+                assert_eq!(frustum.intersects_sphere(&sphere), in_view, "{name}: cube at {pos}");
+            }
+        }
+    }
+}
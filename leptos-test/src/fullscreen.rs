@@ -0,0 +1,22 @@
+use crate::gpu::{shader, Gpu, Mesh, PipelineState, Program, ProgramBuilder, Shader};
+
+// A program covering the whole target, for fragment only effects. Draws a
+// single oversized triangle without vertex buffer, see `fullscreen_mesh`:
+// `fullscreen_vertex` from the shader crate generates positions and uvs from
+// the vertex index, so the program has no vertex attributes.
+pub fn fullscreen_program(gpu: &Gpu, fragment: Shader) -> ProgramBuilder<'_> {
+    Program::build(gpu, shader!("fullscreen_vertex")).with_fragment(fragment)
+}
+
+pub fn fullscreen_mesh() -> Mesh {
+    Mesh::empty(3)
+}
+
+// The triangle is wound either way depending on the target's y direction, so
+// nothing is culled.
+pub fn fullscreen_state(state: PipelineState) -> PipelineState {
+    PipelineState {
+        cull_mode: None,
+        ..state
+    }
+}
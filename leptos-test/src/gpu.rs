@@ -0,0 +1,868 @@
+use crate::vertex_layout::VertexLayout;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::rc::Rc;
+use trivalibs::painter::prelude::*;
+use trivalibs::prelude::*;
+use wgpu::util::DeviceExt;
+
+// Optional features the demos use if the adapter has them, each with a
+// fallback: line polygon mode for the wireframe toggle, timestamp queries for
+// GPU times, shown as "N/A" without, and push constants for model matrices,
+// uniform buffers without. Requesting a feature the adapter lacks fails
+// device creation, so `AppConfig::features` gets these intersected with the
+// adapter's features.
+pub const OPTIONAL_FEATURES: wgpu::Features = wgpu::Features::POLYGON_MODE_LINE
+    .union(wgpu::Features::TIMESTAMP_QUERY)
+    .union(wgpu::Features::PUSH_CONSTANTS);
+
+// The optional features of the default native adapter, which the app runner
+// picks too. See `webgpu::probe_webgpu` for the browser.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn native_features() -> wgpu::Features {
+    let instance = wgpu::Instance::default();
+    match pollster::block_on(instance.request_adapter(&Default::default())) {
+        Ok(adapter) => OPTIONAL_FEATURES & adapter.features(),
+        Err(e) => {
+            log::warn!("No adapter to probe for optional features: {e}");
+            wgpu::Features::empty()
+        }
+    }
+}
+
+// Device and queue of the painter. The demos render with wgpu directly, with
+// the helpers of this module, as the painter's shapes and layers can't express
+// index buffers, depth testing, blending, textures, storage buffers, viewports
+// or push constants. The painter still creates the device and surface and
+// runs the frame loop.
+#[derive(Clone)]
+pub struct Gpu {
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+}
+
+impl Gpu {
+    pub fn new(p: &Painter) -> Self {
+        Self {
+            device: p.device.clone(),
+            queue: p.queue.clone(),
+        }
+    }
+
+    // A device without surface for tests, with those of `features` the
+    // adapter supports. `None` without adapter, e.g. on machines without GPU
+    // or software renderer.
+    #[cfg(all(test, not(target_arch = "wasm32")))]
+    pub fn headless(features: wgpu::Features) -> Option<Self> {
+        let instance = wgpu::Instance::default();
+        let adapter = pollster::block_on(instance.request_adapter(&Default::default())).ok()?;
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("Headless Device"),
+            required_features: features & adapter.features(),
+            // Includes the adapter's `max_push_constant_size`, 0 without
+            // `PUSH_CONSTANTS`.
+            required_limits: adapter.limits(),
+            ..Default::default()
+        }))
+        .ok()?;
+        Some(Self { device, queue })
+    }
+
+    pub fn encoder(&self, label: &str) -> wgpu::CommandEncoder {
+        self.device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some(label) })
+    }
+}
+
+// A SPIR-V module of the shader crate, which builds one module per entry
+// point, named like it. Included with `shader!`.
+#[derive(Clone, Copy)]
+pub struct Shader {
+    pub entry: &'static str,
+    pub spirv: &'static [u8],
+}
+
+// `shader!("vertex")` includes `src/shader/vertex.spv`.
+macro_rules! shader {
+    ($entry:literal) => {
+        $crate::gpu::Shader {
+            entry: $entry,
+            spirv: include_bytes!(concat!("shader/", $entry, ".spv")),
+        }
+    };
+}
+pub(crate) use shader;
+
+impl Shader {
+    pub fn module(&self, gpu: &Gpu) -> wgpu::ShaderModule {
+        gpu.device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(self.entry),
+                source: wgpu::util::make_spirv(self.spirv),
+            })
+    }
+}
+
+// Type and stages of a binding, like the painter's `BINDING_*` constants.
+#[derive(Debug, Clone, Copy)]
+pub struct Slot {
+    visibility: wgpu::ShaderStages,
+    ty: wgpu::BindingType,
+}
+
+const fn buffer(visibility: wgpu::ShaderStages, ty: wgpu::BufferBindingType) -> Slot {
+    Slot {
+        visibility,
+        ty: wgpu::BindingType::Buffer {
+            ty,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+    }
+}
+
+const fn texture(view_dimension: wgpu::TextureViewDimension) -> Slot {
+    Slot {
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension,
+            multisampled: false,
+        },
+    }
+}
+
+const UNIFORM: wgpu::BufferBindingType = wgpu::BufferBindingType::Uniform;
+const STORAGE: wgpu::BufferBindingType = wgpu::BufferBindingType::Storage { read_only: true };
+const STORAGE_RW: wgpu::BufferBindingType =
+    wgpu::BufferBindingType::Storage { read_only: false };
+
+pub const UNIFORM_VERT: Slot = buffer(wgpu::ShaderStages::VERTEX, UNIFORM);
+pub const UNIFORM_FRAG: Slot = buffer(wgpu::ShaderStages::FRAGMENT, UNIFORM);
+pub const UNIFORM_COMPUTE: Slot = buffer(wgpu::ShaderStages::COMPUTE, UNIFORM);
+pub const STORAGE_VERT: Slot = buffer(wgpu::ShaderStages::VERTEX, STORAGE);
+pub const STORAGE_RW_COMPUTE: Slot = buffer(wgpu::ShaderStages::COMPUTE, STORAGE_RW);
+pub const SAMPLER_FRAG: Slot = Slot {
+    visibility: wgpu::ShaderStages::FRAGMENT,
+    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+};
+pub const TEXTURE_FRAG: Slot = texture(wgpu::TextureViewDimension::D2);
+pub const CUBE_TEXTURE_FRAG: Slot = texture(wgpu::TextureViewDimension::Cube);
+
+// Layout of a bind group with the slots at bindings 0, 1, 2, ...
+pub fn bind_group_layout(gpu: &Gpu, slots: &[Slot]) -> wgpu::BindGroupLayout {
+    let entries: Vec<_> = slots
+        .iter()
+        .enumerate()
+        .map(|(i, slot)| wgpu::BindGroupLayoutEntry {
+            binding: i as u32,
+            visibility: slot.visibility,
+            ty: slot.ty,
+            count: None,
+        })
+        .collect();
+    gpu.device
+        .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &entries,
+        })
+}
+
+// Bind group with the resources at bindings 0, 1, 2, ..., in the order of
+// the slots of `layout`.
+pub fn bind_group(
+    gpu: &Gpu,
+    layout: &wgpu::BindGroupLayout,
+    resources: &[wgpu::BindingResource],
+) -> wgpu::BindGroup {
+    let entries: Vec<_> = resources
+        .iter()
+        .enumerate()
+        .map(|(i, resource)| wgpu::BindGroupEntry {
+            binding: i as u32,
+            resource: resource.clone(),
+        })
+        .collect();
+    gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: None,
+        layout,
+        entries: &entries,
+    })
+}
+
+pub fn sampler(gpu: &Gpu, filter: wgpu::FilterMode, address_mode: wgpu::AddressMode) -> wgpu::Sampler {
+    gpu.device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: address_mode,
+        address_mode_v: address_mode,
+        address_mode_w: address_mode,
+        mag_filter: filter,
+        min_filter: filter,
+        ..Default::default()
+    })
+}
+
+// Uniform buffers are rounded up to 16 bytes, the alignment of uniform
+// structs, so scalars and `Vec3`s are bound with the size the shader expects.
+fn uniform_size<T>() -> u64 {
+    (std::mem::size_of::<T>() as u64).div_ceil(16).max(1) * 16
+}
+
+// A uniform buffer holding one `T`, like the painter's `BindingBuffer`. Clones
+// share the buffer.
+pub struct Uniform<T> {
+    buffer: wgpu::Buffer,
+    value: PhantomData<T>,
+}
+
+impl<T> Clone for Uniform<T> {
+    fn clone(&self) -> Self {
+        Self {
+            buffer: self.buffer.clone(),
+            value: PhantomData,
+        }
+    }
+}
+
+impl<T: bytemuck::Pod> Uniform<T> {
+    pub fn new(gpu: &Gpu, value: T) -> Self {
+        let buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: uniform_size::<T>(),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let uniform = Self {
+            buffer,
+            value: PhantomData,
+        };
+        uniform.update(gpu, value);
+        uniform
+    }
+
+    // Written before the next submitted commands run.
+    pub fn update(&self, gpu: &Gpu, value: T) {
+        gpu.queue
+            .write_buffer(&self.buffer, 0, bytemuck::bytes_of(&value));
+    }
+
+    pub fn binding(&self) -> wgpu::BindingResource<'_> {
+        self.buffer.as_entire_binding()
+    }
+}
+
+// Columns of `m` padded to 16 bytes, the layout of a `Mat3A` uniform.
+pub fn mat3_columns(m: Mat3) -> [Vec4; 3] {
+    [m.x_axis, m.y_axis, m.z_axis].map(|c| c.extend(0.0))
+}
+
+// A storage buffer holding a slice of `T`, which can be bound as read-only
+// or read-write storage. Clones share the buffer.
+pub struct Storage<T> {
+    buffer: wgpu::Buffer,
+    len: usize,
+    value: PhantomData<T>,
+}
+
+impl<T> Clone for Storage<T> {
+    fn clone(&self) -> Self {
+        Self {
+            buffer: self.buffer.clone(),
+            len: self.len,
+            value: PhantomData,
+        }
+    }
+}
+
+impl<T: bytemuck::Pod> Storage<T> {
+    pub fn new(gpu: &Gpu, data: &[T]) -> Self {
+        let buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(data),
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            });
+        Self {
+            buffer,
+            len: data.len(),
+            value: PhantomData,
+        }
+    }
+
+    // Overwrites the first `data.len()` elements, which must fit.
+    pub fn update(&self, gpu: &Gpu, data: &[T]) {
+        assert!(data.len() <= self.len, "{} elements exceed {}", data.len(), self.len);
+        gpu.queue
+            .write_buffer(&self.buffer, 0, bytemuck::cast_slice(data));
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn binding(&self) -> wgpu::BindingResource<'_> {
+        self.buffer.as_entire_binding()
+    }
+}
+
+// Vertex and index buffers of a draw, like the painter's forms. Clones share
+// the buffers.
+#[derive(Clone)]
+pub struct Mesh {
+    vertex_buffers: Vec<wgpu::Buffer>,
+    vertex_count: u32,
+    indices: Option<(wgpu::Buffer, u32)>,
+}
+
+fn vertex_buffer<T: bytemuck::Pod>(gpu: &Gpu, vertices: &[T]) -> wgpu::Buffer {
+    gpu.device
+        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Vertex Buffer"),
+            contents: bytemuck::cast_slice(vertices),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        })
+}
+
+impl Mesh {
+    pub fn new<T: bytemuck::Pod>(gpu: &Gpu, vertices: &[T]) -> Self {
+        Self {
+            vertex_buffers: vec![vertex_buffer(gpu, vertices)],
+            vertex_count: vertices.len() as u32,
+            indices: None,
+        }
+    }
+
+    // Without vertex buffers, for shaders generating their vertices from the
+    // vertex index, like `fullscreen_vertex`.
+    pub fn empty(vertex_count: u32) -> Self {
+        Self {
+            vertex_buffers: Vec::new(),
+            vertex_count,
+            indices: None,
+        }
+    }
+
+    // The buffer for the next vertex buffer slot, with the same number of
+    // vertices as the first.
+    pub fn with_vertex_buffer<T: bytemuck::Pod>(mut self, gpu: &Gpu, vertices: &[T]) -> Self {
+        assert_eq!(vertices.len() as u32, self.vertex_count, "vertex count mismatch");
+        self.vertex_buffers.push(vertex_buffer(gpu, vertices));
+        self
+    }
+
+    // Draws with `draw_indexed`, the indices referring to the vertices.
+    pub fn with_indices(mut self, gpu: &Gpu, indices: &[u32]) -> Self {
+        let buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Index Buffer"),
+                contents: bytemuck::cast_slice(indices),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+        self.indices = Some((buffer, indices.len() as u32));
+        self
+    }
+
+    // Replaces the vertices of the first buffer. Fewer vertices are written
+    // into the existing buffer, more into a new one, which other clones of
+    // the mesh don't see.
+    pub fn update_vertices<T: bytemuck::Pod>(&mut self, gpu: &Gpu, vertices: &[T]) {
+        let bytes: &[u8] = bytemuck::cast_slice(vertices);
+        match self.vertex_buffers.first() {
+            Some(buffer) if bytes.len() as u64 <= buffer.size() => {
+                gpu.queue.write_buffer(buffer, 0, bytes);
+            }
+            _ => {
+                let buffer = vertex_buffer(gpu, vertices);
+                if self.vertex_buffers.is_empty() {
+                    self.vertex_buffers.push(buffer);
+                } else {
+                    self.vertex_buffers[0] = buffer;
+                }
+            }
+        }
+        self.vertex_count = vertices.len() as u32;
+    }
+
+    // The number of vertices drawn, the index count for indexed meshes.
+    pub fn count(&self) -> u32 {
+        self.indices
+            .as_ref()
+            .map_or(self.vertex_count, |(_, count)| *count)
+    }
+
+    pub fn draw(&self, pass: &mut wgpu::RenderPass, instances: Range<u32>) {
+        if self.count() == 0 {
+            return;
+        }
+        for (slot, buffer) in self.vertex_buffers.iter().enumerate() {
+            pass.set_vertex_buffer(slot as u32, buffer.slice(..));
+        }
+        match &self.indices {
+            Some((buffer, count)) => {
+                pass.set_index_buffer(buffer.slice(..), wgpu::IndexFormat::Uint32);
+                pass.draw_indexed(0..*count, 0, instances);
+            }
+            None => pass.draw(0..self.vertex_count, instances),
+        }
+    }
+}
+
+// Formats and fixed function state of a render pipeline. Usually taken from
+// `Target::pipeline_state`, which fills in the formats of the target, with
+// the other fields changed as needed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PipelineState {
+    // `None` for depth only pipelines.
+    pub format: Option<wgpu::TextureFormat>,
+    pub sample_count: u32,
+    // Depth testing and writing, if set.
+    pub depth_format: Option<wgpu::TextureFormat>,
+    pub depth_write: bool,
+    pub depth_compare: wgpu::CompareFunction,
+    pub depth_bias: wgpu::DepthBiasState,
+    pub topology: wgpu::PrimitiveTopology,
+    pub cull_mode: Option<wgpu::Face>,
+    pub front_face: wgpu::FrontFace,
+    pub polygon_mode: wgpu::PolygonMode,
+    // Replaces the target's color if `None`.
+    pub blend: Option<wgpu::BlendState>,
+}
+
+impl PipelineState {
+    // Like the painter's shapes: triangle lists, culling back faces, which
+    // are wound clockwise, without blending and depth.
+    pub fn new(format: Option<wgpu::TextureFormat>) -> Self {
+        Self {
+            format,
+            sample_count: 1,
+            depth_format: None,
+            depth_write: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            depth_bias: wgpu::DepthBiasState::default(),
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            cull_mode: Some(wgpu::Face::Back),
+            front_face: wgpu::FrontFace::Ccw,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            blend: None,
+        }
+    }
+}
+
+// Shaders with the layouts of their inputs, like the painter's shades.
+// Render pipelines are created per `PipelineState` when first used and kept,
+// shared by all clones.
+#[derive(Clone)]
+pub struct Program {
+    vertex: (wgpu::ShaderModule, &'static str),
+    fragment: Option<(wgpu::ShaderModule, &'static str)>,
+    vertex_layouts: Vec<VertexLayout>,
+    bind_group_layouts: Vec<wgpu::BindGroupLayout>,
+    layout: wgpu::PipelineLayout,
+    pipelines: Rc<RefCell<Vec<(PipelineState, wgpu::RenderPipeline)>>>,
+}
+
+pub struct ProgramBuilder<'a> {
+    gpu: &'a Gpu,
+    vertex: Shader,
+    fragment: Option<Shader>,
+    vertex_layouts: Vec<VertexLayout>,
+    bind_groups: Vec<Vec<Slot>>,
+    push_constants: Vec<wgpu::PushConstantRange>,
+}
+
+impl Program {
+    pub fn build(gpu: &Gpu, vertex: Shader) -> ProgramBuilder<'_> {
+        ProgramBuilder {
+            gpu,
+            vertex,
+            fragment: None,
+            vertex_layouts: Vec::new(),
+            bind_groups: Vec::new(),
+            push_constants: Vec::new(),
+        }
+    }
+
+    // Bind group of descriptor set `set` with the resources at bindings 0, 1,
+    // 2, ...
+    pub fn bind_group(
+        &self,
+        gpu: &Gpu,
+        set: usize,
+        resources: &[wgpu::BindingResource],
+    ) -> wgpu::BindGroup {
+        bind_group(gpu, &self.bind_group_layouts[set], resources)
+    }
+
+    pub fn pipeline(&self, gpu: &Gpu, state: PipelineState) -> wgpu::RenderPipeline {
+        let mut pipelines = self.pipelines.borrow_mut();
+        if let Some((_, pipeline)) = pipelines.iter().find(|(s, _)| *s == state) {
+            return pipeline.clone();
+        }
+        let buffers: Vec<_> = self
+            .vertex_layouts
+            .iter()
+            .map(|layout| layout.buffer_layout().expect("invalid vertex layout"))
+            .collect();
+        let targets = [state.format.map(|format| wgpu::ColorTargetState {
+            format,
+            blend: state.blend,
+            write_mask: wgpu::ColorWrites::ALL,
+        })];
+        let pipeline = gpu
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(self.vertex.1),
+                layout: Some(&self.layout),
+                vertex: wgpu::VertexState {
+                    module: &self.vertex.0,
+                    entry_point: Some(self.vertex.1),
+                    compilation_options: Default::default(),
+                    buffers: &buffers,
+                },
+                fragment: self.fragment.as_ref().map(|(module, entry)| wgpu::FragmentState {
+                    module,
+                    entry_point: Some(entry),
+                    compilation_options: Default::default(),
+                    targets: if state.format.is_some() { &targets } else { &[] },
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: state.topology,
+                    front_face: state.front_face,
+                    cull_mode: state.cull_mode,
+                    polygon_mode: state.polygon_mode,
+                    ..Default::default()
+                },
+                depth_stencil: state.depth_format.map(|format| wgpu::DepthStencilState {
+                    format,
+                    depth_write_enabled: state.depth_write,
+                    depth_compare: state.depth_compare,
+                    stencil: Default::default(),
+                    bias: state.depth_bias,
+                }),
+                multisample: wgpu::MultisampleState {
+                    count: state.sample_count,
+                    ..Default::default()
+                },
+                multiview: None,
+                cache: None,
+            });
+        pipelines.push((state, pipeline.clone()));
+        pipeline
+    }
+}
+
+impl ProgramBuilder<'_> {
+    pub fn with_fragment(mut self, fragment: Shader) -> Self {
+        self.fragment = Some(fragment);
+        self
+    }
+
+    // A vertex buffer with tightly packed attributes, at the shader locations
+    // following those of the previous buffers.
+    pub fn with_vertex(self, formats: &[wgpu::VertexFormat]) -> Self {
+        let location = self.next_location();
+        self.with_vertex_layout(VertexLayout::packed(formats, location))
+    }
+
+    // A vertex buffer with explicit attribute offsets, see `VertexLayout`.
+    pub fn with_vertex_layout(mut self, layout: VertexLayout) -> Self {
+        self.vertex_layouts.push(layout);
+        self
+    }
+
+    fn next_location(&self) -> u32 {
+        self.vertex_layouts
+            .iter()
+            .filter_map(VertexLayout::last_location)
+            .max()
+            .map_or(0, |l| l + 1)
+    }
+
+    // Each call adds the bind group of the next descriptor set, with the
+    // slots at bindings 0, 1, 2, ...
+    pub fn with_bindings(mut self, slots: &[Slot]) -> Self {
+        self.bind_groups.push(slots.to_vec());
+        self
+    }
+
+    // Push constants of `size` bytes from offset 0, set per draw with
+    // `DrawCall::set_push_constants`. Needs the `PUSH_CONSTANTS` feature and
+    // a `max_push_constant_size` limit of at least `size`.
+    pub fn with_push_constants(mut self, stages: wgpu::ShaderStages, size: u32) -> Self {
+        self.push_constants.push(wgpu::PushConstantRange {
+            stages,
+            range: 0..size,
+        });
+        self
+    }
+
+    pub fn create(self) -> Program {
+        let gpu = self.gpu;
+        let bind_group_layouts: Vec<_> = self
+            .bind_groups
+            .iter()
+            .map(|slots| bind_group_layout(gpu, slots))
+            .collect();
+        let layout = gpu
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(self.vertex.entry),
+                bind_group_layouts: &bind_group_layouts.iter().collect::<Vec<_>>(),
+                push_constant_ranges: &self.push_constants,
+            });
+        Program {
+            vertex: (self.vertex.module(gpu), self.vertex.entry),
+            fragment: self.fragment.map(|f| (f.module(gpu), f.entry)),
+            vertex_layouts: self.vertex_layouts,
+            bind_group_layouts,
+            layout,
+            pipelines: Rc::default(),
+        }
+    }
+}
+
+// A rect of the target in physical pixels, origin top left.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+// A mesh drawn with a program, its bind groups and pipeline state, like the
+// painter's shapes. Changing the state switches to the program's pipeline for
+// it, the mesh and bind groups are kept.
+#[derive(Clone)]
+pub struct DrawCall {
+    program: Program,
+    state: PipelineState,
+    pipeline: wgpu::RenderPipeline,
+    mesh: Mesh,
+    bind_groups: Vec<wgpu::BindGroup>,
+    instances: u32,
+    viewport: Option<Viewport>,
+    push_constants: Option<(wgpu::ShaderStages, Vec<u8>)>,
+}
+
+impl DrawCall {
+    // `bind_groups` in the order of the program's descriptor sets.
+    pub fn new(
+        gpu: &Gpu,
+        program: &Program,
+        mesh: &Mesh,
+        bind_groups: Vec<wgpu::BindGroup>,
+        state: PipelineState,
+    ) -> Self {
+        Self {
+            program: program.clone(),
+            state,
+            pipeline: program.pipeline(gpu, state),
+            mesh: mesh.clone(),
+            bind_groups,
+            instances: 1,
+            viewport: None,
+            push_constants: None,
+        }
+    }
+
+    // Draws the mesh `count` times in one call, for shaders reading per
+    // instance data with the instance index.
+    pub fn with_instances(mut self, count: u32) -> Self {
+        self.instances = count;
+        self
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    pub fn state(&self) -> PipelineState {
+        self.state
+    }
+
+    pub fn set_state(&mut self, gpu: &Gpu, state: PipelineState) {
+        if state != self.state {
+            self.state = state;
+            self.pipeline = self.program.pipeline(gpu, state);
+        }
+    }
+
+    pub fn mesh(&self) -> &Mesh {
+        &self.mesh
+    }
+
+    // The mesh needs the vertex layouts of the program.
+    pub fn set_mesh(&mut self, mesh: &Mesh) {
+        self.mesh = mesh.clone();
+    }
+
+    pub fn set_bind_group(&mut self, set: usize, bind_group: wgpu::BindGroup) {
+        self.bind_groups[set] = bind_group;
+    }
+
+    pub fn set_instances(&mut self, count: u32) {
+        self.instances = count;
+    }
+
+    // Restricts drawing to a rect of the target, with a scissor rect of the
+    // same size. `None` draws to the whole target.
+    pub fn set_viewport(&mut self, viewport: Option<Viewport>) {
+        self.viewport = viewport;
+    }
+
+    // Recorded with the draw, see `ProgramBuilder::with_push_constants`.
+    pub fn set_push_constants(&mut self, stages: wgpu::ShaderStages, data: &[u8]) {
+        self.push_constants = Some((stages, data.to_vec()));
+    }
+
+    // What `draw` records, nothing for an empty mesh. Viewports outside the
+    // target, which skip the draw, are still counted.
+    pub fn stats(&self) -> RenderStats {
+        let count = self.mesh.count();
+        if count == 0 {
+            return RenderStats::default();
+        }
+        RenderStats {
+            draw_calls: 1,
+            triangles: triangles(self.state.topology, count) * self.instances,
+            bind_groups: self.bind_groups.len() as u32,
+        }
+    }
+
+    // Draws into a pass of a target with the formats of the state. `size` is
+    // the target size, to which viewports are clamped.
+    pub fn draw(&self, pass: &mut wgpu::RenderPass, size: (u32, u32)) {
+        let (width, height) = (size.0 as f32, size.1 as f32);
+        let rect = self.viewport.map_or((0.0, 0.0, width, height), |v| {
+            let x = v.x.clamp(0.0, width);
+            let y = v.y.clamp(0.0, height);
+            (x, y, v.width.min(width - x), v.height.min(height - y))
+        });
+        if rect.2 <= 0.0 || rect.3 <= 0.0 {
+            return;
+        }
+        pass.set_viewport(rect.0, rect.1, rect.2, rect.3, 0.0, 1.0);
+        pass.set_scissor_rect(rect.0 as u32, rect.1 as u32, rect.2 as u32, rect.3 as u32);
+        pass.set_pipeline(&self.pipeline);
+        for (set, bind_group) in self.bind_groups.iter().enumerate() {
+            pass.set_bind_group(set as u32, bind_group, &[]);
+        }
+        if let Some((stages, data)) = &self.push_constants {
+            pass.set_push_constants(*stages, 0, data);
+        }
+        self.mesh.draw(pass, 0..self.instances);
+    }
+}
+
+// Work recorded by draw calls, summed over a frame with `+` or `sum`, see
+// `DrawCall::stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    pub draw_calls: u32,
+    pub triangles: u32,
+    // One per descriptor set of each draw, as every draw sets all of them.
+    pub bind_groups: u32,
+}
+
+impl std::ops::Add for RenderStats {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            draw_calls: self.draw_calls + other.draw_calls,
+            triangles: self.triangles + other.triangles,
+            bind_groups: self.bind_groups + other.bind_groups,
+        }
+    }
+}
+
+impl std::iter::Sum for RenderStats {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |a, b| a + b)
+    }
+}
+
+// Triangles of one instance of `count` vertices, or indices, 0 for lines and
+// points.
+fn triangles(topology: wgpu::PrimitiveTopology, count: u32) -> u32 {
+    match topology {
+        wgpu::PrimitiveTopology::TriangleList => count / 3,
+        wgpu::PrimitiveTopology::TriangleStrip => count.saturating_sub(2),
+        _ => 0,
+    }
+}
+
+// A compute shader with its single bind group, like a draw call for compute
+// passes.
+pub struct Compute {
+    pipeline: wgpu::ComputePipeline,
+    bind_group: wgpu::BindGroup,
+}
+
+impl Compute {
+    // The resources at bindings 0, 1, 2, ... of the slots.
+    pub fn new(
+        gpu: &Gpu,
+        shader: Shader,
+        slots: &[Slot],
+        resources: &[wgpu::BindingResource],
+    ) -> Self {
+        let layout = bind_group_layout(gpu, slots);
+        let pipeline_layout = gpu
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(shader.entry),
+                bind_group_layouts: &[&layout],
+                push_constant_ranges: &[],
+            });
+        let pipeline = gpu
+            .device
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(shader.entry),
+                layout: Some(&pipeline_layout),
+                module: &shader.module(gpu),
+                entry_point: Some(shader.entry),
+                compilation_options: Default::default(),
+                cache: None,
+            });
+        Self {
+            pipeline,
+            bind_group: bind_group(gpu, &layout, resources),
+        }
+    }
+
+    // Records a pass running `workgroups` workgroups along x.
+    pub fn dispatch(&self, encoder: &mut wgpu::CommandEncoder, workgroups: u32) {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Compute Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.dispatch_workgroups(workgroups, 1, 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_triangles_per_topology() {
+        use wgpu::PrimitiveTopology::*;
+        // The 36 indices of a cube.
+        assert_eq!(triangles(TriangleList, 36), 12);
+        // A quad of 4 vertices.
+        assert_eq!(triangles(TriangleStrip, 4), 2);
+        assert_eq!(triangles(TriangleStrip, 1), 0);
+        assert_eq!(triangles(LineList, 24), 0);
+        assert_eq!(triangles(PointList, 100), 0);
+    }
+}
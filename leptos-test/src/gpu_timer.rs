@@ -0,0 +1,144 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use crate::gpu::Gpu;
+
+// Queries written per frame: before the first timed pass and after the last
+// one.
+const QUERY_COUNT: u32 = 2;
+const QUERY_BUFFER_SIZE: u64 = QUERY_COUNT as u64 * wgpu::QUERY_SIZE as u64;
+
+// Measures the GPU time of a frame with timestamp queries, written by empty
+// compute passes submitted before and after the timed passes, so the passes
+// in between don't need to know about the query set. Writing timestamps
+// directly into an encoder would need `TIMESTAMP_QUERY_INSIDE_ENCODERS`,
+// which WebGPU doesn't offer. Needs the optional `TIMESTAMP_QUERY` feature,
+// `new` returns `None` without it.
+//
+// Results arrive a few frames late: after the frame, the queries are resolved
+// into a buffer and copied to a readback buffer, which is mapped
+// asynchronously. While a readback is in flight, further frames are not
+// resolved, so there is a single readback buffer.
+pub struct GpuTimer {
+    queries: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    // Nanoseconds per timestamp tick.
+    period: f32,
+    // Set while the readback buffer is being copied to or mapped.
+    pending: Arc<AtomicBool>,
+    // Set by the `map_async` callback once the buffer can be read.
+    mapped: Arc<AtomicBool>,
+}
+
+impl GpuTimer {
+    pub fn new(gpu: &Gpu) -> Option<Self> {
+        if !gpu.device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            log::info!("GPU timing disabled: TIMESTAMP_QUERY is not supported");
+            return None;
+        }
+        let queries = gpu.device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("GPU Timer Queries"),
+            ty: wgpu::QueryType::Timestamp,
+            count: QUERY_COUNT,
+        });
+        let resolve_buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Timer Resolve Buffer"),
+            size: QUERY_BUFFER_SIZE,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Timer Readback Buffer"),
+            size: QUERY_BUFFER_SIZE,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        Some(Self {
+            queries,
+            resolve_buffer,
+            readback_buffer,
+            period: gpu.queue.get_timestamp_period(),
+            pending: Arc::new(AtomicBool::new(false)),
+            mapped: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    // Call before submitting the first timed pass.
+    pub fn begin(&self, gpu: &Gpu) {
+        self.write_timestamp(gpu, Some(0), None);
+    }
+
+    // Call after submitting the last timed pass.
+    pub fn end(&self, gpu: &Gpu) {
+        self.write_timestamp(gpu, None, Some(1));
+    }
+
+    fn write_timestamp(&self, gpu: &Gpu, begin: Option<u32>, end: Option<u32>) {
+        let mut encoder = gpu.encoder("GPU Timer Timestamp Encoder");
+        encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("GPU Timer Timestamp Pass"),
+            timestamp_writes: Some(wgpu::ComputePassTimestampWrites {
+                query_set: &self.queries,
+                beginning_of_pass_write_index: begin,
+                end_of_pass_write_index: end,
+            }),
+        });
+        gpu.queue.submit([encoder.finish()]);
+    }
+
+    // Resolves the timestamps of the frame just rendered and starts reading
+    // them back. Call after `end`. Skipped while the previous readback is
+    // still in flight.
+    pub fn resolve(&self, gpu: &Gpu) {
+        if self.pending.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let mut encoder = gpu.encoder("GPU Timer Encoder");
+        encoder.resolve_query_set(&self.queries, 0..QUERY_COUNT, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            QUERY_BUFFER_SIZE,
+        );
+        gpu.queue.submit([encoder.finish()]);
+
+        let mapped = self.mapped.clone();
+        let pending = self.pending.clone();
+        self.readback_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| match result {
+                Ok(()) => mapped.store(true, Ordering::Release),
+                Err(e) => {
+                    log::warn!("Failed to read back GPU timestamps: {e}");
+                    pending.store(false, Ordering::Release);
+                }
+            });
+    }
+
+    // The GPU time of the last frame read back, in milliseconds, or `None`
+    // if no new result arrived since the last call.
+    pub fn read(&self, gpu: &Gpu) -> Option<f32> {
+        // Native needs polling for `map_async` callbacks, on wasm the
+        // browser runs them.
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = gpu.device.poll(wgpu::PollType::Poll);
+        #[cfg(target_arch = "wasm32")]
+        let _ = gpu;
+
+        if !self.mapped.swap(false, Ordering::AcqRel) {
+            return None;
+        }
+        let ticks = {
+            let data = self.readback_buffer.slice(..).get_mapped_range();
+            let timestamps: &[u64] = bytemuck::cast_slice(&data);
+            // Timestamps can go backwards on some drivers, e.g. after the GPU
+            // changed its clock, which would underflow.
+            timestamps[1].saturating_sub(timestamps[0])
+        };
+        self.readback_buffer.unmap();
+        self.pending.store(false, Ordering::Release);
+        Some(ticks as f32 * self.period / 1_000_000.0)
+    }
+}
@@ -0,0 +1,88 @@
+use crate::fullscreen::{fullscreen_mesh, fullscreen_program, fullscreen_state};
+use crate::gpu::{shader, DrawCall, Gpu, Uniform, UNIFORM_FRAG};
+use crate::input::{input_from_event, Input};
+use crate::surface::{self, Frame};
+use crate::target::Target;
+use trivalibs::painter::app::Event;
+use trivalibs::painter::prelude::*;
+use trivalibs::prelude::*;
+
+// Shadertoy style fullscreen effect, tinted by the mouse position.
+//
+// The effect is drawn without a vertex buffer: `fullscreen_mesh` only carries
+// a vertex count of 3, and `fullscreen_vertex` derives the clip space corners
+// of one oversized triangle from `vertex_index`. The fragment shader derives
+// its uv from `frag_coord` and the resolution uniform, so it doesn't depend
+// on the vertex shader. `gradient_vertex` draws the same effect as a quad of
+// two triangles, with a vertex count of 6.
+pub struct GradientApp {
+    gpu: Gpu,
+    size: Vec2,
+    resolution: Uniform<Vec2>,
+    mouse: Uniform<Vec2>,
+
+    canvas: Target,
+    draw: DrawCall,
+}
+
+impl CanvasApp<()> for GradientApp {
+    fn init(p: &mut Painter) -> Self {
+        let gpu = Gpu::new(p);
+        let program = fullscreen_program(&gpu, shader!("gradient_fragment"))
+            .with_bindings(&[UNIFORM_FRAG, UNIFORM_FRAG])
+            .create();
+
+        let resolution = Uniform::new(&gpu, Vec2::ONE);
+        let mouse = Uniform::new(&gpu, vec2(0.5, 0.5));
+
+        let canvas = Target::canvas(surface::view_format(p)).create(&gpu, 1, 1);
+        let bind_group = program.bind_group(&gpu, 0, &[resolution.binding(), mouse.binding()]);
+        let draw = DrawCall::new(
+            &gpu,
+            &program,
+            &fullscreen_mesh(),
+            vec![bind_group],
+            fullscreen_state(canvas.pipeline_state()),
+        );
+
+        Self {
+            gpu,
+            size: Vec2::ONE,
+            resolution,
+            mouse,
+
+            canvas,
+            draw,
+        }
+    }
+
+    fn resize(&mut self, _p: &mut Painter, width: u32, height: u32) {
+        self.size = vec2(width as f32, height as f32);
+        self.resolution.update(&self.gpu, self.size);
+        self.canvas.resize(&self.gpu, width, height);
+    }
+
+    fn update(&mut self, _p: &mut Painter, _tpf: f32) {}
+
+    fn render(&self, p: &mut Painter) -> Result<(), SurfaceError> {
+        let Some(frame) = Frame::acquire(p)? else {
+            return Ok(());
+        };
+        let mut encoder = self.gpu.encoder("Gradient Encoder");
+        self.draw.draw(
+            &mut self.canvas.pass(&mut encoder, Some(&frame.view)),
+            self.canvas.size(),
+        );
+        self.gpu.queue.submit([encoder.finish()]);
+        frame.present();
+        Ok(())
+    }
+
+    fn event(&mut self, e: Event<()>, p: &mut Painter) {
+        if let Some(Input::MouseMove(pos)) = input_from_event(&e) {
+            self.mouse
+                .update(&self.gpu, (pos / self.size).clamp(Vec2::ZERO, Vec2::ONE));
+            p.request_next_frame();
+        }
+    }
+}
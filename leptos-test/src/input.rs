@@ -0,0 +1,82 @@
+use trivalibs::painter::app::Event;
+use trivalibs::prelude::*;
+pub use winit::event::MouseButton;
+use winit::event::{ElementState, KeyEvent, MouseScrollDelta, WindowEvent};
+pub use winit::keyboard::KeyCode;
+use winit::keyboard::PhysicalKey;
+
+// Approximate pixel height of one scroll line, used to bring line based mouse
+// wheels and pixel based trackpads into the same unit.
+const SCROLL_LINE_HEIGHT: f32 = 20.0;
+
+// Input events extracted from the raw winit window events that the painter
+// forwards through `CanvasApp::event`.
+//
+// Keys are reported by physical position (`KeyCode`), so WASD stays on the same
+// keys regardless of keyboard layout. On native every key is delivered. On wasm
+// the canvas only receives keyboard events while it has focus (it needs a
+// `tabindex`), and keys the browser reserves (Tab, F5, Ctrl+W, ...) never
+// arrive. Arrow keys and letters are delivered on both targets.
+//
+// Mouse positions are in physical pixels relative to the top left of the
+// canvas/window.
+//
+// Touch events are only forwarded on wasm, where they drive the camera on
+// mobile browsers. Positions are in physical pixels like mouse positions.
+//
+// Scroll deltas are in pixels, positive when scrolling up/away from the user.
+// Browsers report trackpad pinch gestures as wheel events with the ctrl key
+// held, so pinching also arrives as `Scroll` on wasm.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Input {
+    KeyDown(KeyCode),
+    KeyUp(KeyCode),
+    MouseDown(MouseButton),
+    MouseUp(MouseButton),
+    MouseMove(Vec2),
+    Scroll(f32),
+    TouchStart(u64, Vec2),
+    TouchMove(u64, Vec2),
+    TouchEnd(u64),
+}
+
+pub fn input_from_event<U>(e: &Event<U>) -> Option<Input> {
+    match e {
+        Event::WindowEvent(WindowEvent::KeyboardInput {
+            event:
+                KeyEvent {
+                    physical_key: PhysicalKey::Code(code),
+                    state,
+                    ..
+                },
+            ..
+        }) => Some(match state {
+            ElementState::Pressed => Input::KeyDown(*code),
+            ElementState::Released => Input::KeyUp(*code),
+        }),
+        Event::WindowEvent(WindowEvent::MouseInput { state, button, .. }) => Some(match state {
+            ElementState::Pressed => Input::MouseDown(*button),
+            ElementState::Released => Input::MouseUp(*button),
+        }),
+        Event::WindowEvent(WindowEvent::CursorMoved { position, .. }) => {
+            Some(Input::MouseMove(vec2(position.x as f32, position.y as f32)))
+        }
+        Event::WindowEvent(WindowEvent::MouseWheel { delta, .. }) => {
+            Some(Input::Scroll(match delta {
+                MouseScrollDelta::LineDelta(_, y) => *y * SCROLL_LINE_HEIGHT,
+                MouseScrollDelta::PixelDelta(pos) => pos.y as f32,
+            }))
+        }
+        #[cfg(target_arch = "wasm32")]
+        Event::WindowEvent(WindowEvent::Touch(touch)) => {
+            use winit::event::TouchPhase;
+            let pos = vec2(touch.location.x as f32, touch.location.y as f32);
+            Some(match touch.phase {
+                TouchPhase::Started => Input::TouchStart(touch.id, pos),
+                TouchPhase::Moved => Input::TouchMove(touch.id, pos),
+                TouchPhase::Ended | TouchPhase::Cancelled => Input::TouchEnd(touch.id),
+            })
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,161 @@
+use crate::gpu::{shader, DrawCall, Gpu, Mesh, PipelineState, Program, Storage, Uniform};
+use crate::gpu::{STORAGE_VERT, UNIFORM_VERT};
+use crate::msaa::{msaa_samples, MSAA_SAMPLES};
+use crate::surface::{self, Frame};
+use crate::target::Target;
+use crate::util::SplitMix64;
+use std::f32::consts::TAU;
+use trivalibs::math::transform::Transform;
+use trivalibs::painter::app::Event;
+use trivalibs::painter::prelude::*;
+use trivalibs::prelude::*;
+use trivalibs::rendering::camera::{CamProps, PerspectiveCamera};
+
+const VERTICES: &[Vec3] = &[vec3(0.0, 5.0, 0.0), vec3(-2.5, 0., 0.0), vec3(2.5, 0., 0.0)];
+
+const INSTANCE_COUNT: usize = 10_000;
+// Half the side of the square the instances are scattered over, and half its
+// depth.
+const SCATTER_EXTENT: f32 = 75.0;
+const SCATTER_DEPTH: f32 = 20.0;
+const INSTANCE_SCALE: (f32, f32) = (0.1, 0.3);
+const SPIN_SPEED: (f32, f32) = (0.5, 2.5);
+
+pub const DEFAULT_SEED: u64 = 1;
+
+// Renders randomly scattered triangles in a single draw call. The model matrices of
+// all instances live in one read-only storage buffer, which the vertex shader
+// indexes with the instance index. Unlike a uniform array, its size is not
+// fixed in the shader, and it holds far more than the 64 KiB uniform limit.
+//
+// The layout comes from a seeded generator, so the same seed gives the same
+// scatter everywhere.
+pub struct InstancedApp {
+    gpu: Gpu,
+    cam: PerspectiveCamera,
+    transforms: Vec<Transform>,
+    spin_speeds: Vec<f32>,
+    model_mats: Storage<Mat4>,
+    vp_mat: Uniform<Mat4>,
+
+    canvas: Target,
+    draw: DrawCall,
+}
+
+impl CanvasApp<()> for InstancedApp {
+    fn init(p: &mut Painter) -> Self {
+        let gpu = Gpu::new(p);
+        let program = Program::build(&gpu, shader!("instanced_vertex"))
+            .with_fragment(shader!("instanced_fragment"))
+            .with_vertex(&[Float32x3])
+            .with_bindings(&[UNIFORM_VERT, STORAGE_VERT, UNIFORM_VERT])
+            .create();
+
+        let mesh = Mesh::new(&gpu, VERTICES);
+
+        let vp_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let model_mats = Storage::new(&gpu, &[Mat4::IDENTITY; INSTANCE_COUNT]);
+        let instance_count = Uniform::new(&gpu, INSTANCE_COUNT as u32);
+
+        let canvas = Target::canvas(surface::view_format(p))
+            .with_samples(msaa_samples(p, MSAA_SAMPLES))
+            .create(&gpu, 1, 1);
+        let bind_group = program.bind_group(
+            &gpu,
+            0,
+            &[
+                vp_mat.binding(),
+                model_mats.binding(),
+                instance_count.binding(),
+            ],
+        );
+        let draw = DrawCall::new(
+            &gpu,
+            &program,
+            &mesh,
+            vec![bind_group],
+            PipelineState {
+                cull_mode: None,
+                ..canvas.pipeline_state()
+            },
+        )
+        .with_instances(INSTANCE_COUNT as u32);
+
+        let (transforms, spin_speeds) = scatter(DEFAULT_SEED);
+
+        Self {
+            gpu,
+            cam: PerspectiveCamera::create(CamProps {
+                fov: Some(0.6),
+                translation: Some(vec3(0.0, 0.0, 250.0)),
+                ..default()
+            }),
+            transforms,
+            spin_speeds,
+            model_mats,
+            vp_mat,
+
+            canvas,
+            draw,
+        }
+    }
+
+    fn resize(&mut self, _p: &mut Painter, width: u32, height: u32) {
+        self.cam.set_aspect_ratio(width as f32 / height as f32);
+        self.canvas.resize(&self.gpu, width, height);
+
+        self.vp_mat.update(&self.gpu, self.cam.view_proj_mat());
+    }
+
+    fn update(&mut self, p: &mut Painter, tpf: f32) {
+        for (t, speed) in self.transforms.iter_mut().zip(&self.spin_speeds) {
+            t.rotate_y(tpf * speed);
+        }
+        let mats: Vec<Mat4> = self.transforms.iter().map(|t| t.model_mat()).collect();
+        self.model_mats.update(&self.gpu, &mats);
+
+        p.request_next_frame();
+    }
+
+    fn render(&self, p: &mut Painter) -> Result<(), SurfaceError> {
+        let Some(frame) = Frame::acquire(p)? else {
+            return Ok(());
+        };
+        let mut encoder = self.gpu.encoder("Instanced Encoder");
+        self.draw.draw(
+            &mut self.canvas.pass(&mut encoder, Some(&frame.view)),
+            self.canvas.size(),
+        );
+        self.gpu.queue.submit([encoder.finish()]);
+        frame.present();
+        Ok(())
+    }
+
+    fn event(&mut self, _e: Event<()>, _p: &mut Painter) {}
+}
+
+impl InstancedApp {
+    // Replaces the layout with the one generated from `seed`.
+    pub fn set_seed(&mut self, seed: u64) {
+        (self.transforms, self.spin_speeds) = scatter(seed);
+    }
+}
+
+// Transforms and spin speeds of all instances for `seed`. The values are drawn
+// in a fixed order, so changing this function changes the layout of every
+// seed.
+fn scatter(seed: u64) -> (Vec<Transform>, Vec<f32>) {
+    let mut rng = SplitMix64::new(seed);
+    (0..INSTANCE_COUNT)
+        .map(|_| {
+            let x = rng.range(-SCATTER_EXTENT, SCATTER_EXTENT);
+            let y = rng.range(-SCATTER_EXTENT, SCATTER_EXTENT);
+            let z = rng.range(-SCATTER_DEPTH, SCATTER_DEPTH);
+            let scale = rng.range(INSTANCE_SCALE.0, INSTANCE_SCALE.1);
+            let mut t = Transform::from_translation(vec3(x, y, z)).with_scale(Vec3::splat(scale));
+            t.rotate_x(rng.range(0.0, TAU));
+            t.rotate_y(rng.range(0.0, TAU));
+            (t, rng.range(SPIN_SPEED.0, SPIN_SPEED.1))
+        })
+        .unzip()
+}
@@ -0,0 +1,180 @@
+use crate::gpu::{mat3_columns, sampler, shader, DrawCall, Gpu, Mesh, Program, Uniform};
+use crate::gpu::{SAMPLER_FRAG, TEXTURE_FRAG, UNIFORM_FRAG, UNIFORM_VERT};
+use crate::msaa::{msaa_samples, MSAA_SAMPLES};
+use crate::primitives::cube_with_normals;
+use crate::scene::normal_mat;
+use crate::surface::{self, Frame};
+use crate::target::Target;
+use crate::texture::{decode_png, texture_from_rgba};
+use crate::vertex_layout::VertexLayout;
+use std::mem::offset_of;
+use trivalibs::math::transform::Transform;
+use trivalibs::painter::app::Event;
+use trivalibs::painter::prelude::*;
+use trivalibs::prelude::*;
+use trivalibs::rendering::camera::{CamProps, PerspectiveCamera};
+
+const IMAGE: &[u8] = include_bytes!("../assets/checker.png");
+
+// All attributes of `lit_textured_vertex` in one buffer. The color is packed
+// into 4 bytes, read by the shader as a `Vec4` in [0, 1], so the attributes
+// don't follow from a list of formats alone and the layout is given with
+// explicit offsets.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: Vec3,
+    normal: Vec3,
+    uv: Vec2,
+    color: [u8; 4],
+}
+
+fn vertex_layout() -> VertexLayout {
+    VertexLayout::of::<Vertex>()
+        .with_attribute(Float32x3, offset_of!(Vertex, position) as u64)
+        .with_attribute(Float32x3, offset_of!(Vertex, normal) as u64)
+        .with_attribute(Float32x2, offset_of!(Vertex, uv) as u64)
+        .with_attribute(Unorm8x4, offset_of!(Vertex, color) as u64)
+}
+
+const CUBE_SIZE: f32 = 2.0;
+// Tint of each face, in the order of `cube_with_normals`.
+const FACE_COLORS: [[u8; 4]; 6] = [
+    [255, 120, 120, 255],
+    [120, 255, 120, 255],
+    [120, 120, 255, 255],
+    [255, 255, 120, 255],
+    [120, 255, 255, 255],
+    [255, 120, 255, 255],
+];
+// Per face corner, with the top of the image up on the side faces.
+const FACE_UVS: [Vec2; 4] = [vec2(0.0, 1.0), vec2(1.0, 1.0), vec2(1.0, 0.0), vec2(0.0, 0.0)];
+
+const LIGHT_DIR: Vec3 = vec3(1.0, 1.0, 1.5);
+
+// A lit, textured cube with tinted faces, drawn from a single interleaved
+// vertex buffer described by a `VertexLayout`.
+pub struct InterleavedApp {
+    gpu: Gpu,
+    cam: PerspectiveCamera,
+    transform: Transform,
+    model_mat: Uniform<Mat4>,
+    normal_mat: Uniform<[Vec4; 3]>,
+    vp_mat: Uniform<Mat4>,
+
+    canvas: Target,
+    draw: DrawCall,
+}
+
+impl CanvasApp<()> for InterleavedApp {
+    fn init(p: &mut Painter) -> Self {
+        let gpu = Gpu::new(p);
+        let layout = vertex_layout();
+        layout.buffer_layout().expect("vertex fields fit the stride");
+        let program = Program::build(&gpu, shader!("lit_textured_vertex"))
+            .with_fragment(shader!("lit_textured_fragment"))
+            .with_vertex_layout(layout)
+            .with_bindings(&[
+                UNIFORM_VERT,
+                UNIFORM_VERT,
+                UNIFORM_VERT,
+                SAMPLER_FRAG,
+                TEXTURE_FRAG,
+                UNIFORM_FRAG,
+            ])
+            .create();
+
+        let (positions, normals, indices) = cube_with_normals(CUBE_SIZE);
+        let vertices: Vec<Vertex> = (0..positions.len())
+            .map(|i| Vertex {
+                position: positions[i],
+                normal: normals[i],
+                uv: FACE_UVS[i % 4],
+                color: FACE_COLORS[i / 4],
+            })
+            .collect();
+        let mesh = Mesh::new(&gpu, &vertices).with_indices(&gpu, &indices);
+
+        let image = decode_png(IMAGE).expect("embedded image is a valid png");
+        let texture = texture_from_rgba(&gpu, image.width, image.height, &image.data);
+        let sampler = sampler(&gpu, wgpu::FilterMode::Linear, wgpu::AddressMode::ClampToEdge);
+
+        let model_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let normal_mat = Uniform::new(&gpu, mat3_columns(Mat3::IDENTITY));
+        let vp_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let light = Uniform::new(&gpu, LIGHT_DIR.normalize().extend(1.0));
+
+        let canvas = Target::canvas(surface::view_format(p))
+            .with_samples(msaa_samples(p, MSAA_SAMPLES))
+            .with_depth()
+            .create(&gpu, 1, 1);
+        let bind_group = program.bind_group(
+            &gpu,
+            0,
+            &[
+                vp_mat.binding(),
+                model_mat.binding(),
+                normal_mat.binding(),
+                wgpu::BindingResource::Sampler(&sampler),
+                wgpu::BindingResource::TextureView(&texture),
+                light.binding(),
+            ],
+        );
+        let draw = DrawCall::new(
+            &gpu,
+            &program,
+            &mesh,
+            vec![bind_group],
+            canvas.pipeline_state(),
+        );
+
+        Self {
+            gpu,
+            cam: PerspectiveCamera::create(CamProps {
+                fov: Some(0.6),
+                translation: Some(vec3(0.0, 0.0, 7.0)),
+                ..default()
+            }),
+            transform: Transform::from_translation(Vec3::ZERO),
+            model_mat,
+            normal_mat,
+            vp_mat,
+
+            canvas,
+            draw,
+        }
+    }
+
+    fn resize(&mut self, _p: &mut Painter, width: u32, height: u32) {
+        self.cam.set_aspect_ratio(width as f32 / height as f32);
+        self.canvas.resize(&self.gpu, width, height);
+
+        self.vp_mat.update(&self.gpu, self.cam.view_proj_mat());
+    }
+
+    fn update(&mut self, p: &mut Painter, tpf: f32) {
+        self.transform.rotate_y(tpf * 0.5);
+        self.transform.rotate_x(tpf * 0.3);
+        let model_mat = self.transform.model_mat();
+        self.model_mat.update(&self.gpu, model_mat);
+        self.normal_mat
+            .update(&self.gpu, mat3_columns(normal_mat(model_mat)));
+        p.request_next_frame();
+    }
+
+    fn render(&self, p: &mut Painter) -> Result<(), SurfaceError> {
+        let Some(frame) = Frame::acquire(p)? else {
+            return Ok(());
+        };
+        let mut encoder = self.gpu.encoder("Interleaved Encoder");
+        self.draw.draw(
+            &mut self.canvas.pass(&mut encoder, Some(&frame.view)),
+            self.canvas.size(),
+        );
+        self.gpu.queue.submit([encoder.finish()]);
+        frame.present();
+        Ok(())
+    }
+
+    fn event(&mut self, _e: Event<()>, _p: &mut Painter) {}
+}
@@ -1 +1,55 @@
+// Proving `wgpu::WasmNotSend` for the readback callbacks of picking.rs nests
+// deeper than the default limit.
+#![recursion_limit = "256"]
+
+pub mod bounds;
+pub mod color;
+pub mod cubemap;
+pub mod debounce;
+pub mod demo;
+pub mod depth_bias;
+pub mod double_click;
+pub mod feedback;
+pub mod file_drop;
+pub mod fixed_step;
+pub mod fps;
+pub mod frame;
+pub mod framing;
+pub mod frustum;
+pub mod fullscreen;
+pub mod gpu;
+pub mod gpu_timer;
+pub mod gradient;
+pub mod input;
+pub mod instanced;
+pub mod interleaved;
+pub mod mesh;
+pub mod msaa;
+pub mod normal_map;
+pub mod orbit;
+pub mod overlay;
+pub mod particles;
+pub mod picking;
+pub mod primitives;
+pub mod reflection;
 pub mod render;
+pub mod renderer_info;
+pub mod scene;
+pub mod screenshot;
+pub mod separate_buffers;
+pub mod share;
+pub mod split_screen;
+pub mod surface;
+pub mod target;
+pub mod text;
+pub mod texture;
+pub mod textured_quad;
+pub mod touch;
+pub mod trails;
+pub mod two_shapes;
+pub mod ui_state;
+pub mod util;
+pub mod vertex_layout;
+pub mod wave;
+pub mod webgpu;
+pub mod winding;
@@ -1,26 +1,503 @@
+use gloo_timers::callback::Timeout;
 use leptos::html;
 use leptos::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
 use trivalibs::painter::app::{AppConfig, CanvasApp, CanvasHandle};
 use trivalibs::utils::default;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_graphics_test::color::{hex_to_rgb, hsv_to_rgb, rgb_to_hsv};
+use wasm_graphics_test::debounce::Debounce;
+use wasm_graphics_test::demo::{Demo, DemoApp, DemoEvent};
+use wasm_graphics_test::feedback::{self, Feedback};
+use wasm_graphics_test::file_drop::{dropped_file, read_file, FileLoad};
+use wasm_graphics_test::fps::FpsStats;
+use wasm_graphics_test::gpu::RenderStats;
+use wasm_graphics_test::instanced;
+use wasm_graphics_test::mesh::load_obj;
+use wasm_graphics_test::render::{
+    AntiAliasing, AppEvent, CameraEvent, ColorEvent, CullMode, LightEvent, PostMode, Projection,
+    RotationAxis, RotationEvent, ScaleEvent, TranslationEvent, CAM_FOV, CAM_FOV_MAX_DEGREES,
+    CAM_FOV_MIN_DEGREES, CUBE_SCALE, FIXED_STEP_RATES, FPS_WINDOW, FPS_WINDOWS, FXAA_QUALITY,
+    GRID_LINE_WIDTH, LIGHT_DIR, LIGHT_INTENSITY, MAX_POINT_LIGHTS, ROTATION_SPEED,
+    ROTATION_STEP_DEGREES, SHININESS, SHININESS_MAX, SHININESS_MIN,
+};
+use wasm_graphics_test::share::SharedParams;
+use wasm_graphics_test::ui_state::UiState;
+use wasm_graphics_test::util::now_seconds;
+use wasm_graphics_test::webgpu::{
+    is_webgl2_supported, is_webgpu_supported, probe_webgpu, StartError,
+};
 
-mod render;
-use render::{ColorEvent, SimpleApp};
+// Delay after the last UI change before the settings are saved.
+const SAVE_DELAY_MS: u32 = 500;
+// How long the copy link button confirms the copy.
+const LINK_COPIED_MS: u32 = 2000;
+// Delay in seconds after the last size change of the canvas' container before
+// the canvas takes the new size. Each canvas resize reconfigures the surface
+// and recreates the size dependent textures, too slow for every step of
+// dragging the window edge.
+const CANVAS_RESIZE_DEBOUNCE: f64 = 0.1;
 
 #[allow(non_snake_case)]
 #[component]
-fn App(handle: CanvasHandle<ColorEvent>, canvas_ref: NodeRef<html::Canvas>) -> impl IntoView {
-    // State for UI controls
-    let (color_r, set_color_r) = signal(1.0);
-    let (color_g, set_color_g) = signal(0.0);
-    let (color_b, set_color_b) = signal(0.0);
+fn App(
+    handle: CanvasHandle<DemoEvent>,
+    canvas_ref: NodeRef<html::Canvas>,
+    // The canvas is composited with premultiplied alpha, so a transparent
+    // background shows the page behind it.
+    transparent: bool,
+) -> impl IntoView {
+    // Feedback from the running app
+    let (fps, set_fps) = signal(FpsStats::default());
+    let (gpu_time, set_gpu_time) = signal(None::<f32>);
+    let (renderer, set_renderer) = signal(String::new());
+    let (wireframe_supported, set_wireframe_supported) = signal(false);
+    let (storage_supported, set_storage_supported) = signal(true);
+    let (picked, set_picked) = signal(None::<&'static str>);
+    let (culling, set_culling) = signal((0, 0));
+    let (render_stats, set_render_stats) = signal(RenderStats::default());
+    // Params of a shared link take precedence over the saved settings.
+    let shared = SharedParams::from_location();
+    let (camera, set_camera) = signal(shared.camera);
+    // Errors starting the app, e.g. when no WebGPU adapter is available and
+    // the app never starts.
+    let (app_error, set_app_error) = signal(None::<String>);
+    feedback::listen(move |f| match f {
+        Feedback::Fps(v) => set_fps.set(v),
+        Feedback::GpuTime(ms) => set_gpu_time.set(ms),
+        Feedback::Renderer(info) => set_renderer.set(info),
+        Feedback::WireframeSupported(supported) => set_wireframe_supported.set(supported),
+        Feedback::StorageSupported(supported) => set_storage_supported.set(supported),
+        Feedback::Picked(name) => set_picked.set(name),
+        Feedback::Culling { drawn, culled } => set_culling.set((drawn, culled)),
+        Feedback::RenderStats(stats) => set_render_stats.set(stats),
+        Feedback::StartFailed(e) => set_app_error.set(Some(error_message(&e))),
+        Feedback::Camera {
+            yaw,
+            pitch,
+            distance,
+        } => set_camera.set(Some(CameraEvent {
+            yaw,
+            pitch,
+            distance,
+        })),
+    });
+    if let Some(c) = shared.camera {
+        let _ = handle.send_event(AppEvent::Camera(c).into());
+    }
+
+    let (demo, set_demo) = signal(Demo::Simple);
+    let demo_handle = handle.clone();
+    let switch_demo = move |d: Demo| {
+        set_demo.set(d);
+        let _ = demo_handle.send_event(DemoEvent::Switch(d));
+    };
+
+    let (instance_seed, set_instance_seed) = signal(instanced::DEFAULT_SEED);
+    let seed_handle = handle.clone();
+    let set_seed = move |seed: u64| {
+        set_instance_seed.set(seed);
+        let _ = seed_handle.send_event(DemoEvent::InstanceSeed(seed));
+    };
+
+    // State for UI controls, starting from the settings of the last visit
+    let saved = UiState::load();
+    let (r, g, b) = shared
+        .color
+        .unwrap_or((saved.color_r, saved.color_g, saved.color_b));
+    let (color_r, set_color_r) = signal(r);
+    let (color_g, set_color_g) = signal(g);
+    let (color_b, set_color_b) = signal(b);
+    let (color_a, set_color_a) = signal(shared.alpha.unwrap_or(saved.color_a));
+
+    // HSV mode edits hue/saturation/value, which are converted to the rgb
+    // signals above. The hsv signals are only derived from rgb when switching
+    // modes, so they don't drift while dragging.
+    let (hsv_mode, set_hsv_mode) = signal(false);
+    let (hue, set_hue) = signal(0.0);
+    let (saturation, set_saturation) = signal(1.0);
+    let (value, set_value) = signal(1.0);
+
+    let swatch_color = Memo::new(move |_| {
+        let to_byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        format!(
+            "rgba({}, {}, {}, {})",
+            to_byte(color_r.get()),
+            to_byte(color_g.get()),
+            to_byte(color_b.get()),
+            color_a.get().clamp(0.0, 1.0)
+        )
+    });
+
+    let toggle_hsv_mode = move |enabled: bool| {
+        if enabled {
+            let (h, s, v) = rgb_to_hsv(
+                color_r.get_untracked(),
+                color_g.get_untracked(),
+                color_b.get_untracked(),
+                hue.get_untracked(),
+                saturation.get_untracked(),
+            );
+            set_hue.set(h);
+            set_saturation.set(s);
+            set_value.set(v);
+        }
+        set_hsv_mode.set(enabled);
+    };
+
+    let set_hsv = move |h: f32, s: f32, v: f32| {
+        set_hue.set(h);
+        set_saturation.set(s);
+        set_value.set(v);
+        let (r, g, b) = hsv_to_rgb(h, s, v);
+        set_color_r.set(r);
+        set_color_g.set(g);
+        set_color_b.set(b);
+    };
+
+    let (light_x, set_light_x) = signal(saved.light_x);
+    let (light_y, set_light_y) = signal(saved.light_y);
+    let (light_z, set_light_z) = signal(saved.light_z);
+    let (light_intensity, set_light_intensity) = signal(saved.light_intensity);
+    let (shininess, set_shininess) = signal(saved.shininess);
+    let (point_lights, set_point_lights) = signal(saved.point_lights.min(MAX_POINT_LIGHTS as u32));
+
+    let (translate_x, set_translate_x) = signal(0.0);
+    let (translate_y, set_translate_y) = signal(0.0);
+    let (translate_z, set_translate_z) = signal(0.0);
+
+    // In uniform mode a single slider sets all three axes.
+    let (uniform_scale, set_uniform_scale) = signal(true);
+    let (scale_x, set_scale_x) = signal(CUBE_SCALE);
+    let (scale_y, set_scale_y) = signal(CUBE_SCALE);
+    let (scale_z, set_scale_z) = signal(CUBE_SCALE);
+    let set_scale = move |s: f32| {
+        set_scale_x.set(s);
+        set_scale_y.set(s);
+        set_scale_z.set(s);
+    };
+    let toggle_uniform_scale = move |enabled: bool| {
+        if enabled {
+            set_scale(scale_x.get_untracked());
+        }
+        set_uniform_scale.set(enabled);
+    };
+
+    let (rotation_axis, set_rotation_axis) = signal(RotationAxis::Y);
+    let (rotation_speed, set_rotation_speed) = signal(saved.rotation_speed);
+
+    let (grid_line_width, set_grid_line_width) = signal(GRID_LINE_WIDTH);
+    let grid_handle = handle.clone();
+    Effect::new(move |_| {
+        let width = grid_line_width.get();
+        let _ = grid_handle.send_event(AppEvent::GridLineWidth(width).into());
+    });
+
+    let (fov, set_fov) = signal(shared.fov.unwrap_or(saved.fov));
+    let fov_handle = handle.clone();
+    Effect::new(move |_| {
+        let degrees = fov.get();
+        let _ = fov_handle.send_event(AppEvent::FieldOfView(degrees).into());
+    });
+
+    let (paused, set_paused) = signal(false);
+    let pause_handle = handle.clone();
+    let toggle_pause = move |_| {
+        let p = !paused.get_untracked();
+        set_paused.set(p);
+        let _ = pause_handle.send_event(AppEvent::Pause(p).into());
+    };
+
+    // Puts a link to the current color and camera into the address bar and,
+    // where the clipboard API is available (secure contexts only), copies it.
+    let (link_copied, set_link_copied) = signal(false);
+    let copy_link = move |_| {
+        let params = SharedParams {
+            color: Some((
+                color_r.get_untracked(),
+                color_g.get_untracked(),
+                color_b.get_untracked(),
+            )),
+            alpha: Some(color_a.get_untracked()),
+            fov: Some(fov.get_untracked()),
+            camera: camera.get_untracked(),
+        };
+        let (Some(url), Some(window)) = (params.to_url(), web_sys::window()) else {
+            return;
+        };
+        if let Ok(history) = window.history() {
+            let _ = history.replace_state_with_url(&JsValue::NULL, "", Some(&url));
+        }
+
+        let navigator = window.navigator();
+        if !js_sys::Reflect::has(&navigator, &"clipboard".into()).unwrap_or(false) {
+            log::warn!("Clipboard not available, the link is in the address bar");
+            return;
+        }
+        let promise = navigator.clipboard().write_text(&url);
+        wasm_bindgen_futures::spawn_local(async move {
+            match wasm_bindgen_futures::JsFuture::from(promise).await {
+                Ok(_) => {
+                    set_link_copied.set(true);
+                    Timeout::new(LINK_COPIED_MS, move || set_link_copied.set(false)).forget();
+                }
+                Err(e) => log::warn!("Could not copy link: {e:?}"),
+            }
+        });
+    };
+
+    // OBJ files dropped onto the canvas replace the cube. They are parsed
+    // here, so errors show up next to the file name.
+    let (file_load, set_file_load) = signal(None::<FileLoad>);
+    let mesh_handle = handle.clone();
+    let drop_file = move |ev: leptos::ev::DragEvent| {
+        ev.prevent_default();
+        let Some(file) = dropped_file(&ev) else {
+            return;
+        };
+        let name = file.name();
+        if !name.to_lowercase().ends_with(".obj") {
+            set_file_load.set(Some(FileLoad::Failed(format!("{name}: not an .obj file"))));
+            return;
+        }
+        set_file_load.set(Some(FileLoad::Loading(name.clone())));
+        let mesh_handle = mesh_handle.clone();
+        read_file(&file, move |bytes| {
+            let mesh = bytes.and_then(|bytes| load_obj(&bytes).map_err(|e| e.to_string()));
+            match mesh {
+                Ok(mesh) if mesh.indices.is_empty() => {
+                    set_file_load.set(Some(FileLoad::Failed(format!("{name}: no faces"))));
+                }
+                Ok(mesh) => {
+                    let _ = mesh_handle.send_event(AppEvent::Mesh(Arc::new(mesh)).into());
+                    set_file_load.set(Some(FileLoad::Loaded(name)));
+                }
+                Err(e) => set_file_load.set(Some(FileLoad::Failed(format!("{name}: {e}")))),
+            }
+        });
+    };
+
+    // In step mode the cube turns by `step_degrees` per click instead of
+    // spinning.
+    let fps_window_handle = handle.clone();
+    let select_fps_window = move |window: &str| {
+        let window = window.parse().unwrap_or(FPS_WINDOW);
+        let _ = fps_window_handle.send_event(AppEvent::FpsWindow(window).into());
+    };
+
+    let timestep_handle = handle.clone();
+    let select_timestep = move |rate: &str| {
+        let dt = rate.parse::<u32>().ok().map(|rate| 1.0 / rate as f32);
+        let _ = timestep_handle.send_event(AppEvent::FixedTimestep(dt).into());
+    };
+
+    let (step_mode, set_step_mode) = signal(false);
+    let (step_degrees, set_step_degrees) = signal(ROTATION_STEP_DEGREES);
+    let step_mode_handle = handle.clone();
+    let toggle_step_mode = move |on: bool| {
+        set_step_mode.set(on);
+        let _ = step_mode_handle.send_event(AppEvent::StepMode(on).into());
+    };
+    let step_handle = handle.clone();
+    let rotate_step = move |sign: f32| {
+        let degrees = sign * step_degrees.get_untracked();
+        let _ = step_handle.send_event(AppEvent::RotateStep(degrees).into());
+    };
+
+    let frame_handle = handle.clone();
+    let frame_all = move |_| {
+        let _ = frame_handle.send_event(AppEvent::FrameAll.into());
+    };
+
+    let screenshot_handle = handle.clone();
+    let save_png = move |_| {
+        let _ = screenshot_handle.send_event(AppEvent::Screenshot.into());
+    };
+
+    let projection_handle = handle.clone();
+    let set_orthographic = move |ortho: bool| {
+        let projection = if ortho {
+            Projection::Orthographic
+        } else {
+            Projection::Perspective
+        };
+        let _ = projection_handle.send_event(AppEvent::Projection(projection).into());
+    };
+
+    let post_handle = handle.clone();
+    let set_post_mode = move |mode: &str| {
+        let mode = match mode {
+            "grayscale" => PostMode::Grayscale,
+            "invert" => PostMode::Invert,
+            _ => PostMode::Passthrough,
+        };
+        let _ = post_handle.send_event(AppEvent::PostMode(mode).into());
+    };
+
+    let front_face_handle = handle.clone();
+    let set_front_face = move |front_face: &str| {
+        let front_face = match front_face {
+            "cw" => wgpu::FrontFace::Cw,
+            _ => wgpu::FrontFace::Ccw,
+        };
+        let _ = front_face_handle.send_event(AppEvent::FrontFace(front_face).into());
+    };
+
+    let cull_handle = handle.clone();
+    let set_cull_mode = move |mode: &str| {
+        let mode = match mode {
+            "none" => CullMode::None,
+            "front" => CullMode::Front,
+            _ => CullMode::Back,
+        };
+        let _ = cull_handle.send_event(AppEvent::CullMode(mode).into());
+    };
+
+    let (anti_aliasing, set_anti_aliasing) = signal(AntiAliasing::Msaa);
+    let aa_handle = handle.clone();
+    let select_anti_aliasing = move |mode: &str| {
+        let mode = match mode {
+            "fxaa" => AntiAliasing::Fxaa,
+            "off" => AntiAliasing::Off,
+            _ => AntiAliasing::Msaa,
+        };
+        set_anti_aliasing.set(mode);
+        let _ = aa_handle.send_event(AppEvent::AntiAliasing(mode).into());
+    };
+    let (fxaa_quality, set_fxaa_quality) = signal(FXAA_QUALITY);
+    let fxaa_handle = handle.clone();
+    Effect::new(move |_| {
+        let quality = fxaa_quality.get();
+        let _ = fxaa_handle.send_event(AppEvent::FxaaQuality(quality).into());
+    });
+
+    let shininess_handle = handle.clone();
+    Effect::new(move |_| {
+        let exponent = shininess.get();
+        let _ = shininess_handle.send_event(AppEvent::Shininess(exponent).into());
+    });
+
+    let point_lights_handle = handle.clone();
+    Effect::new(move |_| {
+        let count = point_lights.get();
+        let _ = point_lights_handle.send_event(AppEvent::PointLights(count).into());
+    });
+
+    let specular_handle = handle.clone();
+    let set_specular_color = move |hex: &str| {
+        if let Some((r, g, b)) = hex_to_rgb(hex) {
+            let _ = specular_handle
+                .send_event(AppEvent::SpecularColor(ColorEvent { r, g, b, a: 1.0 }).into());
+        }
+    };
+
+    // The alpha only has an effect on a transparent canvas, which starts
+    // fully see-through.
+    let (clear_hex, set_clear_hex) = signal("#000000".to_string());
+    let (clear_alpha, set_clear_alpha) = signal(if transparent { 0.0 } else { 1.0 });
+    let clear_handle = handle.clone();
+    Effect::new(move |_| {
+        let a = clear_alpha.get();
+        if let Some((r, g, b)) = hex_to_rgb(&clear_hex.get()) {
+            let _ = clear_handle
+                .send_event(AppEvent::ClearColor(ColorEvent { r, g, b, a }).into());
+        }
+    });
+
+    let wireframe_handle = handle.clone();
+    let set_wireframe = move |wireframe: bool| {
+        let _ = wireframe_handle.send_event(AppEvent::Wireframe(wireframe).into());
+    };
+
+    // Restores the initial color and camera. Changing the color signals
+    // sends the matching color event through the effect below.
+    let reset_handle = handle.clone();
+    let reset = move |_| {
+        let ColorEvent { r, g, b, a } = ColorEvent::default();
+        set_color_r.set(r);
+        set_color_g.set(g);
+        set_color_b.set(b);
+        set_color_a.set(a);
+        if hsv_mode.get_untracked() {
+            toggle_hsv_mode(true);
+        }
+        let _ = reset_handle.send_event(AppEvent::ResetCamera.into());
+    };
+
+    // Save the settings once they stopped changing for a moment, instead of
+    // on every slider step. Replacing the timeout cancels the previous one.
+    let save_timeout = StoredValue::new_local(None::<Timeout>);
+    Effect::new(move |_| {
+        let state = UiState {
+            color_r: color_r.get(),
+            color_g: color_g.get(),
+            color_b: color_b.get(),
+            color_a: color_a.get(),
+            light_x: light_x.get(),
+            light_y: light_y.get(),
+            light_z: light_z.get(),
+            light_intensity: light_intensity.get(),
+            shininess: shininess.get(),
+            point_lights: point_lights.get(),
+            fov: fov.get(),
+            rotation_speed: rotation_speed.get(),
+        };
+        save_timeout.set_value(Some(Timeout::new(SAVE_DELAY_MS, move || state.save())));
+    });
 
     // Send color updates when values change
+    let color_handle = handle.clone();
     Effect::new(move |_| {
         let r = color_r.get();
         let g = color_g.get();
         let b = color_b.get();
+        let a = color_a.get();
+
+        let _ = color_handle.send_event(AppEvent::Color(ColorEvent { r, g, b, a }).into());
+    });
+
+    // Send translation updates when values change
+    let translation_handle = handle.clone();
+    Effect::new(move |_| {
+        let x = translate_x.get();
+        let y = translate_y.get();
+        let z = translate_z.get();
+
+        let _ = translation_handle
+            .send_event(AppEvent::Translation(TranslationEvent { x, y, z }).into());
+    });
+
+    // Send scale updates when values change
+    let scale_handle = handle.clone();
+    Effect::new(move |_| {
+        let x = scale_x.get();
+        let y = scale_y.get();
+        let z = scale_z.get();
+
+        let _ = scale_handle.send_event(AppEvent::Scale(ScaleEvent { x, y, z }).into());
+    });
+
+    // Send rotation updates when values change
+    let rotation_handle = handle.clone();
+    Effect::new(move |_| {
+        let axis = rotation_axis.get();
+        let speed = rotation_speed.get();
+
+        let _ =
+            rotation_handle.send_event(AppEvent::Rotation(RotationEvent { axis, speed }).into());
+    });
+
+    // Send light updates when values change
+    Effect::new(move |_| {
+        let x = light_x.get();
+        let y = light_y.get();
+        let z = light_z.get();
+        let intensity = light_intensity.get();
 
-        let _ = handle.send_event(ColorEvent { r, g, b });
+        let _ = handle.send_event(AppEvent::Light(LightEvent { x, y, z, intensity }).into());
     });
 
     view! {
@@ -28,75 +505,817 @@ fn App(handle: CanvasHandle<ColorEvent>, canvas_ref: NodeRef<html::Canvas>) -> i
             <div style="padding: 10px; background-color: #f0f0f0;">
                 <h1>"WASM Graphics Test with Leptos"</h1>
                 <div style="display: flex; gap: 20px;">
+                    <span>
+                        {move || {
+                            let s = fps.get();
+                            format!("FPS: {:.0} (min {:.0}, max {:.0})", s.avg, s.min, s.max)
+                        }}
+                    </span>
+                    <select on:change=move |ev| select_fps_window(&event_target_value(&ev))>
+                        {FPS_WINDOWS
+                            .iter()
+                            .map(|&w| {
+                                let label =
+                                    if w == 0.0 { "Instant".to_string() } else { format!("{w}s") };
+                                view! {
+                                    <option value=w.to_string() selected=w == FPS_WINDOW>
+                                        {label}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+                    </select>
+                    <span>
+                        {move || match gpu_time.get() {
+                            Some(ms) => format!("GPU: {ms:.2} ms"),
+                            None => "GPU: N/A".to_string(),
+                        }}
+                    </span>
+                    <span>{move || format!("Renderer: {}", renderer.get())}</span>
+                    <span>{move || format!("Picked: {}", picked.get().unwrap_or("none"))}</span>
+                    <span>
+                        {move || {
+                            let (drawn, culled) = culling.get();
+                            format!("Objects: {drawn} drawn, {culled} culled")
+                        }}
+                    </span>
+                    <span>
+                        {move || {
+                            let s = render_stats.get();
+                            format!(
+                                "Draws: {}, triangles: {}, bind groups: {}",
+                                s.draw_calls, s.triangles, s.bind_groups,
+                            )
+                        }}
+                    </span>
+                    <select on:change=move |ev| {
+                        if let Ok(i) = event_target_value(&ev).parse::<usize>() {
+                            switch_demo(Demo::ALL[i]);
+                        }
+                    }>
+                        {Demo::ALL
+                            .iter()
+                            .enumerate()
+                            .map(|(i, &d)| {
+                                let unavailable = move || d.needs_storage() && !storage_supported.get();
+                                view! {
+                                    <option value=i.to_string() prop:disabled=unavailable>
+                                        {d.label()}
+                                        {move || if unavailable() { " (needs WebGPU)" } else { "" }}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+                    </select>
+                </div>
+                // Controls of the lit cube demo
+                <div style:display=move || if demo.get() == Demo::Simple { "block" } else { "none" }>
+                    <div style="display: flex; gap: 20px; align-items: center;">
+                        <label>
+                            <input
+                                type="checkbox"
+                                prop:checked=hsv_mode
+                                on:change=move |ev| toggle_hsv_mode(event_target_checked(&ev))
+                            />
+                            "HSV"
+                        </label>
+                        <div
+                            style="width: 40px; height: 20px; border: 1px solid #888;"
+                            style:background-color=swatch_color
+                        />
+                        <button on:click=reset>"Reset"</button>
+                        <button on:click=frame_all>"Frame all"</button>
+                        <button on:click=toggle_pause>
+                            {move || if paused.get() { "Resume" } else { "Pause" }}
+                        </button>
+                        <button on:click=save_png>"Save PNG"</button>
+                        <button on:click=copy_link>
+                            {move || if link_copied.get() { "Link copied" } else { "Copy link" }}
+                        </button>
+                        <label>
+                            <input
+                                type="checkbox"
+                                on:change=move |ev| set_orthographic(event_target_checked(&ev))
+                            />
+                            "Orthographic"
+                        </label>
+                        <label>
+                            <input
+                                type="checkbox"
+                                prop:disabled=move || !wireframe_supported.get()
+                                on:change=move |ev| set_wireframe(event_target_checked(&ev))
+                            />
+                            "Wireframe"
+                        </label>
+                        <label>
+                            "Background: "
+                            <input
+                                type="color"
+                                value="#000000"
+                                on:input=move |ev| set_clear_hex.set(event_target_value(&ev))
+                            />
+                        </label>
+                        <Show when=move || transparent>
+                            <label>
+                                "Background alpha: "
+                                <input
+                                    type="range"
+                                    min="0"
+                                    max="1"
+                                    step="0.01"
+                                    value=clear_alpha
+                                    on:input=move |ev| {
+                                        set_clear_alpha
+                                            .set(event_target_value(&ev).parse().unwrap_or(1.0))
+                                    }
+                                />
+                                {move || format!("{:.2}", clear_alpha.get())}
+                            </label>
+                        </Show>
+                        <select on:change=move |ev| set_cull_mode(&event_target_value(&ev))>
+                            <option value="back">"Cull back faces"</option>
+                            <option value="front">"Cull front faces"</option>
+                            <option value="none">"No culling"</option>
+                        </select>
+                        <select on:change=move |ev| set_front_face(&event_target_value(&ev))>
+                            <option value="ccw">"Front faces CCW"</option>
+                            <option value="cw">"Front faces CW"</option>
+                        </select>
+                        <select on:change=move |ev| set_post_mode(&event_target_value(&ev))>
+                            <option value="none">"No effect"</option>
+                            <option value="grayscale">"Grayscale"</option>
+                            <option value="invert">"Invert"</option>
+                        </select>
+                        <select on:change=move |ev| select_anti_aliasing(&event_target_value(&ev))>
+                            <option value="msaa">"MSAA 4x"</option>
+                            <option value="fxaa">"FXAA"</option>
+                            <option value="off">"No anti-aliasing"</option>
+                        </select>
+                        <Show when=move || anti_aliasing.get() == AntiAliasing::Fxaa>
+                            <label>
+                                "FXAA quality: "
+                                <input
+                                    type="range"
+                                    min="0"
+                                    max="1"
+                                    step="0.05"
+                                    value=fxaa_quality
+                                    on:input=move |ev| {
+                                        set_fxaa_quality
+                                            .set(event_target_value(&ev).parse().unwrap_or(FXAA_QUALITY))
+                                    }
+                                />
+                                {move || format!("{:.2}", fxaa_quality.get())}
+                            </label>
+                        </Show>
+                    </div>
+                    <Show when=move || !hsv_mode.get() fallback=move || view! {
+                        <div style="display: flex; gap: 20px;">
+                            <label>
+                                "Hue: "
+                                <input
+                                    type="range"
+                                    min="0"
+                                    max="360"
+                                    step="1"
+                                    value=hue
+                                    on:input=move |ev| {
+                                        let h = event_target_value(&ev).parse().unwrap_or(0.0);
+                                        set_hsv(h, saturation.get_untracked(), value.get_untracked())
+                                    }
+                                />
+                                {move || format!("{:.0}", hue.get())}
+                            </label>
+                            <label>
+                                "Saturation: "
+                                <input
+                                    type="range"
+                                    min="0"
+                                    max="1"
+                                    step="0.01"
+                                    value=saturation
+                                    on:input=move |ev| {
+                                        let s = event_target_value(&ev).parse().unwrap_or(1.0);
+                                        set_hsv(hue.get_untracked(), s, value.get_untracked())
+                                    }
+                                />
+                                {move || format!("{:.2}", saturation.get())}
+                            </label>
+                            <label>
+                                "Value: "
+                                <input
+                                    type="range"
+                                    min="0"
+                                    max="1"
+                                    step="0.01"
+                                    value=value
+                                    on:input=move |ev| {
+                                        let v = event_target_value(&ev).parse().unwrap_or(1.0);
+                                        set_hsv(hue.get_untracked(), saturation.get_untracked(), v)
+                                    }
+                                />
+                                {move || format!("{:.2}", value.get())}
+                            </label>
+                        </div>
+                    }>
+                        <div style="display: flex; gap: 20px;">
+                            <label>
+                                "Red: "
+                                <input
+                                    type="range"
+                                    min="0"
+                                    max="1"
+                                    step="0.01"
+                                    value=color_r
+                                    on:input=move |ev| {
+                                        set_color_r.set(event_target_value(&ev).parse().unwrap_or(1.0))
+                                    }
+                                />
+                                {move || format!("{:.2}", color_r.get())}
+                            </label>
+                            <label>
+                                "Green: "
+                                <input
+                                    type="range"
+                                    min="0"
+                                    max="1"
+                                    step="0.01"
+                                    value=color_g
+                                    on:input=move |ev| {
+                                        set_color_g.set(event_target_value(&ev).parse().unwrap_or(0.0))
+                                    }
+                                />
+                                {move || format!("{:.2}", color_g.get())}
+                            </label>
+                            <label>
+                                "Blue: "
+                                <input
+                                    type="range"
+                                    min="0"
+                                    max="1"
+                                    step="0.01"
+                                    value=color_b
+                                    on:input=move |ev| {
+                                        set_color_b.set(event_target_value(&ev).parse().unwrap_or(0.0))
+                                    }
+                                />
+                                {move || format!("{:.2}", color_b.get())}
+                            </label>
+                        </div>
+                    </Show>
                     <label>
-                        "Red: "
+                        "Alpha: "
                         <input
                             type="range"
                             min="0"
                             max="1"
                             step="0.01"
-                            value=color_r
+                            value=color_a
                             on:input=move |ev| {
-                                set_color_r.set(event_target_value(&ev).parse().unwrap_or(1.0))
+                                set_color_a.set(event_target_value(&ev).parse().unwrap_or(1.0))
                             }
                         />
-                        {move || format!("{:.2}", color_r.get())}
+                        {move || format!("{:.2}", color_a.get())}
                     </label>
-                    <label>
-                        "Green: "
+                    <div style="display: flex; gap: 20px;">
+                        <label>
+                            "Light X: "
+                            <input
+                                type="range"
+                                min="-1"
+                                max="1"
+                                step="0.01"
+                                value=light_x
+                                on:input=move |ev| {
+                                    set_light_x.set(event_target_value(&ev).parse().unwrap_or(LIGHT_DIR.x))
+                                }
+                            />
+                            {move || format!("{:.2}", light_x.get())}
+                        </label>
+                        <label>
+                            "Light Y: "
+                            <input
+                                type="range"
+                                min="-1"
+                                max="1"
+                                step="0.01"
+                                value=light_y
+                                on:input=move |ev| {
+                                    set_light_y.set(event_target_value(&ev).parse().unwrap_or(LIGHT_DIR.y))
+                                }
+                            />
+                            {move || format!("{:.2}", light_y.get())}
+                        </label>
+                        <label>
+                            "Light Z: "
+                            <input
+                                type="range"
+                                min="-1"
+                                max="1"
+                                step="0.01"
+                                value=light_z
+                                on:input=move |ev| {
+                                    set_light_z.set(event_target_value(&ev).parse().unwrap_or(LIGHT_DIR.z))
+                                }
+                            />
+                            {move || format!("{:.2}", light_z.get())}
+                        </label>
+                        <label>
+                            "Intensity: "
+                            <input
+                                type="range"
+                                min="0"
+                                max="2"
+                                step="0.01"
+                                value=light_intensity
+                                on:input=move |ev| {
+                                    set_light_intensity.set(
+                                        event_target_value(&ev).parse().unwrap_or(LIGHT_INTENSITY),
+                                    )
+                                }
+                            />
+                            {move || format!("{:.2}", light_intensity.get())}
+                        </label>
+                        <label>
+                            "Shininess: "
+                            <input
+                                type="range"
+                                min=SHININESS_MIN
+                                max=SHININESS_MAX
+                                step="1"
+                                value=shininess
+                                on:input=move |ev| {
+                                    set_shininess.set(event_target_value(&ev).parse().unwrap_or(SHININESS))
+                                }
+                            />
+                            {move || format!("{:.0}", shininess.get())}
+                        </label>
+                        <label>
+                            "Highlight: "
+                            <input
+                                type="color"
+                                value="#ffffff"
+                                on:input=move |ev| set_specular_color(&event_target_value(&ev))
+                            />
+                        </label>
+                        <span>
+                            {move || format!("Point lights: {}/{MAX_POINT_LIGHTS} ", point_lights.get())}
+                            <button
+                                prop:disabled=move || point_lights.get() as usize >= MAX_POINT_LIGHTS
+                                on:click=move |_| set_point_lights.update(|n| *n += 1)
+                            >
+                                "Add"
+                            </button>
+                            <button
+                                prop:disabled=move || point_lights.get() == 0
+                                on:click=move |_| set_point_lights.update(|n| *n -= 1)
+                            >
+                                "Remove"
+                            </button>
+                        </span>
+                    </div>
+                    <div style="display: flex; gap: 20px;">
+                        <label>
+                            "Move X: "
+                            <input
+                                type="range"
+                                min="-20"
+                                max="20"
+                                step="0.5"
+                                value=translate_x
+                                on:input=move |ev| {
+                                    set_translate_x.set(event_target_value(&ev).parse().unwrap_or(0.0))
+                                }
+                            />
+                            {move || format!("{:.1}", translate_x.get())}
+                        </label>
+                        <label>
+                            "Move Y: "
+                            <input
+                                type="range"
+                                min="-20"
+                                max="20"
+                                step="0.5"
+                                value=translate_y
+                                on:input=move |ev| {
+                                    set_translate_y.set(event_target_value(&ev).parse().unwrap_or(0.0))
+                                }
+                            />
+                            {move || format!("{:.1}", translate_y.get())}
+                        </label>
+                        <label>
+                            "Move Z: "
+                            <input
+                                type="range"
+                                min="-20"
+                                max="20"
+                                step="0.5"
+                                value=translate_z
+                                on:input=move |ev| {
+                                    set_translate_z.set(event_target_value(&ev).parse().unwrap_or(0.0))
+                                }
+                            />
+                            {move || format!("{:.1}", translate_z.get())}
+                        </label>
+                    </div>
+                    <div style="display: flex; gap: 20px;">
+                        <label>
+                            <input
+                                type="checkbox"
+                                prop:checked=uniform_scale
+                                on:change=move |ev| toggle_uniform_scale(event_target_checked(&ev))
+                            />
+                            "Uniform scale"
+                        </label>
+                        <Show when=move || uniform_scale.get() fallback=move || view! {
+                            <label>
+                                "Scale X: "
+                                <input
+                                    type="range"
+                                    min="0.5"
+                                    max="20"
+                                    step="0.5"
+                                    value=scale_x
+                                    on:input=move |ev| {
+                                        set_scale_x.set(event_target_value(&ev).parse().unwrap_or(CUBE_SCALE))
+                                    }
+                                />
+                                {move || format!("{:.1}", scale_x.get())}
+                            </label>
+                            <label>
+                                "Scale Y: "
+                                <input
+                                    type="range"
+                                    min="0.5"
+                                    max="20"
+                                    step="0.5"
+                                    value=scale_y
+                                    on:input=move |ev| {
+                                        set_scale_y.set(event_target_value(&ev).parse().unwrap_or(CUBE_SCALE))
+                                    }
+                                />
+                                {move || format!("{:.1}", scale_y.get())}
+                            </label>
+                            <label>
+                                "Scale Z: "
+                                <input
+                                    type="range"
+                                    min="0.5"
+                                    max="20"
+                                    step="0.5"
+                                    value=scale_z
+                                    on:input=move |ev| {
+                                        set_scale_z.set(event_target_value(&ev).parse().unwrap_or(CUBE_SCALE))
+                                    }
+                                />
+                                {move || format!("{:.1}", scale_z.get())}
+                            </label>
+                        }>
+                            <label>
+                                "Scale: "
+                                <input
+                                    type="range"
+                                    min="0.5"
+                                    max="20"
+                                    step="0.5"
+                                    value=scale_x
+                                    on:input=move |ev| {
+                                        set_scale(event_target_value(&ev).parse().unwrap_or(CUBE_SCALE))
+                                    }
+                                />
+                                {move || format!("{:.1}", scale_x.get())}
+                            </label>
+                        </Show>
+                    </div>
+                    <div style="display: flex; gap: 20px;">
+                        <label>
+                            "Rotation: "
+                            <select on:change=move |ev| {
+                                let axis = match event_target_value(&ev).as_str() {
+                                    "x" => RotationAxis::X,
+                                    "z" => RotationAxis::Z,
+                                    "tumble" => RotationAxis::Tumble,
+                                    _ => RotationAxis::Y,
+                                };
+                                set_rotation_axis.set(axis);
+                            }>
+                                <option value="x">"X"</option>
+                                <option value="y" selected>"Y"</option>
+                                <option value="z">"Z"</option>
+                                <option value="tumble">"Tumble"</option>
+                            </select>
+                        </label>
+                        <label>
+                            "Speed: "
+                            <input
+                                type="range"
+                                min="0"
+                                max="3"
+                                step="0.05"
+                                value=rotation_speed
+                                prop:disabled=step_mode
+                                on:input=move |ev| {
+                                    set_rotation_speed.set(
+                                        event_target_value(&ev).parse().unwrap_or(ROTATION_SPEED),
+                                    )
+                                }
+                            />
+                            {move || format!("{:.2}", rotation_speed.get())}
+                        </label>
+                        <label>
+                            "Timestep: "
+                            <select on:change=move |ev| select_timestep(&event_target_value(&ev))>
+                                <option value="variable">"Variable"</option>
+                                {FIXED_STEP_RATES
+                                    .iter()
+                                    .map(|rate| view! { <option value=rate.to_string()>{format!("{rate} Hz")}</option> })
+                                    .collect_view()}
+                            </select>
+                        </label>
+                        <label>
+                            <input
+                                type="checkbox"
+                                on:change=move |ev| toggle_step_mode(event_target_checked(&ev))
+                            />
+                            "Step mode"
+                        </label>
                         <input
-                            type="range"
-                            min="0"
-                            max="1"
-                            step="0.01"
-                            value=color_g
+                            type="number"
+                            min="1"
+                            max="360"
+                            style="width: 60px;"
+                            value=step_degrees
+                            prop:disabled=move || !step_mode.get()
                             on:input=move |ev| {
-                                set_color_g.set(event_target_value(&ev).parse().unwrap_or(0.0))
+                                set_step_degrees.set(
+                                    event_target_value(&ev).parse().unwrap_or(ROTATION_STEP_DEGREES),
+                                )
                             }
                         />
-                        {move || format!("{:.2}", color_g.get())}
-                    </label>
+                        <button
+                            prop:disabled=move || !step_mode.get()
+                            on:click={
+                                let rotate_step = rotate_step.clone();
+                                move |_| rotate_step(-1.0)
+                            }
+                        >
+                            {move || format!("-{}°", step_degrees.get())}
+                        </button>
+                        <button
+                            prop:disabled=move || !step_mode.get()
+                            on:click=move |_| rotate_step(1.0)
+                        >
+                            {move || format!("+{}°", step_degrees.get())}
+                        </button>
+                        <label>
+                            "FOV: "
+                            <input
+                                type="range"
+                                min=CAM_FOV_MIN_DEGREES
+                                max=CAM_FOV_MAX_DEGREES
+                                step="1"
+                                value=fov
+                                on:input=move |ev| {
+                                    set_fov.set(event_target_value(&ev).parse().unwrap_or(CAM_FOV.to_degrees()))
+                                }
+                            />
+                            {move || format!("{:.0}°", fov.get())}
+                        </label>
+                        <label>
+                            "Grid lines: "
+                            <input
+                                type="range"
+                                min="0.5"
+                                max="6"
+                                step="0.5"
+                                value=grid_line_width
+                                on:input=move |ev| {
+                                    set_grid_line_width.set(
+                                        event_target_value(&ev).parse().unwrap_or(GRID_LINE_WIDTH),
+                                    )
+                                }
+                            />
+                            {move || format!("{:.1}px", grid_line_width.get())}
+                        </label>
+                    </div>
+                </div>
+                // Controls of the instanced demo
+                <div style:display=move || if demo.get() == Demo::Instanced { "block" } else { "none" }>
                     <label>
-                        "Blue: "
+                        "Seed: "
                         <input
-                            type="range"
+                            type="number"
                             min="0"
-                            max="1"
-                            step="0.01"
-                            value=color_b
-                            on:input=move |ev| {
-                                set_color_b.set(event_target_value(&ev).parse().unwrap_or(0.0))
+                            style="width: 120px;"
+                            prop:value=move || instance_seed.get().to_string()
+                            on:change=move |ev| {
+                                if let Ok(seed) = event_target_value(&ev).parse() {
+                                    set_seed(seed);
+                                }
                             }
                         />
-                        {move || format!("{:.2}", color_b.get())}
                     </label>
                 </div>
             </div>
-            <canvas
-                node_ref=canvas_ref
-                style="flex: 1; width: 100%;"
-                id="wgpu-canvas"
-            />
+            <Show when=move || app_error.get().is_some()>
+                <div style="padding: 10px; background-color: #fdd; color: #800;">
+                    {move || app_error.get()}
+                </div>
+            </Show>
+            <div style="padding: 4px 10px; background-color: #f0f0f0;">
+                {move || match file_load.get() {
+                    None => "Drop an .obj file onto the canvas to replace the cube".to_string(),
+                    Some(FileLoad::Loading(name)) => format!("Loading {name}..."),
+                    Some(FileLoad::Loaded(name)) => format!("Showing {name}"),
+                    Some(FileLoad::Failed(message)) => format!("Could not load {message}"),
+                }}
+            </div>
+            // The canvas is sized by `debounce_canvas_size`, positioned
+            // absolutely so its size doesn't feed back into the container's.
+            <div style="flex: 1; position: relative; overflow: hidden; min-height: 0;">
+                <canvas
+                    node_ref=canvas_ref
+                    on:dragover=move |ev| ev.prevent_default()
+                    on:drop=drop_file
+                    style="position: absolute; top: 0; left: 0;"
+                    // A checkerboard behind a transparent canvas, showing through
+                    // where the background is see-through.
+                    style:background=move || {
+                        if transparent {
+                            "repeating-conic-gradient(#ccc 0% 25%, #fff 0% 50%) 50% / 32px 32px"
+                        } else {
+                            "none"
+                        }
+                    }
+                    id="wgpu-canvas"
+                    tabindex="0"
+                />
+            </div>
+        </div>
+    }
+}
+
+#[allow(non_snake_case)]
+#[component]
+fn Unsupported() -> impl IntoView {
+    view! {
+        <div style="padding: 10px;">
+            <h1>"WASM Graphics Test with Leptos"</h1>
+            <p>
+                "This demo needs WebGPU or WebGL2, neither of which is available in this "
+                "browser. Try a recent version of Chrome, Edge, Firefox or Safari."
+            </p>
         </div>
     }
 }
 
+fn error_message(err: &StartError) -> String {
+    match err {
+        StartError::NoAdapter => "No suitable graphics adapter was found.".to_string(),
+        StartError::RequestDevice(e) => format!("The graphics device could not be created: {e}"),
+    }
+}
+
+fn query_params() -> Option<web_sys::UrlSearchParams> {
+    web_sys::window()
+        .and_then(|w| w.location().search().ok())
+        .and_then(|search| web_sys::UrlSearchParams::new_with_str(&search).ok())
+}
+
+// Whether the page's query string has the param `name`, with or without a
+// value. `?transparent` makes the canvas background see-through.
+fn query_flag(name: &str) -> bool {
+    query_params().is_some_and(|params| params.has(name))
+}
+
+// The frame rate cap of `?max-fps=N`, `None` without it or if it's not a
+// positive number.
+fn query_max_fps() -> Option<f32> {
+    let max_fps = query_params()?.get("max-fps")?;
+    let parsed = max_fps.parse::<f32>().ok().filter(|fps| *fps > 0.0);
+    if parsed.is_none() {
+        log::warn!("Ignoring invalid max-fps {max_fps:?}");
+    }
+    parsed
+}
+
+// Forwards the visibility of the page, from `visibilitychange` and
+// `document.hidden`, and the focus of the window to the app. The listeners stay
+// for the lifetime of the page.
+fn forward_page_events(handle: CanvasHandle<DemoEvent>) {
+    fn listen(target: &web_sys::EventTarget, name: &str, on_event: impl Fn() + 'static) {
+        let on_event = Closure::<dyn Fn()>::new(on_event);
+        let callback = on_event.as_ref().unchecked_ref();
+        if let Err(e) = target.add_event_listener_with_callback(name, callback) {
+            log::error!("Failed to listen to {name}: {e:?}");
+        }
+        on_event.forget();
+    }
+
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    if let Some(document) = window.document() {
+        let visible_handle = handle.clone();
+        let target = document.clone();
+        listen(&document, "visibilitychange", move || {
+            let _ = visible_handle.send_event(DemoEvent::Visible(!target.hidden()));
+        });
+    }
+    let focus_handle = handle.clone();
+    listen(&window, "focus", move || {
+        let _ = focus_handle.send_event(DemoEvent::Focus(true));
+    });
+    listen(&window, "blur", move || {
+        let _ = handle.send_event(DemoEvent::Focus(false));
+    });
+}
+
+// Sizes `canvas` to fill its parent, debounced by `interval` seconds: while
+// the parent keeps changing size, the canvas keeps its size and the surface
+// keeps presenting at it. The first size is applied right away. The observer
+// stays for the lifetime of the page.
+fn debounce_canvas_size(canvas: &web_sys::HtmlCanvasElement, interval: f64) {
+    let Some(container) = canvas.parent_element() else {
+        return;
+    };
+    let set_size = {
+        let canvas = canvas.clone();
+        move |(width, height): (i32, i32)| {
+            // Not the `style` of leptos' element extensions.
+            let style = web_sys::HtmlElement::style(&canvas);
+            let _ = style.set_property("width", &format!("{width}px"));
+            let _ = style.set_property("height", &format!("{height}px"));
+        }
+    };
+    set_size((container.client_width(), container.client_height()));
+
+    // Each size change starts a timer for when it's due. Timers of sizes
+    // replaced in the meantime find nothing due, the last one applies it.
+    let resizes = Rc::new(RefCell::new(Debounce::new(interval)));
+    let on_resize = Closure::<dyn Fn()>::new({
+        let container = container.clone();
+        move || {
+            let now = now_seconds();
+            let mut pending = resizes.borrow_mut();
+            pending.push((container.client_width(), container.client_height()), now);
+            let Some(due) = pending.due() else {
+                return;
+            };
+            let resizes = resizes.clone();
+            let set_size = set_size.clone();
+            Timeout::new(((due - now) * 1000.0).ceil() as u32, move || {
+                if let Some(size) = resizes.borrow_mut().poll(now_seconds()) {
+                    set_size(size);
+                }
+            })
+            .forget();
+        }
+    });
+    match web_sys::ResizeObserver::new(on_resize.as_ref().unchecked_ref()) {
+        Ok(observer) => {
+            observer.observe(&container);
+            std::mem::forget(observer);
+            on_resize.forget();
+        }
+        Err(e) => log::error!("Failed to observe the canvas size: {e:?}"),
+    }
+}
+
 fn main() {
-    // Set up panic hook
+    // Set up panic hook and logging
     console_error_panic_hook::set_once();
+    let _ = console_log::init_with_level(log::Level::Info);
+
+    let webgpu = is_webgpu_supported();
+    if !webgpu && !is_webgl2_supported() {
+        log::warn!("Neither WebGPU nor WebGL2 is supported, not starting the app");
+        leptos::mount::mount_to_body(Unsupported);
+        return;
+    }
 
     // Create canvas node ref
     let canvas_ref = NodeRef::<html::Canvas>::new();
     let canvas_ref_for_app = canvas_ref.clone();
 
+    let transparent = query_flag("transparent");
+
     // Create the app but don't start it yet
-    let app = SimpleApp::create();
+    let app = DemoApp::create();
     let handle = app.get_handle();
+    // Opaque unless the page asked for a transparent canvas.
+    if transparent {
+        let _ = handle.send_event(DemoEvent::AlphaMode(wgpu::CompositeAlphaMode::PreMultiplied));
+    }
+    if let Some(max_fps) = query_max_fps() {
+        let _ = handle.send_event(AppEvent::MaxFps(Some(max_fps)).into());
+    }
+    forward_page_events(handle.clone());
 
     // Mount Leptos app
     leptos::mount::mount_to_body(move || {
         view! {
-            <App handle=handle canvas_ref=canvas_ref />
+            <App handle=handle canvas_ref=canvas_ref transparent=transparent />
         }
     });
 
@@ -105,12 +1324,32 @@ fn main() {
         // Wait a bit for DOM to be ready
         gloo_timers::future::TimeoutFuture::new(50).await;
 
+        // WebGL2 adapters can't be probed before the runner takes over the
+        // canvas, so no optional features are requested there.
+        let features = if webgpu {
+            match probe_webgpu().await {
+                Ok(features) => features,
+                Err(e) => {
+                    log::error!("Not starting the app: {e:?}");
+                    feedback::send(Feedback::StartFailed(e));
+                    return;
+                }
+            }
+        } else {
+            wgpu::Features::empty()
+        };
+
         // Get the canvas element
         if let Some(canvas) = canvas_ref_for_app.get() {
+            debounce_canvas_size(&canvas, CANVAS_RESIZE_DEBOUNCE);
+            // The surface gets the physical size of the canvas, its CSS size
+            // times `devicePixelRatio` as reported by winit, so edges stay
+            // crisp on high DPI screens.
             app.config(AppConfig {
                 canvas: Some(canvas),
                 use_vsync: true,
                 show_fps: true,
+                features: Some(features),
                 ..default()
             })
             .start();
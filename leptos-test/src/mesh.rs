@@ -0,0 +1,206 @@
+use crate::bounds::Bounds;
+use std::collections::HashMap;
+use std::fmt;
+use trivalibs::prelude::*;
+
+#[derive(Debug)]
+pub enum ObjError {
+    InvalidUtf8,
+    InvalidNumber { line: usize },
+    InvalidIndex { line: usize },
+    NotEnoughVertices { line: usize },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObjError::InvalidUtf8 => write!(f, "obj file is not valid utf-8"),
+            ObjError::InvalidNumber { line } => write!(f, "invalid number on line {line}"),
+            ObjError::InvalidIndex { line } => write!(f, "invalid vertex index on line {line}"),
+            ObjError::NotEnoughVertices { line } => {
+                write!(f, "face with less than 3 vertices on line {line}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+pub type Result<T> = std::result::Result<T, ObjError>;
+
+// Geometry of an OBJ file, with the bounds of its positions.
+#[derive(Debug, Clone)]
+pub struct ObjMesh {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub indices: Vec<u32>,
+    pub bounds: Bounds,
+}
+
+// Parses a Wavefront OBJ file into positions, normals and triangle indices,
+// and computes their bounds.
+//
+// Supports `v`, `vn` and `f` with the `v`, `v/vt`, `v//vn` and `v/vt/vn` face
+// formats, including negative (relative) indices. Polygons are triangulated
+// as fans. Vertices are shared between faces if they use the same position
+// and normal. Vertices without a normal get the average of the normals of
+// their faces. All other directives (`vt`, `o`, `g`, `usemtl`, ...) are
+// skipped.
+pub fn load_obj(bytes: &[u8]) -> Result<ObjMesh> {
+    let text = std::str::from_utf8(bytes).map_err(|_| ObjError::InvalidUtf8)?;
+
+    let mut obj_positions = Vec::new();
+    let mut obj_normals = Vec::new();
+
+    let mut positions = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut has_normal = Vec::new();
+    let mut indices = Vec::new();
+    let mut vertex_map = HashMap::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line_nr = i + 1;
+        let mut parts = line.split_whitespace();
+
+        match parts.next() {
+            Some("v") => obj_positions.push(parse_vec3(parts, line_nr)?),
+            Some("vn") => obj_normals.push(parse_vec3(parts, line_nr)?),
+            Some("f") => {
+                let mut face = Vec::new();
+                for part in parts {
+                    let mut refs = part.split('/');
+                    let pos = resolve_index(refs.next(), obj_positions.len(), line_nr)?
+                        .ok_or(ObjError::InvalidIndex { line: line_nr })?;
+                    let _uv = refs.next();
+                    let normal = resolve_index(refs.next(), obj_normals.len(), line_nr)?;
+
+                    let index = *vertex_map.entry((pos, normal)).or_insert_with(|| {
+                        positions.push(obj_positions[pos]);
+                        normals.push(normal.map_or(Vec3::ZERO, |n| obj_normals[n]));
+                        has_normal.push(normal.is_some());
+                        positions.len() as u32 - 1
+                    });
+                    face.push(index);
+                }
+
+                if face.len() < 3 {
+                    return Err(ObjError::NotEnoughVertices { line: line_nr });
+                }
+                for j in 1..face.len() - 1 {
+                    let tri = [face[0], face[j], face[j + 1]];
+                    indices.extend_from_slice(&tri);
+
+                    let [a, b, c] = tri.map(|t| positions[t as usize]);
+                    let face_normal = (b - a).cross(c - a);
+                    for t in tri {
+                        if !has_normal[t as usize] {
+                            normals[t as usize] += face_normal;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (n, given) in normals.iter_mut().zip(has_normal) {
+        if !given {
+            *n = n.normalize_or_zero();
+        }
+    }
+
+    Ok(ObjMesh {
+        bounds: Bounds::from_points(&positions),
+        positions,
+        normals,
+        indices,
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_obj_file(
+    path: impl AsRef<std::path::Path>,
+) -> std::result::Result<ObjMesh, Box<dyn std::error::Error>> {
+    let bytes = std::fs::read(path)?;
+    Ok(load_obj(&bytes)?)
+}
+
+fn parse_vec3<'a>(mut parts: impl Iterator<Item = &'a str>, line: usize) -> Result<Vec3> {
+    let mut next = || {
+        parts
+            .next()
+            .and_then(|s| s.parse::<f32>().ok())
+            .ok_or(ObjError::InvalidNumber { line })
+    };
+    Ok(vec3(next()?, next()?, next()?))
+}
+
+// OBJ indices are 1-based, negative indices count back from the last element.
+// Returns `None` for missing or empty references, like the normal in `1/2`.
+fn resolve_index(s: Option<&str>, len: usize, line: usize) -> Result<Option<usize>> {
+    let s = match s {
+        Some(s) if !s.is_empty() => s,
+        _ => return Ok(None),
+    };
+    let i: isize = s.parse().map_err(|_| ObjError::InvalidIndex { line })?;
+    let index = if i < 0 { len as isize + i } else { i - 1 };
+    if index < 0 || index as usize >= len {
+        return Err(ObjError::InvalidIndex { line });
+    }
+    Ok(Some(index as usize))
+}
+
+// Per-vertex tangents for normal mapping, for meshes that don't come with
+// them. xyz is the direction of increasing u, made perpendicular to the
+// vertex normal. w is the handedness, 1 or -1, such that
+// `normal.cross(tangent.xyz) * w` points in the direction of increasing v,
+// which is down in the image. Normal maps are expected in that convention
+// (DirectX style, green pointing down).
+//
+// The tangents of all triangles using a vertex are summed up, weighted by
+// their uv area. Triangles with degenerate uvs are skipped, and vertices
+// without any usable triangle get an arbitrary tangent perpendicular to their
+// normal.
+pub fn compute_tangents(
+    positions: &[Vec3],
+    normals: &[Vec3],
+    uvs: &[Vec2],
+    indices: &[u32],
+) -> Vec<Vec4> {
+    let mut u_dirs = vec![Vec3::ZERO; positions.len()];
+    let mut v_dirs = vec![Vec3::ZERO; positions.len()];
+
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| triangle[i] as usize);
+        let e1 = positions[b] - positions[a];
+        let e2 = positions[c] - positions[a];
+        let d1 = uvs[b] - uvs[a];
+        let d2 = uvs[c] - uvs[a];
+        let det = d1.x * d2.y - d2.x * d1.y;
+        if det.abs() < f32::EPSILON {
+            continue;
+        }
+        // Solves `e1 = d1.x * u_dir + d1.y * v_dir`, same for `e2`. Only
+        // dividing by the sign of `det` weights each triangle by its uv area.
+        let sign = det.signum();
+        let u_dir = (e1 * d2.y - e2 * d1.y) * sign;
+        let v_dir = (e2 * d1.x - e1 * d2.x) * sign;
+        for i in [a, b, c] {
+            u_dirs[i] += u_dir;
+            v_dirs[i] += v_dir;
+        }
+    }
+
+    normals
+        .iter()
+        .zip(u_dirs.iter().zip(&v_dirs))
+        .map(|(&n, (&u_dir, &v_dir))| {
+            let tangent = (u_dir - n * n.dot(u_dir)).normalize_or_zero();
+            if tangent == Vec3::ZERO {
+                return n.any_orthonormal_vector().extend(1.0);
+            }
+            let w = if n.cross(tangent).dot(v_dir) < 0.0 { -1.0 } else { 1.0 };
+            tangent.extend(w)
+        })
+        .collect()
+}
@@ -0,0 +1,89 @@
+use crate::surface::view_format;
+use trivalibs::painter::prelude::*;
+
+// Sample count of the multisampled targets of the demos, 4 like the painter's
+// multisampled layers. Falls back to the nearest count the adapter supports,
+// see `msaa_samples`.
+pub const MSAA_SAMPLES: u32 = 4;
+
+// Sample counts a render target can have in wgpu.
+const SAMPLE_COUNTS: [u32; 5] = [1, 2, 4, 8, 16];
+
+// Sample counts the adapter supports for a target rendering into `format`
+// with a `Depth24Plus` depth target, ascending. Always includes 1.
+pub fn supported_sample_counts(adapter: &wgpu::Adapter, format: wgpu::TextureFormat) -> Vec<u32> {
+    let color = adapter.get_texture_format_features(format).flags;
+    let depth = adapter
+        .get_texture_format_features(wgpu::TextureFormat::Depth24Plus)
+        .flags;
+    SAMPLE_COUNTS
+        .into_iter()
+        .filter(|&count| {
+            count == 1
+                || (color.sample_count_supported(count) && depth.sample_count_supported(count))
+        })
+        .collect()
+}
+
+// The supported count closest to `requested`, the lower one on ties. 1 if
+// nothing is supported.
+pub fn nearest_sample_count(requested: u32, supported: &[u32]) -> u32 {
+    supported
+        .iter()
+        .copied()
+        .min_by_key(|&count| (count.abs_diff(requested), count))
+        .unwrap_or(1)
+}
+
+// The sample count to create multisampled targets in the surface's view
+// format with: `requested` if the adapter supports it, otherwise the nearest
+// supported count, with a warning. E.g. mobile GPUs often lack 8x.
+pub fn msaa_samples(p: &Painter, requested: u32) -> u32 {
+    let supported = supported_sample_counts(&p.adapter, view_format(p));
+    let samples = nearest_sample_count(requested, &supported);
+    if samples != requested {
+        log::warn!("{requested}x MSAA not supported, using {samples}x, supported: {supported:?}");
+    }
+    samples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_prefers_lower_count_on_ties() {
+        assert_eq!(nearest_sample_count(4, &[1, 2, 4, 8]), 4);
+        assert_eq!(nearest_sample_count(8, &[1, 2, 4]), 4);
+        assert_eq!(nearest_sample_count(3, &[1, 2, 4]), 2);
+        assert_eq!(nearest_sample_count(8, &[1]), 1);
+        assert_eq!(nearest_sample_count(8, &[]), 1);
+    }
+
+    #[test]
+    fn fallback_is_supported_by_adapter() {
+        let instance = wgpu::Instance::default();
+        let Ok(adapter) = pollster::block_on(instance.request_adapter(&Default::default())) else {
+            eprintln!("No adapter available, skipping");
+            return;
+        };
+
+        for format in [
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            wgpu::TextureFormat::Bgra8UnormSrgb,
+        ] {
+            let supported = supported_sample_counts(&adapter, format);
+            assert!(supported.contains(&1), "{format:?}: {supported:?}");
+
+            let color = adapter.get_texture_format_features(format).flags;
+            for requested in SAMPLE_COUNTS {
+                let samples = nearest_sample_count(requested, &supported);
+                assert!(supported.contains(&samples));
+                assert!(
+                    samples == 1 || color.sample_count_supported(samples),
+                    "{format:?} can't have {samples} samples"
+                );
+            }
+        }
+    }
+}
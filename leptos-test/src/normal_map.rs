@@ -0,0 +1,181 @@
+use crate::gpu::{mat3_columns, sampler, shader, DrawCall, Gpu, Mesh, Program, Uniform};
+use crate::gpu::{SAMPLER_FRAG, TEXTURE_FRAG, UNIFORM_FRAG, UNIFORM_VERT};
+use crate::mesh::compute_tangents;
+use crate::msaa::{msaa_samples, MSAA_SAMPLES};
+use crate::primitives::uv_sphere_with_uvs;
+use crate::scene::normal_mat;
+use crate::surface::{self, Frame};
+use crate::target::Target;
+use crate::texture::texture_from_rgba_linear;
+use std::f32::consts::{PI, TAU};
+use trivalibs::math::transform::Transform;
+use trivalibs::painter::app::Event;
+use trivalibs::painter::prelude::*;
+use trivalibs::prelude::*;
+use trivalibs::rendering::camera::{CamProps, PerspectiveCamera};
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: Vec3,
+    normal: Vec3,
+    uv: Vec2,
+    tangent: Vec4,
+}
+
+// Size of the generated normal map tile, in pixels.
+const MAP_SIZE: u32 = 64;
+// Slope of the bumps, higher is bumpier.
+const BUMP_STRENGTH: f32 = 0.6;
+// u goes around the sphere and v only from pole to pole, so twice as many
+// tiles along u keep them roughly square at the equator.
+const TILING: Vec2 = vec2(12.0, 6.0);
+
+const LIGHT_DIR: Vec3 = vec3(1.0, 1.0, 1.5);
+
+// A rotating sphere with a tiling bump pattern, shaded from a normal map
+// instead of geometry. The tangents are computed from the sphere's uvs.
+pub struct NormalMapApp {
+    gpu: Gpu,
+    cam: PerspectiveCamera,
+    transform: Transform,
+    model_mat: Uniform<Mat4>,
+    normal_mat: Uniform<[Vec4; 3]>,
+    vp_mat: Uniform<Mat4>,
+
+    canvas: Target,
+    draw: DrawCall,
+}
+
+// Tangent-space normals of an egg crate height field,
+// `h = (cos(2πu) + cos(2πv)) / 4`, which tiles seamlessly. Encoded as
+// `n * 0.5 + 0.5`, with rows going down in v like the sphere's uvs.
+fn bump_normal_map() -> Vec<u8> {
+    let mut data = Vec::with_capacity((MAP_SIZE * MAP_SIZE * 4) as usize);
+    for y in 0..MAP_SIZE {
+        for x in 0..MAP_SIZE {
+            let u = (x as f32 + 0.5) / MAP_SIZE as f32;
+            let v = (y as f32 + 0.5) / MAP_SIZE as f32;
+            let dh_du = -0.5 * PI * (TAU * u).sin();
+            let dh_dv = -0.5 * PI * (TAU * v).sin();
+            let n = vec3(-dh_du * BUMP_STRENGTH, -dh_dv * BUMP_STRENGTH, 1.0).normalize();
+            let rgb = ((n * 0.5 + 0.5) * 255.0).round();
+            data.extend_from_slice(&[rgb.x as u8, rgb.y as u8, rgb.z as u8, 255]);
+        }
+    }
+    data
+}
+
+impl CanvasApp<()> for NormalMapApp {
+    fn init(p: &mut Painter) -> Self {
+        let gpu = Gpu::new(p);
+        let program = Program::build(&gpu, shader!("normal_map_vertex"))
+            .with_fragment(shader!("normal_map_fragment"))
+            .with_vertex(&[Float32x3, Float32x3, Float32x2, Float32x4])
+            .with_bindings(&[
+                UNIFORM_VERT,
+                UNIFORM_VERT,
+                UNIFORM_VERT,
+                SAMPLER_FRAG,
+                TEXTURE_FRAG,
+                UNIFORM_FRAG,
+                UNIFORM_FRAG,
+            ])
+            .create();
+
+        let (positions, normals, uvs, indices) = uv_sphere_with_uvs(1.5, 32, 64);
+        let tangents = compute_tangents(&positions, &normals, &uvs, &indices);
+        let vertices: Vec<Vertex> = (0..positions.len())
+            .map(|i| Vertex {
+                position: positions[i],
+                normal: normals[i],
+                uv: uvs[i],
+                tangent: tangents[i],
+            })
+            .collect();
+        let mesh = Mesh::new(&gpu, &vertices).with_indices(&gpu, &indices);
+
+        // Normals are data, not colors, so the map must not be sRGB decoded.
+        let normal_map = texture_from_rgba_linear(&gpu, MAP_SIZE, MAP_SIZE, &bump_normal_map());
+        let sampler = sampler(&gpu, wgpu::FilterMode::Linear, wgpu::AddressMode::Repeat);
+
+        let model_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let normal_mat = Uniform::new(&gpu, mat3_columns(Mat3::IDENTITY));
+        let vp_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let light = Uniform::new(&gpu, LIGHT_DIR.normalize().extend(1.0));
+        let tiling = Uniform::new(&gpu, TILING);
+
+        let canvas = Target::canvas(surface::view_format(p))
+            .with_samples(msaa_samples(p, MSAA_SAMPLES))
+            .with_depth()
+            .create(&gpu, 1, 1);
+        let bind_group = program.bind_group(
+            &gpu,
+            0,
+            &[
+                vp_mat.binding(),
+                model_mat.binding(),
+                normal_mat.binding(),
+                wgpu::BindingResource::Sampler(&sampler),
+                wgpu::BindingResource::TextureView(&normal_map),
+                light.binding(),
+                tiling.binding(),
+            ],
+        );
+        let draw = DrawCall::new(
+            &gpu,
+            &program,
+            &mesh,
+            vec![bind_group],
+            canvas.pipeline_state(),
+        );
+
+        Self {
+            gpu,
+            cam: PerspectiveCamera::create(CamProps {
+                fov: Some(0.6),
+                translation: Some(vec3(0.0, 0.0, 6.0)),
+                ..default()
+            }),
+            transform: Transform::from_translation(Vec3::ZERO),
+            model_mat,
+            normal_mat,
+            vp_mat,
+
+            canvas,
+            draw,
+        }
+    }
+
+    fn resize(&mut self, _p: &mut Painter, width: u32, height: u32) {
+        self.cam.set_aspect_ratio(width as f32 / height as f32);
+        self.canvas.resize(&self.gpu, width, height);
+
+        self.vp_mat.update(&self.gpu, self.cam.view_proj_mat());
+    }
+
+    fn update(&mut self, p: &mut Painter, tpf: f32) {
+        self.transform.rotate_y(tpf * 0.3);
+        let model_mat = self.transform.model_mat();
+        self.model_mat.update(&self.gpu, model_mat);
+        self.normal_mat
+            .update(&self.gpu, mat3_columns(normal_mat(model_mat)));
+        p.request_next_frame();
+    }
+
+    fn render(&self, p: &mut Painter) -> Result<(), SurfaceError> {
+        let Some(frame) = Frame::acquire(p)? else {
+            return Ok(());
+        };
+        let mut encoder = self.gpu.encoder("Normal Map Encoder");
+        self.draw.draw(
+            &mut self.canvas.pass(&mut encoder, Some(&frame.view)),
+            self.canvas.size(),
+        );
+        self.gpu.queue.submit([encoder.finish()]);
+        frame.present();
+        Ok(())
+    }
+
+    fn event(&mut self, _e: Event<()>, _p: &mut Painter) {}
+}
@@ -0,0 +1,153 @@
+use std::f32::consts::FRAC_PI_2;
+use trivalibs::prelude::*;
+
+// Radians of rotation per dragged pixel.
+const ORBIT_SPEED: f32 = 0.005;
+// How fast the camera catches up with the dragged target, per second.
+const ORBIT_DAMPING: f32 = 12.0;
+// Relative change of the distance per scrolled pixel.
+const ZOOM_SPEED: f32 = 0.002;
+// Keep a small margin to the poles, so the view never flips over.
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+// Orbits a camera around a center point, the origin unless framing moved it.
+//
+// Dragging and zooming move the targets immediately, the actual angles and
+// distance follow in `update`, scaled by the frame time. This keeps the orbit
+// speed independent of the framerate, while drags between two frames are never
+// lost.
+pub struct Orbit {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+    pub center: Vec3,
+
+    pub min_distance: f32,
+    pub max_distance: f32,
+
+    initial_distance: f32,
+    target_yaw: f32,
+    target_pitch: f32,
+    target_distance: f32,
+    target_center: Vec3,
+    dragging: bool,
+    last_pointer: Option<Vec2>,
+}
+
+impl Orbit {
+    pub fn new(distance: f32) -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: 0.0,
+            distance,
+            center: Vec3::ZERO,
+            min_distance: 0.0,
+            max_distance: f32::INFINITY,
+            initial_distance: distance,
+            target_yaw: 0.0,
+            target_pitch: 0.0,
+            target_distance: distance,
+            target_center: Vec3::ZERO,
+            dragging: false,
+            last_pointer: None,
+        }
+    }
+
+    pub fn with_distance_range(mut self, min: f32, max: f32) -> Self {
+        self.min_distance = min;
+        self.max_distance = max;
+        self.distance = self.distance.clamp(min, max);
+        self.initial_distance = self.distance;
+        self.target_distance = self.distance;
+        self
+    }
+
+    // Moves the camera back to its initial position. The transition is
+    // animated like any other orbit movement.
+    pub fn reset(&mut self) {
+        self.target_yaw = 0.0;
+        self.target_pitch = 0.0;
+        self.target_distance = self.initial_distance;
+        self.target_center = Vec3::ZERO;
+    }
+
+    // Moves the center and distance, keeping the view direction. Animated,
+    // with the distance clamped to its range.
+    pub fn frame(&mut self, center: Vec3, distance: f32) {
+        self.target_center = center;
+        self.target_distance = distance.clamp(self.min_distance, self.max_distance);
+    }
+
+    // Jumps to the given angles and distance without animation. Pitch and
+    // distance are clamped to their ranges.
+    pub fn set(&mut self, yaw: f32, pitch: f32, distance: f32) {
+        self.target_yaw = yaw;
+        self.target_pitch = pitch.clamp(-MAX_PITCH, MAX_PITCH);
+        self.target_distance = distance.clamp(self.min_distance, self.max_distance);
+        self.yaw = self.target_yaw;
+        self.pitch = self.target_pitch;
+        self.distance = self.target_distance;
+    }
+
+    pub fn start_drag(&mut self) {
+        self.dragging = true;
+    }
+
+    pub fn end_drag(&mut self) {
+        self.dragging = false;
+    }
+
+    pub fn pointer_move(&mut self, pos: Vec2) {
+        if let (true, Some(last)) = (self.dragging, self.last_pointer) {
+            self.drag(pos - last);
+        }
+        self.last_pointer = Some(pos);
+    }
+
+    // Rotates by a dragged distance in pixels.
+    pub fn drag(&mut self, delta: Vec2) {
+        self.target_yaw -= delta.x * ORBIT_SPEED;
+        self.target_pitch =
+            (self.target_pitch + delta.y * ORBIT_SPEED).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    pub fn zoom(&mut self, delta: f32) {
+        self.target_distance = (self.target_distance * (-delta * ZOOM_SPEED).exp())
+            .clamp(self.min_distance, self.max_distance);
+    }
+
+    // Moves the angles towards their targets. Returns whether the camera is
+    // still moving and needs another frame.
+    pub fn update(&mut self, tpf: f32) -> bool {
+        let t = 1.0 - (-ORBIT_DAMPING * tpf).exp();
+        self.yaw += (self.target_yaw - self.yaw) * t;
+        self.pitch += (self.target_pitch - self.pitch) * t;
+        self.distance += (self.target_distance - self.distance) * t;
+        self.center += (self.target_center - self.center) * t;
+
+        let moving = (self.target_yaw - self.yaw).abs() > 0.0001
+            || (self.target_pitch - self.pitch).abs() > 0.0001
+            || (self.target_distance - self.distance).abs() > 0.001
+            || self.target_center.distance(self.center) > 0.001;
+        if !moving {
+            self.yaw = self.target_yaw;
+            self.pitch = self.target_pitch;
+            self.distance = self.target_distance;
+            self.center = self.target_center;
+        }
+        moving
+    }
+
+    pub fn translation(&self) -> Vec3 {
+        vec3(
+            self.yaw.sin() * self.pitch.cos(),
+            self.pitch.sin(),
+            self.yaw.cos() * self.pitch.cos(),
+        ) * self.distance
+            + self.center
+    }
+
+    pub fn view_mat(&self) -> Mat4 {
+        Mat4::look_at_rh(self.translation(), self.center, Vec3::Y)
+    }
+}
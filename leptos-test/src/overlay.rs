@@ -0,0 +1,152 @@
+use crate::gpu::{
+    shader, DrawCall, Gpu, Mesh, PipelineState, Program, RenderStats, Uniform, UNIFORM_FRAG,
+    UNIFORM_VERT,
+};
+use trivalibs::prelude::*;
+
+// Unit square with its corner at the origin, scaled and moved into place by
+// the model matrix of each quad.
+const UNIT_QUAD: [Vec3; 6] = [
+    vec3(0.0, 0.0, 0.0),
+    vec3(1.0, 0.0, 0.0),
+    vec3(1.0, 1.0, 0.0),
+    vec3(0.0, 0.0, 0.0),
+    vec3(1.0, 1.0, 0.0),
+    vec3(0.0, 1.0, 0.0),
+];
+
+// Maps pixel coordinates, (0, 0) to (width, height), to clip space. With
+// `y_down` the origin is the top left corner, like in CSS, otherwise the
+// bottom left.
+pub fn screen_proj_mat(width: f32, height: f32, y_down: bool) -> Mat4 {
+    let (bottom, top) = if y_down { (height, 0.0) } else { (0.0, height) };
+    Mat4::orthographic_rh(0.0, width, bottom, top, -1.0, 1.0)
+}
+
+// 2D quads positioned in physical pixels, for drawing on top of a target
+// after its 3D content, e.g. a crosshair. The projection follows the canvas
+// size through `resize`. Quads have integer pixel edges as long as their
+// rects do, so they stay sharp without multisampling.
+pub struct Overlay {
+    program: Program,
+    mesh: Mesh,
+    proj_mat: Uniform<Mat4>,
+    state: PipelineState,
+    y_down: bool,
+}
+
+// A quad created by `Overlay::quad`. Draw `draw` in a pass after the scene.
+pub struct OverlayQuad {
+    pub draw: DrawCall,
+    model_mat: Uniform<Mat4>,
+}
+
+impl Overlay {
+    // `state` is that of the target the overlay is drawn into. Overlay draws
+    // blend with alpha and cull nothing.
+    pub fn new(gpu: &Gpu, state: PipelineState, y_down: bool) -> Self {
+        let program = Program::build(gpu, shader!("flat_vertex"))
+            .with_fragment(shader!("flat_fragment"))
+            .with_vertex(&[wgpu::VertexFormat::Float32x3])
+            .with_bindings(&[UNIFORM_VERT, UNIFORM_VERT, UNIFORM_FRAG])
+            .create();
+
+        Self {
+            program,
+            mesh: Mesh::new(gpu, &UNIT_QUAD),
+            proj_mat: Uniform::new(gpu, Mat4::IDENTITY),
+            state: PipelineState {
+                cull_mode: None,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                ..state
+            },
+            y_down,
+        }
+    }
+
+    // Pixel to clip space projection, for other overlay programs.
+    pub fn proj_mat(&self) -> &Uniform<Mat4> {
+        &self.proj_mat
+    }
+
+    // The pipeline state of overlay draws.
+    pub fn state(&self) -> PipelineState {
+        self.state
+    }
+
+    pub fn resize(&self, gpu: &Gpu, width: u32, height: u32) {
+        self.proj_mat
+            .update(gpu, screen_proj_mat(width as f32, height as f32, self.y_down));
+    }
+
+    // A quad covering `size` pixels from `pos`, in a linear color, blended
+    // with its alpha.
+    pub fn quad(&self, gpu: &Gpu, pos: Vec2, size: Vec2, color: Vec4) -> OverlayQuad {
+        let model_mat = Uniform::new(gpu, Mat4::IDENTITY);
+        let color_buffer = Uniform::new(gpu, color);
+        let bind_group = self.program.bind_group(
+            gpu,
+            0,
+            &[
+                self.proj_mat.binding(),
+                model_mat.binding(),
+                color_buffer.binding(),
+            ],
+        );
+        let draw = DrawCall::new(gpu, &self.program, &self.mesh, vec![bind_group], self.state);
+        let quad = OverlayQuad { draw, model_mat };
+        quad.set_rect(gpu, pos, size);
+        quad
+    }
+}
+
+impl OverlayQuad {
+    pub fn set_rect(&self, gpu: &Gpu, pos: Vec2, size: Vec2) {
+        self.model_mat.update(
+            gpu,
+            Mat4::from_translation(pos.extend(0.0)) * Mat4::from_scale(size.extend(1.0)),
+        );
+    }
+}
+
+// Four arms around the canvas center, leaving a gap, so the center pixel
+// still shows the scene.
+pub struct Crosshair {
+    arms: [OverlayQuad; 4],
+}
+
+const CROSSHAIR_GAP: f32 = 6.0;
+const CROSSHAIR_LENGTH: f32 = 14.0;
+const CROSSHAIR_THICKNESS: f32 = 2.0;
+
+impl Crosshair {
+    pub fn new(gpu: &Gpu, overlay: &Overlay, color: Vec4) -> Self {
+        let arms = [(); 4].map(|_| overlay.quad(gpu, Vec2::ZERO, Vec2::ZERO, color));
+        Self { arms }
+    }
+
+    pub fn draw(&self, pass: &mut wgpu::RenderPass, size: (u32, u32)) {
+        for arm in &self.arms {
+            arm.draw.draw(pass, size);
+        }
+    }
+
+    pub fn stats(&self) -> RenderStats {
+        self.arms.iter().map(|arm| arm.draw.stats()).sum()
+    }
+
+    // Centers the arms on a canvas of the given size, snapped to whole pixels.
+    pub fn resize(&self, gpu: &Gpu, width: u32, height: u32) {
+        let center = vec2((width / 2) as f32, (height / 2) as f32);
+        let half = CROSSHAIR_THICKNESS * 0.5;
+        let near = CROSSHAIR_GAP;
+        let far = CROSSHAIR_GAP + CROSSHAIR_LENGTH;
+        let horizontal = vec2(CROSSHAIR_LENGTH, CROSSHAIR_THICKNESS);
+        let vertical = vec2(CROSSHAIR_THICKNESS, CROSSHAIR_LENGTH);
+        let [left, right, low, high] = &self.arms;
+        left.set_rect(gpu, center + vec2(-far, -half), horizontal);
+        right.set_rect(gpu, center + vec2(near, -half), horizontal);
+        low.set_rect(gpu, center + vec2(-half, -far), vertical);
+        high.set_rect(gpu, center + vec2(-half, near), vertical);
+    }
+}
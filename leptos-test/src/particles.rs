@@ -0,0 +1,176 @@
+use crate::gpu::{shader, Compute, DrawCall, Gpu, Mesh, PipelineState, Program, Storage, Uniform};
+use crate::gpu::{STORAGE_RW_COMPUTE, STORAGE_VERT, UNIFORM_COMPUTE, UNIFORM_VERT};
+use crate::surface::{self, Frame};
+use crate::target::Target;
+use std::f32::consts::TAU;
+use trivalibs::painter::app::Event;
+use trivalibs::painter::prelude::*;
+use trivalibs::prelude::*;
+use trivalibs::rendering::camera::{CamProps, PerspectiveCamera};
+
+const PARTICLE_COUNT: u32 = 16384;
+// Must match the `threads` of `particles_compute` in the shader crate.
+const WORKGROUP_SIZE: u32 = 64;
+const SPAWN_RADIUS: f32 = 30.0;
+const ORBIT_SPEED: f32 = 6.0;
+// Half the sprite size, as a fraction of the viewport height.
+const PARTICLE_SIZE: f32 = 0.004;
+// Two triangles per sprite, generated from `vertex_index` in the shader.
+const SPRITE_VERTICES: u32 = 6;
+
+// Must match `Particle` in the shader crate. Both fields are 16 byte aligned,
+// so the layout is the same in Rust and in the storage buffer. The w
+// components are unused.
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct Particle {
+    position: Vec4,
+    velocity: Vec4,
+}
+
+// Particles spread on a disc, moving sideways so they circle the center.
+fn particles() -> Vec<Particle> {
+    (0..PARTICLE_COUNT)
+        .map(|i| {
+            let t = i as f32 / PARTICLE_COUNT as f32;
+            // Golden angle spiral, covering the disc evenly.
+            let angle = i as f32 * 2.399_963;
+            let radius = SPAWN_RADIUS * t.sqrt();
+            let (sin, cos) = angle.sin_cos();
+            let height = ((t * TAU * 8.0).sin()) * 2.0;
+            Particle {
+                position: vec4(cos * radius, height, sin * radius, 1.0),
+                velocity: vec4(-sin, 0.0, cos, 0.0) * ORBIT_SPEED,
+            }
+        })
+        .collect()
+}
+
+// Particles attracted to the center, simulated on the GPU. Every frame a
+// compute pass integrates velocities and positions in a storage buffer, which
+// the render pass then reads to draw one instanced quad per particle, so the
+// particle data never leaves the GPU.
+//
+// Quads instead of a point list, since WebGPU points are always one pixel.
+// Their size is set in clip space relative to the viewport height, so
+// particles cover the same part of the canvas at any resolution.
+pub struct ParticlesApp {
+    gpu: Gpu,
+    cam: PerspectiveCamera,
+    vp_mat: Uniform<Mat4>,
+    sprite_size: Uniform<Vec2>,
+    tpf: Uniform<f32>,
+    simulate: Compute,
+
+    canvas: Target,
+    draw: DrawCall,
+}
+
+impl CanvasApp<()> for ParticlesApp {
+    fn init(p: &mut Painter) -> Self {
+        let gpu = Gpu::new(p);
+        let particles_buffer = Storage::new(&gpu, &particles());
+
+        let tpf = Uniform::new(&gpu, 0.0f32);
+        let simulate = Compute::new(
+            &gpu,
+            shader!("particles_compute"),
+            &[STORAGE_RW_COMPUTE, UNIFORM_COMPUTE],
+            &[particles_buffer.binding(), tpf.binding()],
+        );
+
+        let program = Program::build(&gpu, shader!("particles_vertex"))
+            .with_fragment(shader!("particles_fragment"))
+            .with_bindings(&[UNIFORM_VERT, UNIFORM_VERT, STORAGE_VERT])
+            .create();
+
+        let vp_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let sprite_size = Uniform::new(&gpu, Vec2::ZERO);
+
+        let canvas = Target::canvas(surface::view_format(p)).create(&gpu, 1, 1);
+        let bind_group = program.bind_group(
+            &gpu,
+            0,
+            &[
+                vp_mat.binding(),
+                sprite_size.binding(),
+                particles_buffer.binding(),
+            ],
+        );
+        // Overlapping sprites add up, so dense regions glow.
+        let state = PipelineState {
+            cull_mode: None,
+            blend: Some(wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent::OVER,
+            }),
+            ..canvas.pipeline_state()
+        };
+        let draw = DrawCall::new(
+            &gpu,
+            &program,
+            &Mesh::empty(SPRITE_VERTICES),
+            vec![bind_group],
+            state,
+        )
+        .with_instances(PARTICLE_COUNT);
+
+        Self {
+            gpu,
+            cam: PerspectiveCamera::create(CamProps {
+                fov: Some(0.6),
+                translation: Some(vec3(0.0, 40.0, 80.0)),
+                rot_vertical: Some(-0.46),
+                ..default()
+            }),
+            vp_mat,
+            sprite_size,
+            tpf,
+            simulate,
+
+            canvas,
+            draw,
+        }
+    }
+
+    fn resize(&mut self, _p: &mut Painter, width: u32, height: u32) {
+        let aspect_ratio = width as f32 / height as f32;
+        self.cam.set_aspect_ratio(aspect_ratio);
+        self.canvas.resize(&self.gpu, width, height);
+
+        self.vp_mat.update(&self.gpu, self.cam.view_proj_mat());
+        self.sprite_size
+            .update(&self.gpu, vec2(PARTICLE_SIZE / aspect_ratio, PARTICLE_SIZE));
+    }
+
+    fn update(&mut self, p: &mut Painter, tpf: f32) {
+        // Submitted right away, so it runs before the render pass submitted
+        // by `render`, which reads the updated positions.
+        self.tpf.update(&self.gpu, tpf);
+        let mut encoder = self.gpu.encoder("Particles Compute Encoder");
+        self.simulate
+            .dispatch(&mut encoder, PARTICLE_COUNT.div_ceil(WORKGROUP_SIZE));
+        self.gpu.queue.submit([encoder.finish()]);
+        p.request_next_frame();
+    }
+
+    fn render(&self, p: &mut Painter) -> Result<(), SurfaceError> {
+        let Some(frame) = Frame::acquire(p)? else {
+            return Ok(());
+        };
+        let mut encoder = self.gpu.encoder("Particles Encoder");
+        self.draw.draw(
+            &mut self.canvas.pass(&mut encoder, Some(&frame.view)),
+            self.canvas.size(),
+        );
+        self.gpu.queue.submit([encoder.finish()]);
+        frame.present();
+        Ok(())
+    }
+
+    fn event(&mut self, _e: Event<()>, _p: &mut Painter) {}
+}
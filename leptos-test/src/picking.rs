@@ -0,0 +1,86 @@
+use crate::gpu::Gpu;
+use crate::target::Target;
+use trivalibs::prelude::*;
+
+// Color format of pick targets. Each pixel holds the pick value of the object
+// covering it, 0 where there is none. Integer formats can't be multisampled,
+// which would mix the ids of neighboring objects at the edges anyway.
+pub const PICK_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Uint;
+
+// Renders the pick target with `draw`, which records the pick draws into the
+// pass, and passes the value under `pos` to `done`, `None` for the background
+// and for positions outside the target. `pos` is in physical pixels from the
+// top left, like mouse positions. The target must be offscreen, in
+// `PICK_FORMAT`, and cleared to 0.
+//
+// The target is only rendered here, not every frame, so it shows the scene
+// as of the last `Scene::update`. The readback is asynchronous on wasm, so
+// `done` runs a few frames later there, and right away on native.
+pub fn pick(
+    gpu: &Gpu,
+    target: &Target,
+    pos: Vec2,
+    draw: impl FnOnce(&mut wgpu::RenderPass),
+    done: impl FnOnce(Option<u32>) + wgpu::WasmNotSend + 'static,
+) {
+    let (width, height) = target.size();
+    let texture = target.texture().expect("pick targets are offscreen");
+    if pos.x < 0.0 || pos.y < 0.0 || pos.x >= width as f32 || pos.y >= height as f32 {
+        done(None);
+        return;
+    }
+
+    // A single texel, so the row needs no padding.
+    let buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Pick Buffer"),
+        size: 4,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = gpu.encoder("Pick Encoder");
+    draw(&mut target.pass(&mut encoder, None));
+    encoder.copy_texture_to_buffer(
+        wgpu::TexelCopyTextureInfo {
+            origin: wgpu::Origin3d {
+                x: pos.x as u32,
+                y: pos.y as u32,
+                z: 0,
+            },
+            ..texture.as_image_copy()
+        },
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: None,
+                rows_per_image: None,
+            },
+        },
+        wgpu::Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+    );
+    gpu.queue.submit([encoder.finish()]);
+
+    let mapped = buffer.clone();
+    buffer
+        .slice(..)
+        .map_async(wgpu::MapMode::Read, move |result| {
+            if let Err(e) = result {
+                log::error!("Failed to read back pick value: {e}");
+                done(None);
+                return;
+            }
+            let value = {
+                let data = mapped.slice(..).get_mapped_range();
+                u32::from_le_bytes([data[0], data[1], data[2], data[3]])
+            };
+            mapped.unmap();
+            done(Some(value).filter(|&v| v != 0));
+        });
+
+    // Blocks until the callback above ran on native, no-op on wasm.
+    let _ = gpu.device.poll(wgpu::PollType::Wait);
+}
@@ -0,0 +1,332 @@
+use std::f32::consts::{PI, TAU};
+use trivalibs::prelude::*;
+
+// Triangles are wound counter clockwise when seen from outside, matching
+// wgpu's default `FrontFace::Ccw`, so back faces can be culled.
+
+// Per face: normal, and the two in-plane axes with `u.cross(v) == normal`.
+const CUBE_FACES: [(Vec3, Vec3, Vec3); 6] = [
+    (
+        vec3(1.0, 0.0, 0.0),
+        vec3(0.0, 0.0, -1.0),
+        vec3(0.0, 1.0, 0.0),
+    ),
+    (
+        vec3(-1.0, 0.0, 0.0),
+        vec3(0.0, 0.0, 1.0),
+        vec3(0.0, 1.0, 0.0),
+    ),
+    (
+        vec3(0.0, 1.0, 0.0),
+        vec3(1.0, 0.0, 0.0),
+        vec3(0.0, 0.0, -1.0),
+    ),
+    (
+        vec3(0.0, -1.0, 0.0),
+        vec3(1.0, 0.0, 0.0),
+        vec3(0.0, 0.0, 1.0),
+    ),
+    (
+        vec3(0.0, 0.0, 1.0),
+        vec3(1.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+    ),
+    (
+        vec3(0.0, 0.0, -1.0),
+        vec3(-1.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+    ),
+];
+
+// Face corners in counter clockwise order, as factors of the face axes.
+const FACE_CORNERS: [(f32, f32); 4] = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+
+fn face_corner(face: (Vec3, Vec3, Vec3), corner: (f32, f32), half: f32) -> Vec3 {
+    let (n, u, v) = face;
+    (n + u * corner.0 + v * corner.1) * half
+}
+
+// A cube centered at the origin with edge length `size`, sharing its 8 corner
+// vertices between faces. Returns positions and triangle list indices.
+pub fn cube(size: f32) -> (Vec<Vec3>, Vec<u32>) {
+    let half = size * 0.5;
+
+    // Corner index from the signs of its components: bit 0 = x, 1 = y, 2 = z.
+    let positions = (0..8)
+        .map(|i| {
+            let sign = |bit: u32| if i & (1 << bit) != 0 { 1.0 } else { -1.0 };
+            vec3(sign(0), sign(1), sign(2)) * half
+        })
+        .collect();
+
+    let corner_index =
+        |p: Vec3| (p.x > 0.0) as u32 | ((p.y > 0.0) as u32) << 1 | ((p.z > 0.0) as u32) << 2;
+
+    let indices = CUBE_FACES
+        .iter()
+        .flat_map(|&face| {
+            let c = FACE_CORNERS.map(|corner| corner_index(face_corner(face, corner, 1.0)));
+            [c[0], c[1], c[2], c[0], c[2], c[3]]
+        })
+        .collect();
+
+    (positions, indices)
+}
+
+// Like `cube`, but with 4 vertices per face, so that each vertex carries the
+// flat normal of its face. Returns positions, normals and indices.
+pub fn cube_with_normals(size: f32) -> (Vec<Vec3>, Vec<Vec3>, Vec<u32>) {
+    let half = size * 0.5;
+
+    let mut positions = Vec::with_capacity(24);
+    let mut normals = Vec::with_capacity(24);
+    let mut indices = Vec::with_capacity(36);
+
+    for face in CUBE_FACES {
+        let i = positions.len() as u32;
+        for corner in FACE_CORNERS {
+            positions.push(face_corner(face, corner, half));
+            normals.push(face.0);
+        }
+        indices.extend_from_slice(&[i, i + 1, i + 2, i, i + 2, i + 3]);
+    }
+
+    (positions, normals, indices)
+}
+
+// A square in the y = 0 plane centered at the origin, facing up, with edge
+// length `size`. Two triangles sharing the diagonal, so 4 vertices and 6
+// indices. Returns positions and indices.
+pub fn quad(size: f32) -> (Vec<Vec3>, Vec<u32>) {
+    let h = size * 0.5;
+    let positions = vec![
+        vec3(-h, 0.0, h),
+        vec3(h, 0.0, h),
+        vec3(h, 0.0, -h),
+        vec3(-h, 0.0, -h),
+    ];
+    (positions, vec![0, 1, 2, 0, 2, 3])
+}
+
+// A sphere centered at the origin, split into `rings` horizontal bands and
+// `sectors` vertical slices. Each pole is a single vertex connected to the
+// first/last ring by a triangle fan, so no degenerate triangles are produced.
+// Returns positions, normals and indices.
+//
+// Vertex count: `2 + (rings - 1) * sectors`
+// Index count: `6 * sectors * (rings - 1)`
+pub fn uv_sphere(radius: f32, rings: u32, sectors: u32) -> (Vec<Vec3>, Vec<Vec3>, Vec<u32>) {
+    let rings = rings.max(2);
+    let sectors = sectors.max(3);
+
+    let mut normals = Vec::with_capacity((2 + (rings - 1) * sectors) as usize);
+    normals.push(Vec3::Y);
+    for i in 1..rings {
+        let theta = PI * i as f32 / rings as f32;
+        for j in 0..sectors {
+            let phi = TAU * j as f32 / sectors as f32;
+            normals.push(vec3(
+                theta.sin() * phi.sin(),
+                theta.cos(),
+                theta.sin() * phi.cos(),
+            ));
+        }
+    }
+    normals.push(-Vec3::Y);
+
+    let positions = normals.iter().map(|n| *n * radius).collect();
+
+    let top = 0;
+    let bottom = normals.len() as u32 - 1;
+    let idx = |i: u32, j: u32| 1 + (i - 1) * sectors + j % sectors;
+
+    let mut indices = Vec::with_capacity((6 * sectors * (rings - 1)) as usize);
+    for j in 0..sectors {
+        indices.extend_from_slice(&[idx(1, j), idx(1, j + 1), top]);
+    }
+    for i in 1..rings - 1 {
+        for j in 0..sectors {
+            let a = idx(i + 1, j);
+            let b = idx(i + 1, j + 1);
+            let c = idx(i, j + 1);
+            let d = idx(i, j);
+            indices.extend_from_slice(&[a, b, c, a, c, d]);
+        }
+    }
+    for j in 0..sectors {
+        indices.extend_from_slice(&[bottom, idx(rings - 1, j + 1), idx(rings - 1, j)]);
+    }
+
+    (positions, normals, indices)
+}
+
+// Like `uv_sphere`, with texture coordinates: u goes around the sphere from
+// 0 to 1, v from 0 at the top pole to 1 at the bottom. The seam column is
+// duplicated, so u can jump from 1 back to 0, and each pole has one vertex
+// per sector, at the u of the sector's center. Returns positions, normals,
+// uvs and indices.
+//
+// Vertex count: `(rings + 1) * (sectors + 1)`
+// Index count: `6 * sectors * (rings - 1)`
+pub fn uv_sphere_with_uvs(
+    radius: f32,
+    rings: u32,
+    sectors: u32,
+) -> (Vec<Vec3>, Vec<Vec3>, Vec<Vec2>, Vec<u32>) {
+    let rings = rings.max(2);
+    let sectors = sectors.max(3);
+
+    let vertex_count = ((rings + 1) * (sectors + 1)) as usize;
+    let mut normals = Vec::with_capacity(vertex_count);
+    let mut uvs = Vec::with_capacity(vertex_count);
+    for i in 0..=rings {
+        let theta = PI * i as f32 / rings as f32;
+        let pole = i == 0 || i == rings;
+        for j in 0..=sectors {
+            let u = if pole { j as f32 + 0.5 } else { j as f32 } / sectors as f32;
+            let phi = TAU * j as f32 / sectors as f32;
+            normals.push(vec3(
+                theta.sin() * phi.sin(),
+                theta.cos(),
+                theta.sin() * phi.cos(),
+            ));
+            uvs.push(vec2(u, i as f32 / rings as f32));
+        }
+    }
+
+    let positions = normals.iter().map(|n| *n * radius).collect();
+
+    let idx = |i: u32, j: u32| i * (sectors + 1) + j;
+
+    // The quads touching a pole have two corners there, only their other
+    // triangle has an area.
+    let mut indices = Vec::with_capacity((6 * sectors * (rings - 1)) as usize);
+    for i in 0..rings {
+        for j in 0..sectors {
+            let a = idx(i + 1, j);
+            let b = idx(i + 1, j + 1);
+            let c = idx(i, j + 1);
+            let d = idx(i, j);
+            if i < rings - 1 {
+                indices.extend_from_slice(&[a, b, c]);
+            }
+            if i > 0 {
+                indices.extend_from_slice(&[a, c, d]);
+            }
+        }
+    }
+
+    (positions, normals, uvs, indices)
+}
+
+// Lines of a grid on the XZ plane, centered at the origin, with `spacing`
+// between lines and reaching `extent` in each direction. Returns the two end
+// points of each line, for a line list. The extent is rounded down to a
+// multiple of the spacing.
+pub fn grid(spacing: f32, extent: f32) -> Vec<Vec3> {
+    let lines = (extent / spacing).floor() as i32;
+    let end = lines as f32 * spacing;
+
+    (-lines..=lines)
+        .flat_map(|i| {
+            let offset = i as f32 * spacing;
+            [
+                vec3(offset, 0.0, -end),
+                vec3(offset, 0.0, end),
+                vec3(-end, 0.0, offset),
+                vec3(end, 0.0, offset),
+            ]
+        })
+        .collect()
+}
+
+// Vertex of a line drawn as a quad, matching
+// the vertex layout `[Float32x3, Float32x3, Float32x2]`. All 4 corners of a segment
+// carry both end points, the vertex shader offsets them perpendicular to the
+// line on screen.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LineVertex {
+    pub start: Vec3,
+    pub end: Vec3,
+    // x: 0 at the start, 1 at the end. y: -1 or 1 for the side of the line.
+    pub corner: Vec2,
+}
+
+const LINE_CORNERS: [Vec2; 4] = [
+    vec2(0.0, -1.0),
+    vec2(1.0, -1.0),
+    vec2(1.0, 1.0),
+    vec2(0.0, 1.0),
+];
+
+// Turns a line list (pairs of end points) into two triangles per line, so
+// lines can be drawn with any width. Hardware lines are always 1 pixel wide
+// on WebGPU, and can't be antialiased without multisampling. Returns vertices
+// and indices.
+pub fn line_quads(lines: &[Vec3]) -> (Vec<LineVertex>, Vec<u32>) {
+    let mut vertices = Vec::with_capacity(lines.len() * 2);
+    let mut indices = Vec::with_capacity(lines.len() * 3);
+
+    for line in lines.chunks_exact(2) {
+        let i = vertices.len() as u32;
+        for corner in LINE_CORNERS {
+            vertices.push(LineVertex {
+                start: line[0],
+                end: line[1],
+                corner,
+            });
+        }
+        indices.extend_from_slice(&[i, i + 1, i + 2, i, i + 2, i + 3]);
+    }
+
+    (vertices, indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Normal of each triangle by the right hand rule, which points out of the
+    // front face for counter clockwise winding.
+    fn face_normals(positions: &[Vec3], indices: &[u32]) -> Vec<Vec3> {
+        indices
+            .chunks(3)
+            .map(|t| {
+                let [a, b, c] = [0, 1, 2].map(|i| positions[t[i] as usize]);
+                (b - a).cross(c - a)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn quad_is_two_indexed_triangles() {
+        let (positions, indices) = quad(2.0);
+        assert_eq!(positions.len(), 4);
+        assert_eq!(indices.len(), 6);
+        assert!(indices.iter().all(|&i| (i as usize) < positions.len()));
+        for normal in face_normals(&positions, &indices) {
+            assert!(normal.normalize().dot(Vec3::Y) > 0.999, "{normal} faces down");
+        }
+    }
+
+    #[test]
+    fn uv_sphere_counts_match_formula() {
+        for (rings, sectors) in [(2, 3), (3, 4), (8, 16), (16, 32)] {
+            let (positions, normals, indices) = uv_sphere(1.0, rings, sectors);
+            assert_eq!(positions.len() as u32, 2 + (rings - 1) * sectors);
+            assert_eq!(normals.len(), positions.len());
+            assert_eq!(indices.len() as u32, 6 * sectors * (rings - 1));
+            assert!(indices.iter().all(|&i| (i as usize) < positions.len()));
+        }
+    }
+
+    #[test]
+    fn uv_sphere_faces_point_outward() {
+        let (positions, _, indices) = uv_sphere(1.0, 8, 16);
+        for (t, normal) in indices.chunks(3).zip(face_normals(&positions, &indices)) {
+            let center = t.iter().map(|&i| positions[i as usize]).sum::<Vec3>() / 3.0;
+            assert!(normal.dot(center) > 0.0, "triangle {t:?} faces inward");
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use crate::color::linear_to_srgb;
+use crate::cubemap::cubemap_from_fn;
+use crate::fullscreen::{fullscreen_mesh, fullscreen_program, fullscreen_state};
+use crate::gpu::{mat3_columns, sampler, shader, DrawCall, Gpu, Mesh, Program, Uniform};
+use crate::gpu::{CUBE_TEXTURE_FRAG, SAMPLER_FRAG, UNIFORM_FRAG, UNIFORM_VERT};
+use crate::msaa::{msaa_samples, MSAA_SAMPLES};
+use crate::primitives::uv_sphere;
+use crate::scene::normal_mat;
+use crate::surface::{self, Frame};
+use crate::target::Target;
+use trivalibs::painter::app::Event;
+use trivalibs::painter::prelude::*;
+use trivalibs::prelude::*;
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: Vec3,
+    normal: Vec3,
+}
+
+// Pixels per cube map face edge.
+const ENV_SIZE: u32 = 256;
+const SUN_DIR: Vec3 = vec3(0.5, 0.4, -0.8);
+const ZENITH: Vec3 = vec3(0.05, 0.2, 0.6);
+const HORIZON: Vec3 = vec3(0.6, 0.7, 0.85);
+const GROUND_DARK: Vec3 = vec3(0.1, 0.08, 0.06);
+const GROUND_LIGHT: Vec3 = vec3(0.45, 0.4, 0.3);
+
+const CAM_FOV: f32 = 0.8;
+const CAM_DISTANCE: f32 = 6.0;
+const CAM_HEIGHT: f32 = 1.5;
+// Radians per second.
+const CAM_SPEED: f32 = 0.2;
+
+// The environment: a sky gradient with a sun above the horizon, and a checker
+// board ground below, so the orientation of reflections is easy to check.
+// Returns sRGB encoded bytes, for the sRGB cube map texture.
+fn environment(dir: Vec3) -> [u8; 4] {
+    let color = if dir.y >= 0.0 {
+        let sky = HORIZON.lerp(ZENITH, dir.y.sqrt());
+        let sun = dir.dot(SUN_DIR.normalize()).max(0.0).powf(400.0);
+        sky + Vec3::splat(sun * 4.0)
+    } else {
+        // Intersection with a ground plane one unit below the center.
+        let ground = vec2(dir.x, dir.z) / -dir.y;
+        let checker = (ground.x.floor() + ground.y.floor()).rem_euclid(2.0);
+        let tile = GROUND_DARK.lerp(GROUND_LIGHT, checker);
+        // Far away tiles are smaller than a pixel and would only alias.
+        tile.lerp(HORIZON, (1.0 + dir.y).powf(16.0))
+    };
+    let [r, g, b] = color
+        .min(Vec3::ONE)
+        .to_array()
+        .map(|c| (linear_to_srgb(c) * 255.0).round() as u8);
+    [r, g, b, 255]
+}
+
+// A mirror sphere reflecting a generated environment cube map, which is also
+// drawn as the background. The camera circles around the sphere, so the
+// reflection moves against the background.
+pub struct ReflectionApp {
+    gpu: Gpu,
+    vp_mat: Uniform<Mat4>,
+    inv_sky_vp_mat: Uniform<Mat4>,
+    cam_pos: Uniform<Vec4>,
+    aspect_ratio: f32,
+    yaw: f32,
+
+    canvas: Target,
+    sky: DrawCall,
+    sphere: DrawCall,
+}
+
+impl CanvasApp<()> for ReflectionApp {
+    fn init(p: &mut Painter) -> Self {
+        let gpu = Gpu::new(p);
+        let env = cubemap_from_fn(&gpu, ENV_SIZE, environment);
+        let sampler = sampler(&gpu, wgpu::FilterMode::Linear, wgpu::AddressMode::ClampToEdge);
+
+        // A single convex object with back faces culled needs no depth buffer,
+        // it's simply drawn over the background.
+        let canvas = Target::canvas(surface::view_format(p))
+            .with_samples(msaa_samples(p, MSAA_SAMPLES))
+            .create(&gpu, 1, 1);
+
+        let sky_program = fullscreen_program(&gpu, shader!("skybox_fragment"))
+            .with_bindings(&[UNIFORM_FRAG, SAMPLER_FRAG, CUBE_TEXTURE_FRAG])
+            .create();
+        let inv_sky_vp_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let sky_bind_group = sky_program.bind_group(
+            &gpu,
+            0,
+            &[
+                inv_sky_vp_mat.binding(),
+                wgpu::BindingResource::Sampler(&sampler),
+                wgpu::BindingResource::TextureView(&env),
+            ],
+        );
+        let sky = DrawCall::new(
+            &gpu,
+            &sky_program,
+            &fullscreen_mesh(),
+            vec![sky_bind_group],
+            fullscreen_state(canvas.pipeline_state()),
+        );
+
+        let program = Program::build(&gpu, shader!("reflect_vertex"))
+            .with_fragment(shader!("reflect_fragment"))
+            .with_vertex(&[Float32x3, Float32x3])
+            .with_bindings(&[
+                UNIFORM_VERT,
+                UNIFORM_VERT,
+                UNIFORM_VERT,
+                SAMPLER_FRAG,
+                CUBE_TEXTURE_FRAG,
+                UNIFORM_FRAG,
+            ])
+            .create();
+
+        let (positions, normals, indices) = uv_sphere(1.5, 48, 96);
+        let vertices: Vec<Vertex> = positions
+            .into_iter()
+            .zip(normals)
+            .map(|(position, normal)| Vertex { position, normal })
+            .collect();
+        let mesh = Mesh::new(&gpu, &vertices).with_indices(&gpu, &indices);
+
+        // The sphere doesn't move.
+        let model_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let normal_mat_buffer = Uniform::new(&gpu, mat3_columns(normal_mat(Mat4::IDENTITY)));
+        let vp_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let cam_pos = Uniform::new(&gpu, Vec4::ZERO);
+
+        let bind_group = program.bind_group(
+            &gpu,
+            0,
+            &[
+                vp_mat.binding(),
+                model_mat.binding(),
+                normal_mat_buffer.binding(),
+                wgpu::BindingResource::Sampler(&sampler),
+                wgpu::BindingResource::TextureView(&env),
+                cam_pos.binding(),
+            ],
+        );
+        let sphere = DrawCall::new(
+            &gpu,
+            &program,
+            &mesh,
+            vec![bind_group],
+            canvas.pipeline_state(),
+        );
+
+        Self {
+            gpu,
+            vp_mat,
+            inv_sky_vp_mat,
+            cam_pos,
+            aspect_ratio: 1.0,
+            yaw: 0.0,
+
+            canvas,
+            sky,
+            sphere,
+        }
+    }
+
+    fn resize(&mut self, _p: &mut Painter, width: u32, height: u32) {
+        self.aspect_ratio = width as f32 / height as f32;
+        self.canvas.resize(&self.gpu, width, height);
+        self.update_cam();
+    }
+
+    fn update(&mut self, p: &mut Painter, tpf: f32) {
+        self.yaw += tpf * CAM_SPEED;
+        self.update_cam();
+        p.request_next_frame();
+    }
+
+    fn render(&self, p: &mut Painter) -> Result<(), SurfaceError> {
+        let Some(frame) = Frame::acquire(p)? else {
+            return Ok(());
+        };
+        let mut encoder = self.gpu.encoder("Reflection Encoder");
+        {
+            let mut pass = self.canvas.pass(&mut encoder, Some(&frame.view));
+            self.sky.draw(&mut pass, self.canvas.size());
+            self.sphere.draw(&mut pass, self.canvas.size());
+        }
+        self.gpu.queue.submit([encoder.finish()]);
+        frame.present();
+        Ok(())
+    }
+
+    fn event(&mut self, _e: Event<()>, _p: &mut Painter) {}
+}
+
+impl ReflectionApp {
+    fn update_cam(&self) {
+        let eye = vec3(
+            self.yaw.sin() * CAM_DISTANCE,
+            CAM_HEIGHT,
+            self.yaw.cos() * CAM_DISTANCE,
+        );
+        let view = Mat4::look_at_rh(eye, Vec3::ZERO, Vec3::Y);
+        let proj = Mat4::perspective_rh(CAM_FOV, self.aspect_ratio, 0.1, 100.0);
+        // The sky is infinitely far away, only the camera rotation moves it.
+        let sky_view = Mat4::from_mat3(Mat3::from_mat4(view));
+
+        self.vp_mat.update(&self.gpu, proj * view);
+        self.inv_sky_vp_mat
+            .update(&self.gpu, (proj * sky_view).inverse());
+        self.cam_pos.update(&self.gpu, eye.extend(1.0));
+    }
+}
@@ -1,107 +1,1731 @@
+use crate::bounds::BoundedMesh;
+use crate::color::srgb_to_linear;
+use crate::double_click::DoubleClick;
+use crate::feedback::{self, Feedback};
+use crate::fixed_step::FixedStep;
+use crate::fps::FpsCounter;
+use crate::frame::{FrameClock, FrameLimiter};
+use crate::framing::{framing_distance, FRAME_MARGIN};
+use crate::fullscreen::{fullscreen_mesh, fullscreen_program, fullscreen_state};
+use crate::gpu::{
+    mat3_columns, sampler, shader, DrawCall, Gpu, Mesh, PipelineState, Program, ProgramBuilder,
+    RenderStats, Uniform, SAMPLER_FRAG, TEXTURE_FRAG, UNIFORM_FRAG, UNIFORM_VERT,
+};
+use crate::gpu_timer::GpuTimer;
+use crate::input::{input_from_event, Input, KeyCode, MouseButton};
+use crate::mesh::ObjMesh;
+use crate::msaa::{msaa_samples, MSAA_SAMPLES};
+use crate::orbit::Orbit;
+use crate::overlay::{Crosshair, Overlay};
+use crate::picking::{self, PICK_FORMAT};
+use crate::primitives::{cube_with_normals, grid, line_quads, uv_sphere};
+use crate::renderer_info::renderer_info;
+use crate::scene::{ModelMat, ObjectId, Scene, SceneObject};
+use crate::screenshot;
+use crate::surface::{self, Frame};
+use crate::target::Target;
+use crate::text::Text;
+use crate::touch::{Gesture, TouchGestures};
+use crate::util::now_seconds;
+use std::f32::consts::TAU;
+use std::sync::Arc;
 use trivalibs::math::transform::Transform;
 use trivalibs::painter::app::Event;
 use trivalibs::painter::prelude::*;
-use trivalibs::rendering::camera::{CamProps, PerspectiveCamera};
-use trivalibs::rendering::scene::SceneObject;
-use trivalibs::{map, prelude::*};
+use trivalibs::prelude::*;
 
-const VERTICES: &[Vec3] = &[vec3(0.0, 5.0, 0.0), vec3(-2.5, 0., 0.0), vec3(2.5, 0., 0.0)];
+const CUBE_SIZE: f32 = 4.0;
+
+pub const CUBE_SCALE: f32 = 8.0;
+// Scales at or below zero would collapse the cube or mirror it, which flips
+// the triangle winding and culls the front faces instead of the back ones.
+const MIN_SCALE: f32 = 0.1;
+
+// The moon is a child of the cube, so its transform is in the cube's space and
+// it circles the cube as the cube rotates.
+const MOON_RADIUS: f32 = 2.0;
+const MOON_DISTANCE: f32 = 3.5;
+const MOON_SCALE: f32 = 0.25;
+
+// Floor grid below the cube, for orientation when orbiting. Lines fade out
+// towards the extent.
+const GRID_SPACING: f32 = 5.0;
+const GRID_EXTENT: f32 = 100.0;
+const GRID_HEIGHT: f32 = -25.0;
+
+// Ground plane below the objects, just below the grid so the lines stay
+// visible.
+const GROUND_EXTENT: f32 = 60.0;
+const GROUND_HEIGHT: f32 = GRID_HEIGHT - 0.05;
+const GROUND_COLOR: Vec3 = vec3(0.5, 0.5, 0.5);
+// In physical pixels.
+pub const GRID_LINE_WIDTH: f32 = 1.5;
+
+// Interleaved vertex data, matching `with_vertex(&[Float32x3, Float32x3, Float32x3])`.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: Vec3,
+    color: Vec3,
+    normal: Vec3,
+}
+
+pub const LIGHT_DIR: Vec3 = vec3(0.5, 1.0, 0.8);
+pub const LIGHT_INTENSITY: f32 = 1.0;
+
+// Blinn-Phong exponent, higher gives smaller and sharper highlights. Below 1
+// the highlight would spread over the whole lit side, and 0 makes it
+// constant, so the UI range starts at 1.
+pub const SHININESS: f32 = 32.0;
+pub const SHININESS_MIN: f32 = 1.0;
+pub const SHININESS_MAX: f32 = 256.0;
+
+// Must match `PointLight` in the shader crate. Two `Vec4` fields give an
+// array stride of 32 bytes, a multiple of 16 as std140 requires, so the
+// `repr(C)` array has the same layout.
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct PointLight {
+    // xyz: position in world space, w: range, beyond which it adds nothing.
+    position: Vec4,
+    // rgb: linear color, w: intensity.
+    color: Vec4,
+}
+
+pub const MAX_POINT_LIGHTS: usize = 4;
+const _: () = assert!(std::mem::size_of::<[PointLight; MAX_POINT_LIGHTS]>() == 128);
+
+// Point lights around the cube, turned on in this order.
+const POINT_LIGHTS: [PointLight; MAX_POINT_LIGHTS] = [
+    PointLight {
+        position: vec4(28.0, 8.0, 20.0, 50.0),
+        color: vec4(1.0, 0.5, 0.1, 1.5),
+    },
+    PointLight {
+        position: vec4(-28.0, 8.0, 20.0, 50.0),
+        color: vec4(0.1, 0.6, 1.0, 1.5),
+    },
+    PointLight {
+        position: vec4(0.0, 30.0, -20.0, 50.0),
+        color: vec4(1.0, 0.2, 0.8, 1.5),
+    },
+    PointLight {
+        position: vec4(0.0, -28.0, 10.0, 50.0),
+        color: vec4(0.4, 1.0, 0.2, 1.5),
+    },
+];
+
+const KEY_ROTATE_STEP: f32 = 0.1;
+const KEY_TRANSLATE_STEP: f32 = 1.0;
+
+// A left button release within this many pixels of the press is a click,
+// which picks the object under the cursor, further away it ends a drag.
+const CLICK_SLOP: f32 = 4.0;
+
+// Average the fps over the last second by default, and report it every 15
+// frames. The UI offers the other windows in seconds, 0 is the rate of the
+// latest frame only.
+pub const FPS_WINDOW: f32 = 1.0;
+pub const FPS_WINDOWS: [f32; 4] = [0.0, 0.5, 1.0, 5.0];
+const FPS_REPORT_FRAMES: u64 = 15;
+
+// Default duration of color changes in seconds.
+pub const COLOR_TRANSITION_DURATION: f32 = 0.2;
+
+// Default angular speed of the cube in radians per second. The moon spins
+// faster, and tumbling adds rotation around x at a fraction of the speed.
+pub const ROTATION_SPEED: f32 = 0.5;
+const MOON_SPIN_RATIO: f32 = 4.0;
+const TUMBLE_RATIO: f32 = 0.6;
+
+// Default angle of the rotation buttons in step mode, and the duration of
+// the eased transition to the next angle in seconds.
+pub const ROTATION_STEP_DEGREES: f32 = 90.0;
+const ROTATION_STEP_DURATION: f32 = 0.3;
+
+// Steps per second offered for the fixed timestep mode, and the most steps
+// run in one frame, so below 1/8 of the rate the rotation slows down instead
+// of the frame rate spiraling down.
+pub const FIXED_STEP_RATES: [u32; 3] = [30, 60, 120];
+const MAX_FIXED_STEPS: u32 = 8;
+
+pub const FXAA_QUALITY: f32 = 0.75;
+
+// Linear color of the crosshair drawn over the scene.
+const CROSSHAIR_COLOR: Vec4 = vec4(1.0, 1.0, 1.0, 0.8);
+// FPS counter in the top left corner of the canvas, in pixels.
+const FPS_TEXT_POS: Vec2 = vec2(8.0, 8.0);
+const FPS_TEXT_SIZE: f32 = 16.0;
+const FPS_TEXT_COLOR: Vec4 = vec4(1.0, 1.0, 1.0, 0.9);
+
+// Vertical field of view in radians, adjustable between the min and max
+// degrees in the UI.
+pub const CAM_FOV: f32 = 0.6;
+pub const CAM_FOV_MIN_DEGREES: f32 = 10.0;
+pub const CAM_FOV_MAX_DEGREES: f32 = 120.0;
+const CAM_DISTANCE: f32 = 80.0;
+const CAM_MIN_DISTANCE: f32 = 15.0;
+const CAM_MAX_DISTANCE: f32 = 300.0;
+// Clip planes of both projections. The far plane covers the grid seen from
+// the maximum camera distance. The near plane is kept well above 0, since
+// depth precision depends mostly on it.
+const CAM_NEAR: f32 = 1.0;
+const CAM_FAR: f32 = 1000.0;
 
 #[derive(Debug, Clone)]
 pub struct ColorEvent {
     pub r: f32,
     pub g: f32,
     pub b: f32,
+    // Opacity, not sRGB encoded. For the clear color only used if the surface
+    // is composited with premultiplied alpha, see `surface::set_alpha_mode`.
+    pub a: f32,
+}
+
+impl Default for ColorEvent {
+    fn default() -> Self {
+        Self {
+            r: 1.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        }
+    }
+}
+
+impl ColorEvent {
+    // The UI sends sRGB colors, shaders expect linear ones.
+    fn to_linear(&self) -> Vec4 {
+        vec4(
+            srgb_to_linear(self.r),
+            srgb_to_linear(self.g),
+            srgb_to_linear(self.b),
+            self.a.clamp(0.0, 1.0),
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LightEvent {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub intensity: f32,
+}
+
+// Position of the cube, the moon follows as its child.
+#[derive(Debug, Clone)]
+pub struct TranslationEvent {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+// Scale of the cube per axis, clamped to `MIN_SCALE`.
+#[derive(Debug, Clone)]
+pub struct ScaleEvent {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+// Orbit camera angles in radians and distance from the center.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraEvent {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RotationAxis {
+    X,
+    Y,
+    Z,
+    // Rotates around y and x at the same time.
+    Tumble,
+}
+
+#[derive(Debug, Clone)]
+pub struct RotationEvent {
+    pub axis: RotationAxis,
+    // Radians per second, 0 stops all animation.
+    pub speed: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    Perspective,
+    Orthographic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PostMode {
+    Passthrough = 0,
+    Grayscale = 1,
+    Invert = 2,
+}
+
+// Which faces of the cube and moon are culled, for debugging winding. The
+// primitives are wound counter clockwise seen from outside, so `Back` hides
+// the inside faces, which show through a transparent cube without culling,
+// and `Front` shows only them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CullMode {
+    None,
+    Front,
+    Back,
+}
+
+impl CullMode {
+    fn face(self) -> Option<wgpu::Face> {
+        match self {
+            CullMode::None => None,
+            CullMode::Front => Some(wgpu::Face::Front),
+            CullMode::Back => Some(wgpu::Face::Back),
+        }
+    }
+}
+
+// How edges of the scene are smoothed.
+//
+// MSAA renders the scene with several samples per pixel. It only smooths
+// geometry edges, which it does accurately, and costs memory and bandwidth
+// for the multisampled color and depth targets, growing with the sample
+// count and resolution.
+//
+// FXAA renders the scene single sampled and blurs along edges it detects by
+// contrast in the composite pass. It costs a fixed number of texture samples
+// per pixel, independent of the scene, and also smooths edges inside
+// textures and shading, but it blurs fine detail and is less stable while
+// the scene moves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AntiAliasing {
+    Msaa,
+    Fxaa,
+    Off,
+}
+
+#[derive(Debug, Clone)]
+pub enum AppEvent {
+    Color(ColorEvent),
+    Light(LightEvent),
+    ResetCamera,
+    // Moves the camera back or forth along its view direction, and its
+    // center, until all objects are in view.
+    FrameAll,
+    // Jumps to a camera position, e.g. from a shared link.
+    Camera(CameraEvent),
+    Pause(bool),
+    Screenshot,
+    Projection(Projection),
+    PostMode(PostMode),
+    ClearColor(ColorEvent),
+    Wireframe(bool),
+    CullMode(CullMode),
+    // Winding of the front faces of the cube and moon. Clockwise turns them
+    // inside out for culling, for meshes wound the other way.
+    FrontFace(wgpu::FrontFace),
+    // Duration of color transitions in seconds, 0 switches instantly.
+    ColorTransition(f32),
+    Rotation(RotationEvent),
+    // Replaces the continuous rotation by steps of `RotateStep`.
+    StepMode(bool),
+    // Rotates the cube around the rotation axis by the given degrees, y for
+    // tumbling. Only in step mode.
+    RotateStep(f32),
+    // Window of the fps average in seconds, 0 for the latest frame only.
+    FpsWindow(f32),
+    // Length of a fixed time step in seconds for the continuous rotation,
+    // `None` advances it by the frame time instead.
+    FixedTimestep(Option<f32>),
+    Translation(TranslationEvent),
+    Scale(ScaleEvent),
+    // Width of the grid lines in physical pixels.
+    GridLineWidth(f32),
+    // Vertical field of view in degrees.
+    FieldOfView(f32),
+    AntiAliasing(AntiAliasing),
+    // In [0, 1], see `composite_fxaa_fragment`.
+    FxaaQuality(f32),
+    // Color of highlights, independent of the object color. Alpha is ignored.
+    SpecularColor(ColorEvent),
+    // Clamped to `SHININESS_MIN..=SHININESS_MAX`.
+    Shininess(f32),
+    // Number of point lights turned on, up to `MAX_POINT_LIGHTS`.
+    PointLights(u32),
+    // Replaces the cube's mesh, e.g. with a dropped OBJ file, and frames the
+    // scene, as the mesh may have any size.
+    Mesh(Arc<ObjMesh>),
+    // Falls back to `Fifo` if the surface doesn't support it.
+    PresentMode(wgpu::PresentMode),
+    // Caps the frame rate, `None` renders as fast as the present mode allows.
+    MaxFps(Option<f32>),
+    // The page became visible or hidden. Hidden stops animating like a pause.
+    Visible(bool),
+    // The window gained or lost focus. Losing it ends drags, as the release
+    // of the mouse button goes to another window.
+    Focus(bool),
 }
 
 pub struct SimpleApp {
-    cam: PerspectiveCamera,
-    transform: Transform,
-    model_mat: BindingBuffer<Mat4>,
-    vp_mat: BindingBuffer<Mat4>,
-    color: BindingBuffer<Vec4>,
+    orbit: Orbit,
+    // Whether the orbit moved in the last frame, to report when it stops.
+    cam_moving: bool,
+    touches: TouchGestures,
+    projection: Projection,
+    aspect_ratio: f32,
+    fov: f32,
+    objects: Scene,
+    cube: ObjectId,
+    moon: ObjectId,
+    ground: ObjectId,
+    gpu: Gpu,
+    vp_mat: Uniform<Mat4>,
+    // World space camera position, for view dependent shading. Updated
+    // together with `vp_mat`.
+    cam_pos: Uniform<Vec3>,
+    color: Uniform<Vec4>,
+    specular_color: Uniform<Vec4>,
+    shininess: Uniform<f32>,
+    point_lights: Uniform<[PointLight; MAX_POINT_LIGHTS]>,
+    point_light_count: Uniform<u32>,
+    // Color changes are eased from `color_start` to `color_target`, with
+    // `color_progress` going from 0 to 1 over `color_transition` seconds.
+    color_current: Vec4,
+    color_start: Vec4,
+    color_target: Vec4,
+    color_progress: f32,
+    color_transition: f32,
+    light: Uniform<Vec4>,
+    // Elapsed seconds, wrapped at 2π, so the shader's `sin(time)` stays
+    // continuous while f32 precision doesn't degrade over long runs.
+    time: f32,
+    time_buffer: Uniform<f32>,
+    viewport: Uniform<Vec2>,
+    line_width: Uniform<f32>,
+
+    fps: FpsCounter,
+    // Measures scene and canvas passes, if timestamp queries are supported.
+    gpu_timer: Option<GpuTimer>,
+    // Last GPU frame time read back, in milliseconds.
+    gpu_time: Option<f32>,
+
+    rotation_axis: RotationAxis,
+    rotation_speed: f32,
+    // Steps are eased from `step_from` to `step_to`, angles in radians summed
+    // over all steps, with `step_angle` of it applied to the cube so far.
+    // Steps clicked during a transition extend `step_to`, so they add up
+    // instead of cutting each other short.
+    step_mode: bool,
+    step_from: f32,
+    step_to: f32,
+    step_angle: f32,
+    step_progress: f32,
+    // In fixed timestep mode, the rotation is advanced by `fixed_update` in
+    // steps of the same length, whatever the frame rate. Each frame shows it
+    // interpolated between the angles before and after the last step,
+    // `spin_from` and `spin_to`, relative to the cube's current rotation.
+    fixed_step: Option<FixedStep>,
+    spin_from: f32,
+    spin_to: f32,
+
+    // Last mouse position, and where the left button went down.
+    cursor: Vec2,
+    press_pos: Option<Vec2>,
+    // Double clicks and double taps reset the camera. Kept apart, so a tap
+    // followed by a click doesn't count.
+    double_click: DoubleClick,
+    double_tap: DoubleClick,
+
+    paused: bool,
+    // The page or window is hidden. Stops animating like `paused`, without
+    // changing the pause state of the UI.
+    hidden: bool,
+    // Set on resume, so the time spent paused is not added to the next frame.
+    skip_next_tpf: bool,
+    clock: FrameClock,
+    // Set with `AppEvent::MaxFps`.
+    frame_limit: Option<FrameLimiter>,
+    // The limiter skipped the frame in `update`.
+    skip_render: bool,
 
-    canvas: Layer,
+    wireframe_supported: bool,
+    // Object ids, rendered only when clicking.
+    pick: Target,
+    // The objects are rendered offscreen into `scene`, which is then drawn
+    // onto `canvas` by a fullscreen composite pass, followed by the 2D
+    // overlay.
+    scene: Target,
+    canvas: Target,
+    post_mode: Uniform<u32>,
+    // One of the two composites is drawn, depending on the anti-aliasing
+    // mode. Both sample the scene's texture with bind group 1.
+    composite: DrawCall,
+    composite_fxaa: DrawCall,
+    anti_aliasing: AntiAliasing,
+    // `MSAA_SAMPLES`, or the nearest count the adapter supports.
+    msaa_samples: u32,
+    fxaa_quality: Uniform<f32>,
+    overlay: Overlay,
+    crosshair: Crosshair,
+    fps_text: Text,
 }
 
-impl CanvasApp<ColorEvent> for SimpleApp {
+impl CanvasApp<AppEvent> for SimpleApp {
     fn init(p: &mut Painter) -> Self {
-        let shade = p
-            .shade(&[Float32x3])
+        let info = renderer_info(p);
+        log::info!("Renderer: {info}");
+        feedback::send(Feedback::Renderer(info));
+
+        Self::new(
+            Gpu::new(p),
+            surface::view_format(p),
+            msaa_samples(p, MSAA_SAMPLES),
+        )
+    }
+
+    fn resize(&mut self, _p: &mut Painter, width: u32, height: u32) {
+        self.set_size(width, height);
+    }
+
+    fn update(&mut self, p: &mut Painter, tpf: f32) {
+        // Frames skipped by the limiter are neither advanced nor rendered,
+        // their time goes to the next rendered frame.
+        let tpf = match self.frame_limit.as_mut().map(|limiter| limiter.tick(tpf)) {
+            Some(None) => {
+                self.skip_render = true;
+                p.request_next_frame();
+                return;
+            }
+            Some(Some(tpf)) => tpf,
+            None => tpf,
+        };
+        self.skip_render = false;
+        if self.advance(tpf) {
+            p.request_next_frame();
+        }
+    }
+
+    fn render(&self, p: &mut Painter) -> Result<(), SurfaceError> {
+        if self.skip_render {
+            return Ok(());
+        }
+        let Some(frame) = Frame::acquire(p)? else {
+            return Ok(());
+        };
+        if let Some(timer) = &self.gpu_timer {
+            timer.begin(&self.gpu);
+        }
+        let mut encoder = self.gpu.encoder("Frame Encoder");
+        self.draw(&mut encoder, &frame.view);
+        self.gpu.queue.submit([encoder.finish()]);
+        if let Some(timer) = &self.gpu_timer {
+            timer.end(&self.gpu);
+            timer.resolve(&self.gpu);
+        }
+        frame.present();
+        Ok(())
+    }
+
+    fn event(&mut self, e: Event<AppEvent>, p: &mut Painter) {
+        if let Some(input) = input_from_event(&e) {
+            self.input(input, p);
+            return;
+        }
+
+        match e {
+            Event::UserEvent(AppEvent::Color(color)) => {
+                self.set_color(color.to_linear());
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::Light(LightEvent { x, y, z, intensity })) => {
+                self.light.update(&self.gpu, light_value(vec3(x, y, z), intensity));
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::Camera(CameraEvent {
+                yaw,
+                pitch,
+                distance,
+            })) => {
+                self.orbit.set(yaw, pitch, distance);
+                self.update_cam();
+                self.send_camera();
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::ResetCamera) => self.reset_camera(p),
+            Event::UserEvent(AppEvent::FrameAll) => self.frame_all(p),
+            Event::UserEvent(AppEvent::Pause(paused)) => {
+                let was_animating = self.animating();
+                self.paused = paused;
+                self.resume_if_started(was_animating, p);
+            }
+            Event::UserEvent(AppEvent::Visible(visible)) => {
+                let was_animating = self.animating();
+                self.hidden = !visible;
+                self.resume_if_started(was_animating, p);
+            }
+            Event::UserEvent(AppEvent::Focus(false)) => {
+                self.orbit.end_drag();
+                self.press_pos = None;
+            }
+            Event::UserEvent(AppEvent::Translation(TranslationEvent { x, y, z })) => {
+                // Only the translation is replaced, the rotation keeps spinning.
+                if let Some(cube) = self.objects.get_mut(self.cube) {
+                    cube.transform.translation = vec3(x, y, z);
+                }
+                self.objects.update(&self.gpu);
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::Scale(ScaleEvent { x, y, z })) => {
+                if let Some(cube) = self.objects.get_mut(self.cube) {
+                    cube.transform.scale = vec3(x, y, z).max(Vec3::splat(MIN_SCALE));
+                }
+                self.objects.update(&self.gpu);
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::FieldOfView(degrees)) => {
+                // The aspect ratio is kept, only the projection's angle changes.
+                self.fov = degrees
+                    .clamp(CAM_FOV_MIN_DEGREES, CAM_FOV_MAX_DEGREES)
+                    .to_radians();
+                self.update_cam();
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::SpecularColor(color)) => {
+                self.specular_color.update(&self.gpu, color.to_linear());
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::Shininess(shininess)) => {
+                self.shininess
+                    .update(&self.gpu, shininess.clamp(SHININESS_MIN, SHININESS_MAX));
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::PointLights(count)) => {
+                let count = (count as usize).min(MAX_POINT_LIGHTS);
+                self.point_lights.update(&self.gpu, point_light_slots(count));
+                self.point_light_count.update(&self.gpu, count as u32);
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::Mesh(mesh)) => {
+                let size = mesh.bounds.aabb.size().max_element().max(f32::EPSILON);
+                let vertices = colored_vertices(&mesh.positions, mesh.normals.clone(), size);
+                let mesh = BoundedMesh::new(&self.gpu, &vertices, &mesh.indices, &mesh.positions);
+                if let Some(cube) = self.objects.get_mut(self.cube) {
+                    cube.set_mesh(&mesh);
+                }
+                self.objects.update(&self.gpu);
+                self.frame_all(p);
+            }
+            Event::UserEvent(AppEvent::PresentMode(mode)) => {
+                surface::set_present_mode(p, mode);
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::MaxFps(max_fps)) => {
+                self.frame_limit = max_fps.map(FrameLimiter::new);
+            }
+            Event::UserEvent(AppEvent::GridLineWidth(width)) => {
+                self.line_width.update(&self.gpu, width.max(0.0));
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::Rotation(RotationEvent { axis, speed })) => {
+                let was_animating = self.animating();
+                self.rotation_axis = axis;
+                self.rotation_speed = speed;
+                self.resume_if_started(was_animating, p);
+            }
+            Event::UserEvent(AppEvent::StepMode(on)) => {
+                let was_animating = self.animating();
+                self.step_mode = on;
+                // A step still running finishes at its current angle.
+                self.step_to = self.step_angle;
+                self.step_from = self.step_angle;
+                self.step_progress = 1.0;
+                self.resume_if_started(was_animating, p);
+            }
+            Event::UserEvent(AppEvent::RotateStep(degrees)) => {
+                if !self.step_mode {
+                    return;
+                }
+                if self.step_progress >= 1.0 {
+                    // The frame loop was idle, don't count that time.
+                    self.skip_next_tpf = true;
+                }
+                self.step_from = self.step_angle;
+                self.step_to += degrees.to_radians();
+                self.step_progress = 0.0;
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::FpsWindow(window)) => {
+                self.fps.set_window(window);
+            }
+            Event::UserEvent(AppEvent::FixedTimestep(dt)) => {
+                self.fixed_step = dt
+                    .filter(|&dt| dt > 0.0)
+                    .map(|dt| FixedStep::new(dt, MAX_FIXED_STEPS));
+                self.spin_from = 0.0;
+                self.spin_to = 0.0;
+            }
+            Event::UserEvent(AppEvent::Screenshot) => self.screenshot(),
+            Event::UserEvent(AppEvent::ClearColor(color)) => {
+                // Only changes the load op of the next pass, the pipelines stay.
+                // The scene is passed on to the surface with its alpha. Objects
+                // blended over a premultiplied background stay premultiplied,
+                // as `ALPHA_BLENDING` scales the source by its alpha.
+                let c = color.to_linear();
+                let a = if surface::is_premultiplied(p) {
+                    c.w
+                } else {
+                    1.0
+                };
+                self.scene.set_clear_color(Some(wgpu::Color {
+                    r: (c.x * a) as f64,
+                    g: (c.y * a) as f64,
+                    b: (c.z * a) as f64,
+                    a: a as f64,
+                }));
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::ColorTransition(duration)) => {
+                self.color_transition = duration.max(0.0);
+            }
+            Event::UserEvent(AppEvent::Wireframe(wireframe)) => {
+                if !self.wireframe_supported {
+                    log::warn!("Wireframe mode is not supported on this device");
+                    return;
+                }
+                let polygon_mode = if wireframe {
+                    wgpu::PolygonMode::Line
+                } else {
+                    wgpu::PolygonMode::Fill
+                };
+                for object in self.objects.iter_mut() {
+                    let draw = object.draw_mut();
+                    let state = PipelineState {
+                        polygon_mode,
+                        ..draw.state()
+                    };
+                    draw.set_state(&self.gpu, state);
+                }
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::CullMode(mode)) => {
+                // The ground and grid are seen from both sides, so they keep
+                // their cull modes.
+                for id in [self.cube, self.moon] {
+                    if let Some(object) = self.objects.get_mut(id) {
+                        let draw = object.draw_mut();
+                        let state = PipelineState {
+                            cull_mode: mode.face(),
+                            ..draw.state()
+                        };
+                        draw.set_state(&self.gpu, state);
+                    }
+                }
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::FrontFace(front_face)) => {
+                for id in [self.cube, self.moon] {
+                    if let Some(object) = self.objects.get_mut(id) {
+                        let draw = object.draw_mut();
+                        let state = PipelineState {
+                            front_face,
+                            ..draw.state()
+                        };
+                        draw.set_state(&self.gpu, state);
+                    }
+                }
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::AntiAliasing(mode)) => {
+                if mode == self.anti_aliasing {
+                    return;
+                }
+                self.anti_aliasing = mode;
+                let samples = if mode == AntiAliasing::Msaa {
+                    self.msaa_samples
+                } else {
+                    1
+                };
+                self.set_scene_samples(samples);
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::FxaaQuality(quality)) => {
+                self.fxaa_quality.update(&self.gpu, quality.clamp(0.0, 1.0));
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::PostMode(mode)) => {
+                self.post_mode.update(&self.gpu, mode as u32);
+                p.request_next_frame();
+            }
+            Event::UserEvent(AppEvent::Projection(projection)) => {
+                self.projection = projection;
+                self.update_cam();
+                p.request_next_frame();
+            }
+            _ => {}
+        }
+    }
+}
+
+// Colors each vertex by its position, from black at (-,-,-) to white at
+// (+,+,+) of a box with the given edge length.
+fn colored_vertices(positions: &[Vec3], normals: Vec<Vec3>, size: f32) -> Vec<Vertex> {
+    positions
+        .iter()
+        .zip(normals)
+        .map(|(&position, normal)| Vertex {
+            position,
+            color: position / size + 0.5,
+            normal,
+        })
+        .collect()
+}
+
+// Adds the push constant range of the model matrix, if they're used instead
+// of uniform buffers.
+fn with_model_mat(push_constants: bool, program: ProgramBuilder) -> ProgramBuilder {
+    if push_constants {
+        program.with_push_constants(wgpu::ShaderStages::VERTEX, size_of::<Mat4>() as u32)
+    } else {
+        program
+    }
+}
+
+// View projection matrix of the orbit camera, as uploaded to `vp_mat`.
+fn view_proj_mat(projection: Projection, orbit: &Orbit, fov: f32, aspect_ratio: f32) -> Mat4 {
+    let proj_mat = match projection {
+        Projection::Perspective => Mat4::perspective_rh(fov, aspect_ratio, CAM_NEAR, CAM_FAR),
+        Projection::Orthographic => ortho_proj_mat(orbit, fov, aspect_ratio),
+    };
+    proj_mat * orbit.view_mat()
+}
+
+// Orthographic projection showing the same area at the orbit center as the
+// perspective camera, so switching doesn't jump and zooming still works.
+fn ortho_proj_mat(orbit: &Orbit, fov: f32, aspect_ratio: f32) -> Mat4 {
+    let half_height = orbit.distance * (fov * 0.5).tan();
+    let half_width = half_height * aspect_ratio;
+    Mat4::orthographic_rh(
+        -half_width,
+        half_width,
+        -half_height,
+        half_height,
+        CAM_NEAR,
+        CAM_FAR,
+    )
+}
+
+// The first `count` of `POINT_LIGHTS`, the remaining slots zeroed, so they
+// would add nothing even if the shader read them.
+fn point_light_slots(count: usize) -> [PointLight; MAX_POINT_LIGHTS] {
+    std::array::from_fn(|i| if i < count { POINT_LIGHTS[i] } else { PointLight::default() })
+}
+
+// Packs the light into the shader uniform. A zero length direction, e.g. when
+// all UI sliders are at 0, falls back to the default direction instead of
+// producing NaNs in the shader.
+fn light_value(dir: Vec3, intensity: f32) -> Vec4 {
+    let dir = dir.try_normalize().unwrap_or_else(|| LIGHT_DIR.normalize());
+    dir.extend(intensity.max(0.0))
+}
+
+// A square on the XZ plane facing up, in the vertex format of the objects.
+fn ground_plane(extent: f32) -> [Vertex; 4] {
+    [(-1.0, -1.0), (-1.0, 1.0), (1.0, 1.0), (1.0, -1.0)].map(|(x, z)| Vertex {
+        position: vec3(x * extent, 0.0, z * extent),
+        color: GROUND_COLOR,
+        normal: Vec3::Y,
+    })
+}
+
+impl SimpleApp {
+    // Everything but the renderer info, which needs the painter's adapter,
+    // so tests can create the app on a device without surface. `format` is
+    // the format of the views passed to `draw`.
+    fn new(gpu: Gpu, format: wgpu::TextureFormat, msaa_samples: u32) -> Self {
+        // Push constants skip a buffer write per object and frame, but need
+        // the optional `PUSH_CONSTANTS` feature, which WebGPU doesn't offer,
+        // and a `max_push_constant_size` limit that fits a matrix. The app
+        // runner takes no limits, so this checks the ones it requested.
+        // Without them the model matrices are uniform buffers.
+        let push_constants = gpu
+            .device
+            .features()
+            .contains(wgpu::Features::PUSH_CONSTANTS)
+            && gpu.device.limits().max_push_constant_size >= size_of::<Mat4>() as u32;
+        if !push_constants {
+            log::info!("Push constants not supported, using uniform model matrices");
+        }
+        let vertex = if push_constants {
+            shader!("vertex_push")
+        } else {
+            shader!("vertex")
+        };
+        let program = with_model_mat(
+            push_constants,
+            Program::build(&gpu, vertex)
+                .with_fragment(shader!("fragment"))
+                .with_vertex(&[Float32x3, Float32x3, Float32x3])
+                .with_bindings(&[
+                    UNIFORM_VERT,
+                    UNIFORM_VERT,
+                    UNIFORM_FRAG,
+                    UNIFORM_FRAG,
+                    UNIFORM_FRAG,
+                    UNIFORM_VERT,
+                    UNIFORM_FRAG,
+                    UNIFORM_FRAG,
+                    UNIFORM_FRAG,
+                    UNIFORM_FRAG,
+                    UNIFORM_FRAG,
+                ]),
+        )
+        .create();
+
+        let (positions, normals, indices) = cube_with_normals(CUBE_SIZE);
+        let vertices = colored_vertices(&positions, normals, CUBE_SIZE);
+        let cube_mesh = BoundedMesh::new(&gpu, &vertices, &indices, &positions);
+
+        let (positions, normals, indices) = uv_sphere(MOON_RADIUS, 16, 32);
+        let vertices = colored_vertices(&positions, normals, MOON_RADIUS * 2.0);
+        let moon_mesh = BoundedMesh::new(&gpu, &vertices, &indices, &positions);
+
+        let cam = Uniform::new(&gpu, Mat4::IDENTITY);
+        let cam_pos = Uniform::new(&gpu, Vec3::ZERO);
+
+        let initial_color = ColorEvent::default().to_linear();
+        let color = Uniform::new(&gpu, initial_color);
+        let light = Uniform::new(&gpu, light_value(LIGHT_DIR, LIGHT_INTENSITY));
+        let specular_color = Uniform::new(&gpu, Vec4::ONE);
+        let shininess = Uniform::new(&gpu, SHININESS);
+
+        // All slots are always uploaded, the inactive ones zeroed.
+        let point_lights = Uniform::new(&gpu, point_light_slots(0));
+        let point_light_count = Uniform::new(&gpu, 0u32);
+
+        let time_buffer = Uniform::new(&gpu, 0.0f32);
+
+        // Without multisampling, so each pixel holds exactly one id.
+        let pick_vertex = if push_constants {
+            shader!("pick_vertex_push")
+        } else {
+            shader!("pick_vertex")
+        };
+        let pick_program = with_model_mat(
+            push_constants,
+            Program::build(&gpu, pick_vertex)
+                .with_fragment(shader!("pick_fragment"))
+                .with_vertex(&[Float32x3, Float32x3, Float32x3])
+                .with_bindings(&[UNIFORM_VERT, UNIFORM_VERT, UNIFORM_FRAG]),
+        )
+        .create();
+        let pick = Target::offscreen(PICK_FORMAT)
+            .with_clear_color(Some(wgpu::Color::TRANSPARENT))
+            .with_depth()
+            .create(&gpu, 1, 1);
+
+        // Line mode needs the optional `POLYGON_MODE_LINE` feature, which
+        // WebGPU doesn't offer.
+        let wireframe_supported = gpu
+            .device
+            .features()
+            .contains(wgpu::Features::POLYGON_MODE_LINE);
+        if !wireframe_supported {
+            log::info!("Wireframe mode disabled: POLYGON_MODE_LINE is not supported");
+        }
+        feedback::send(Feedback::WireframeSupported(wireframe_supported));
+
+        let scene = Target::offscreen(format)
+            .with_clear_color(Some(wgpu::Color::BLACK))
+            .with_samples(msaa_samples)
+            .with_depth()
+            .create(&gpu, 1, 1);
+
+        // Each object gets its own model and normal matrix, all other uniforms
+        // are shared. All objects can be picked, and are culled by their
+        // bounds.
+        // With push constants, binding 1 is unused by `vertex_push` but still
+        // in the layout, so all objects share one placeholder buffer there.
+        //
+        // Objects are blended with straight (not premultiplied) alpha:
+        // `rgb = src.rgb * src.a + dst.rgb * (1 - src.a)`. They still write
+        // depth, and are drawn in the order they were added without sorting,
+        // so transparent objects only show what was drawn before them.
+        let scene_state = PipelineState {
+            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+            ..scene.pipeline_state()
+        };
+        let unused_model_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let object = |mesh: &BoundedMesh, transform: Transform, tint: &Uniform<Vec4>| {
+            let model_mat = if push_constants {
+                ModelMat::PushConstant
+            } else {
+                ModelMat::Uniform(Uniform::new(&gpu, Mat4::IDENTITY))
+            };
+            let normal_mat = Uniform::new(&gpu, mat3_columns(Mat3::IDENTITY));
+            let model_buffer = match &model_mat {
+                ModelMat::Uniform(buffer) => buffer,
+                ModelMat::PushConstant => &unused_model_mat,
+            };
+            let bind_group = program.bind_group(
+                &gpu,
+                0,
+                &[
+                    cam.binding(),
+                    model_buffer.binding(),
+                    tint.binding(),
+                    light.binding(),
+                    time_buffer.binding(),
+                    normal_mat.binding(),
+                    cam_pos.binding(),
+                    specular_color.binding(),
+                    shininess.binding(),
+                    point_lights.binding(),
+                    point_light_count.binding(),
+                ],
+            );
+            let draw = DrawCall::new(&gpu, &program, &mesh.mesh, vec![bind_group], scene_state);
+            let pick_id = Uniform::new(&gpu, 0u32);
+            let pick_bind_group = pick_program.bind_group(
+                &gpu,
+                0,
+                &[cam.binding(), model_buffer.binding(), pick_id.binding()],
+            );
+            let pick_draw = DrawCall::new(
+                &gpu,
+                &pick_program,
+                &mesh.mesh,
+                vec![pick_bind_group],
+                pick.pipeline_state(),
+            );
+            SceneObject::new(draw, model_mat, transform)
+                .with_normal_mat(normal_mat)
+                .with_pick(pick_draw, pick_id)
+                .with_bounds(mesh.bounds().sphere)
+        };
+
+        let mut objects = Scene::default();
+        let cube = objects.add(object(
+            &cube_mesh,
+            Transform::from_translation(Vec3::ZERO).with_scale(Vec3::splat(CUBE_SCALE)),
+            &color,
+        ));
+        let moon = objects.add(
+            object(
+                &moon_mesh,
+                Transform::from_translation(vec3(MOON_DISTANCE, 0.0, 0.0))
+                    .with_scale(Vec3::splat(MOON_SCALE)),
+                &color,
+            )
+            .with_parent(cube),
+        );
+
+        let ground_vertices = ground_plane(GROUND_EXTENT);
+        let ground_positions = ground_vertices.map(|v| v.position);
+        let ground_mesh =
+            BoundedMesh::new(&gpu, &ground_vertices, &[0, 1, 2, 0, 2, 3], &ground_positions);
+        let ground_tint = Uniform::new(&gpu, Vec4::ONE);
+        let ground = objects.add(object(
+            &ground_mesh,
+            Transform::from_translation(vec3(0.0, GROUND_HEIGHT, 0.0)),
+            &ground_tint,
+        ));
+
+        let grid_program = Program::build(&gpu, shader!("grid_vertex"))
+            .with_fragment(shader!("grid_fragment"))
+            .with_vertex(&[Float32x3, Float32x3, Float32x2])
             .with_bindings(&[
-                BINDING_BUFFER_VERT,
-                BINDING_BUFFER_VERT,
-                BINDING_BUFFER_FRAG,
+                UNIFORM_VERT,
+                UNIFORM_VERT,
+                UNIFORM_VERT,
+                UNIFORM_VERT,
+                UNIFORM_FRAG,
             ])
             .create();
-        load_vertex_shader!(shade, p, "./shader/vertex.spv");
-        load_fragment_shader!(shade, p, "./shader/fragment.spv");
 
-        let form = p.form(VERTICES).create();
+        let (grid_vertices, grid_indices) = line_quads(&grid(GRID_SPACING, GRID_EXTENT));
+        let grid_mesh = Mesh::new(&gpu, &grid_vertices).with_indices(&gpu, &grid_indices);
+        let grid_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let viewport = Uniform::new(&gpu, Vec2::ONE);
+        let line_width = Uniform::new(&gpu, GRID_LINE_WIDTH);
+        let grid_extent = Uniform::new(&gpu, GRID_EXTENT);
+        let grid_bind_group = grid_program.bind_group(
+            &gpu,
+            0,
+            &[
+                cam.binding(),
+                grid_mat.binding(),
+                viewport.binding(),
+                line_width.binding(),
+                grid_extent.binding(),
+            ],
+        );
+        // Added last, so it's blended over the already drawn objects.
+        let grid_draw = DrawCall::new(
+            &gpu,
+            &grid_program,
+            &grid_mesh,
+            vec![grid_bind_group],
+            PipelineState {
+                cull_mode: None,
+                ..scene_state
+            },
+        );
+        objects.add(SceneObject::new(
+            grid_draw,
+            ModelMat::Uniform(grid_mat),
+            Transform::from_translation(vec3(0.0, GRID_HEIGHT, 0.0)),
+        ));
+
+        // Both composites sample the scene with bind group 1, which is
+        // replaced whenever the scene's texture is recreated.
+        let composite_program = fullscreen_program(&gpu, shader!("composite_fragment"))
+            .with_bindings(&[SAMPLER_FRAG, UNIFORM_FRAG])
+            .with_bindings(&[TEXTURE_FRAG])
+            .create();
+        let fxaa_program = fullscreen_program(&gpu, shader!("composite_fxaa_fragment"))
+            .with_bindings(&[SAMPLER_FRAG, UNIFORM_FRAG, UNIFORM_FRAG, UNIFORM_FRAG])
+            .with_bindings(&[TEXTURE_FRAG])
+            .create();
 
-        let model_mat = p.bind_mat4();
-        let cam = p.bind_mat4();
+        let sampler = sampler(&gpu, wgpu::FilterMode::Linear, wgpu::AddressMode::ClampToEdge);
+        let sampler = wgpu::BindingResource::Sampler(&sampler);
+        let post_mode = Uniform::new(&gpu, PostMode::Passthrough as u32);
+        let fxaa_quality = Uniform::new(&gpu, FXAA_QUALITY);
+        let scene_texture = || wgpu::BindingResource::TextureView(scene.view());
 
-        let color = p.bind_vec4();
-        color.update(p, vec4(1.0, 0.0, 0.0, 1.0)); // Initialize with red
+        let canvas = Target::canvas(format).create(&gpu, 1, 1);
+        let canvas_state = fullscreen_state(canvas.pipeline_state());
+        let composite = DrawCall::new(
+            &gpu,
+            &composite_program,
+            &fullscreen_mesh(),
+            vec![
+                composite_program.bind_group(&gpu, 0, &[sampler.clone(), post_mode.binding()]),
+                composite_program.bind_group(&gpu, 1, &[scene_texture()]),
+            ],
+            canvas_state,
+        );
+        let composite_fxaa = DrawCall::new(
+            &gpu,
+            &fxaa_program,
+            &fullscreen_mesh(),
+            vec![
+                fxaa_program.bind_group(
+                    &gpu,
+                    0,
+                    &[
+                        sampler,
+                        post_mode.binding(),
+                        fxaa_quality.binding(),
+                        viewport.binding(),
+                    ],
+                ),
+                fxaa_program.bind_group(&gpu, 1, &[scene_texture()]),
+            ],
+            canvas_state,
+        );
 
-        let shape = p
-            .shape(form, shade)
-            .with_bindings(map! {
-                0 => cam.binding(),
-                1 => model_mat.binding(),
-                2 => color.binding(),
-            })
-            .with_cull_mode(None)
-            .create();
+        // Drawn after the composite, so post effects don't apply to it.
+        let overlay = Overlay::new(&gpu, canvas.pipeline_state(), true);
+        let crosshair = Crosshair::new(&gpu, &overlay, CROSSHAIR_COLOR);
+        let fps_text = Text::new(&gpu, &overlay, FPS_TEXT_COLOR);
 
-        let canvas = p
-            .layer()
-            .with_shape(shape)
-            .with_clear_color(wgpu::Color::BLACK)
-            .with_multisampling()
-            .create();
+        let gpu_timer = GpuTimer::new(&gpu);
+        // Until the first measurement arrives, or for good without support.
+        feedback::send(Feedback::GpuTime(None));
 
-        let transform =
-            Transform::from_translation(vec3(0.0, -20.0, 0.0)).with_scale(Vec3::splat(8.0));
+        let orbit =
+            Orbit::new(CAM_DISTANCE).with_distance_range(CAM_MIN_DISTANCE, CAM_MAX_DISTANCE);
 
         Self {
-            cam: PerspectiveCamera::create(CamProps {
-                fov: Some(0.6),
-                translation: Some(vec3(0.0, 0.0, 80.0)),
-                ..default()
-            }),
-            transform,
-            model_mat,
+            orbit,
+            cam_moving: false,
+            touches: TouchGestures::default(),
+            projection: Projection::Perspective,
+            aspect_ratio: 1.0,
+            fov: CAM_FOV,
+            objects,
+            cube,
+            moon,
+            ground,
+            gpu,
             vp_mat: cam,
+            cam_pos,
             color,
+            specular_color,
+            shininess,
+            point_lights,
+            point_light_count,
+            color_current: initial_color,
+            color_start: initial_color,
+            color_target: initial_color,
+            color_progress: 1.0,
+            color_transition: COLOR_TRANSITION_DURATION,
+            light,
+            time: 0.0,
+            time_buffer,
+            viewport,
+            line_width,
+
+            fps: FpsCounter::new(FPS_WINDOW),
+            gpu_timer,
+            gpu_time: None,
 
+            rotation_axis: RotationAxis::Y,
+            rotation_speed: ROTATION_SPEED,
+            step_mode: false,
+            step_from: 0.0,
+            step_to: 0.0,
+            step_angle: 0.0,
+            step_progress: 1.0,
+            fixed_step: None,
+            spin_from: 0.0,
+            spin_to: 0.0,
+
+            cursor: Vec2::ZERO,
+            press_pos: None,
+            double_click: DoubleClick::default(),
+            double_tap: DoubleClick::default(),
+
+            paused: false,
+            hidden: false,
+            skip_next_tpf: false,
+            clock: FrameClock::default(),
+            frame_limit: None,
+            skip_render: false,
+
+            wireframe_supported,
+            pick,
+            scene,
             canvas,
+            post_mode,
+            composite,
+            composite_fxaa,
+            anti_aliasing: AntiAliasing::Msaa,
+            msaa_samples,
+            fxaa_quality,
+            overlay,
+            crosshair,
+            fps_text,
         }
     }
 
-    fn resize(&mut self, p: &mut Painter, width: u32, height: u32) {
-        self.cam.set_aspect_ratio(width as f32 / height as f32);
+    fn set_size(&mut self, width: u32, height: u32) {
+        self.aspect_ratio = width as f32 / height as f32;
+        self.viewport
+            .update(&self.gpu, vec2(width as f32, height as f32));
+        self.overlay.resize(&self.gpu, width, height);
+        self.crosshair.resize(&self.gpu, width, height);
+        self.canvas.resize(&self.gpu, width, height);
+        self.pick.resize(&self.gpu, width, height);
+        self.scene.resize(&self.gpu, width, height);
+        self.bind_scene_texture();
+        self.update_cam();
+    }
+
+    // Advances the animations by `tpf` seconds and uploads the changes.
+    // Returns whether anything is still moving, so another frame is needed.
+    fn advance(&mut self, tpf: f32) -> bool {
+        let ctx = self.clock.tick(tpf);
+        let tpf = if self.skip_next_tpf {
+            self.skip_next_tpf = false;
+            0.0
+        } else {
+            tpf
+        };
+
+        let cam_moving = self.orbit.update(tpf);
+        self.update_cam();
+        if self.cam_moving && !cam_moving {
+            self.send_camera();
+        }
+        self.cam_moving = cam_moving;
+
+        let color_changing = self.update_color(tpf);
+        let stepping = self.update_step(tpf);
 
-        self.vp_mat.update(p, self.cam.view_proj_mat());
+        // With zero speed or in step mode nothing animates, so frames are only
+        // requested while the camera, color or a step change, like when
+        // paused.
+        if !self.animating() {
+            // Objects may have moved, or entered or left the view.
+            if cam_moving || stepping {
+                self.objects.update(&self.gpu);
+            }
+            return cam_moving || color_changing || stepping;
+        }
+
+        self.fps.push(tpf);
+        if let Some(ms) = self.gpu_timer.as_ref().and_then(|t| t.read(&self.gpu)) {
+            self.gpu_time = Some(ms);
+        }
+        if ctx.frame % FPS_REPORT_FRAMES == 0 {
+            if self.gpu_time.is_some() {
+                feedback::send(Feedback::GpuTime(self.gpu_time));
+            }
+            let fps = self.fps.stats();
+            feedback::send(Feedback::Fps(fps));
+            let stats = self.objects.stats();
+            feedback::send(Feedback::Culling {
+                drawn: stats.drawn,
+                culled: stats.culled,
+            });
+            feedback::send(Feedback::RenderStats(self.render_stats()));
+            let text = format!("FPS: {:.0} ({:.0}-{:.0})", fps.avg, fps.min, fps.max);
+            self.fps_text
+                .set(&self.gpu, &text, FPS_TEXT_POS, FPS_TEXT_SIZE);
+        }
+
+        self.time = (self.time + tpf) % TAU;
+        self.time_buffer.update(&self.gpu, self.time);
+
+        let angle = self.spin_angle(tpf);
+        self.rotate_cube(angle, self.rotation_axis);
+        if let Some(moon) = self.objects.get_mut(self.moon) {
+            moon.transform.rotate_y(angle * MOON_SPIN_RATIO);
+        }
+        self.objects.update(&self.gpu);
+
+        true
     }
 
-    fn update(&mut self, p: &mut Painter, tpf: f32) {
-        self.transform.rotate_y(tpf * 0.5);
-        self.model_mat.update(p, self.transform.model_mat());
+    // Records the frame into `view`, a texture of the canvas format and size:
+    // the scene pass, then the composite and overlay.
+    fn draw(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        {
+            let mut pass = self.scene.pass(encoder, None);
+            self.objects.draw(&mut pass, self.scene.size());
+        }
+        let mut pass = self.canvas.pass(encoder, Some(view));
+        let size = self.canvas.size();
+        self.composite().draw(&mut pass, size);
+        self.crosshair.draw(&mut pass, size);
+        self.fps_text.draw.draw(&mut pass, size);
+    }
+
+    fn composite(&self) -> &DrawCall {
+        if self.anti_aliasing == AntiAliasing::Fxaa {
+            &self.composite_fxaa
+        } else {
+            &self.composite
+        }
+    }
+
+    // What `draw` records.
+    fn render_stats(&self) -> RenderStats {
+        self.objects.render_stats()
+            + self.composite().stats()
+            + self.crosshair.stats()
+            + self.fps_text.draw.stats()
+    }
+
+    // Surface textures can't be copied from, so the frame is drawn once more
+    // into an offscreen texture of the same format and size.
+    fn screenshot(&self) {
+        let (width, height) = self.canvas.size();
+        let target = Target::offscreen(self.canvas.format()).create(&self.gpu, width, height);
+        let mut encoder = self.gpu.encoder("Screenshot Frame Encoder");
+        self.draw(&mut encoder, target.view());
+        self.gpu.queue.submit([encoder.finish()]);
+        if let Some(texture) = target.texture() {
+            screenshot::capture(&self.gpu, texture);
+        }
+    }
+
+    // Starts a transition to the linear color.
+    fn set_color(&mut self, color: Vec4) {
+        self.color_start = self.color_current;
+        self.color_target = color;
+        self.color_progress = 0.0;
+    }
+
+    // Recreates the scene's textures, and switches the objects to pipelines
+    // with the new count.
+    fn set_scene_samples(&mut self, samples: u32) {
+        self.scene.set_sample_count(&self.gpu, samples);
+        for object in self.objects.iter_mut() {
+            let draw = object.draw_mut();
+            let state = PipelineState {
+                sample_count: samples,
+                ..draw.state()
+            };
+            draw.set_state(&self.gpu, state);
+        }
+        self.bind_scene_texture();
+    }
+
+    // The scene's texture is recreated on resize and sample count changes, so
+    // the composites need a new bind group for it.
+    fn bind_scene_texture(&mut self) {
+        let texture = wgpu::BindingResource::TextureView(self.scene.view());
+        for draw in [&mut self.composite, &mut self.composite_fxaa] {
+            let bind_group = draw.program().bind_group(&self.gpu, 1, std::slice::from_ref(&texture));
+            draw.set_bind_group(1, bind_group);
+        }
+    }
+
+    fn send_camera(&self) {
+        feedback::send(Feedback::Camera {
+            yaw: self.orbit.yaw,
+            pitch: self.orbit.pitch,
+            distance: self.orbit.distance,
+        });
+    }
+
+    // Animated back to the initial camera position.
+    fn reset_camera(&mut self, p: &mut Painter) {
+        self.orbit.reset();
         p.request_next_frame();
     }
 
-    fn render(&self, p: &mut Painter) -> Result<(), SurfaceError> {
-        p.paint_and_show(self.canvas)
+    fn animating(&self) -> bool {
+        !self.paused && !self.hidden && !self.step_mode && self.rotation_speed != 0.0
     }
 
-    fn event(&mut self, e: Event<ColorEvent>, p: &mut Painter) {
-        match e {
-            Event::UserEvent(ColorEvent { r, g, b }) => {
-                self.color.update(p, vec4(r, g, b, 1.0));
+    fn rotate_cube(&mut self, angle: f32, axis: RotationAxis) {
+        let Some(cube) = self.objects.get_mut(self.cube) else {
+            return;
+        };
+        let transform = &mut cube.transform;
+        match axis {
+            RotationAxis::X => transform.rotate_x(angle),
+            RotationAxis::Y => transform.rotate_y(angle),
+            RotationAxis::Z => transform.rotate_z(angle),
+            RotationAxis::Tumble => {
+                transform.rotate_y(angle);
+                transform.rotate_x(angle * TUMBLE_RATIO);
+            }
+        }
+    }
+
+    // Angle to rotate the cube by this frame. In fixed timestep mode, runs the
+    // fixed updates due and returns the change of the interpolated angle.
+    fn spin_angle(&mut self, tpf: f32) -> f32 {
+        let Some(fixed_step) = &mut self.fixed_step else {
+            return tpf * self.rotation_speed;
+        };
+        let steps = fixed_step.advance(tpf);
+        let (dt, alpha) = (fixed_step.dt(), fixed_step.alpha());
+        for _ in 0..steps {
+            self.fixed_update(dt);
+        }
+        let angle = self.spin_from + (self.spin_to - self.spin_from) * alpha;
+        // Keeps both relative to the cube, so they stay small.
+        self.spin_from -= angle;
+        self.spin_to -= angle;
+        angle
+    }
+
+    fn fixed_update(&mut self, dt: f32) {
+        self.spin_from = self.spin_to;
+        self.spin_to += dt * self.rotation_speed;
+    }
+
+    // Advances the step transition, rotating the cube by the eased angle
+    // since the last frame. Returns whether it is still running.
+    fn update_step(&mut self, tpf: f32) -> bool {
+        if self.step_progress >= 1.0 {
+            return false;
+        }
+        self.step_progress = (self.step_progress + tpf / ROTATION_STEP_DURATION).min(1.0);
+        let t = self.step_progress;
+        let eased = t * t * (3.0 - 2.0 * t);
+        let angle = self.step_from + (self.step_to - self.step_from) * eased;
+        let axis = match self.rotation_axis {
+            RotationAxis::Tumble => RotationAxis::Y,
+            axis => axis,
+        };
+        self.rotate_cube(angle - self.step_angle, axis);
+        self.step_angle = angle;
+
+        self.step_progress < 1.0
+    }
+
+    // Restarts the frame loop if animation just started again, without adding
+    // the time spent idle to the next frame.
+    fn resume_if_started(&mut self, was_animating: bool, p: &mut Painter) {
+        if !was_animating && self.animating() {
+            self.skip_next_tpf = true;
+            p.request_next_frame();
+        }
+    }
+
+    // Advances the color transition. Returns whether it is still running.
+    fn update_color(&mut self, tpf: f32) -> bool {
+        if self.color_progress >= 1.0 {
+            return false;
+        }
+        self.color_progress = if self.color_transition > 0.0 {
+            (self.color_progress + tpf / self.color_transition).min(1.0)
+        } else {
+            1.0
+        };
+        let t = self.color_progress;
+        let eased = t * t * (3.0 - 2.0 * t);
+        self.color_current = self.color_start.lerp(self.color_target, eased);
+        self.color.update(&self.gpu, self.color_current);
+
+        self.color_progress < 1.0
+    }
+
+    fn update_cam(&mut self) {
+        let vp_mat = view_proj_mat(self.projection, &self.orbit, self.fov, self.aspect_ratio);
+        self.vp_mat.update(&self.gpu, vp_mat);
+        self.objects.set_view_proj(vp_mat);
+        // The orthographic view looks from the same point, only without
+        // perspective.
+        self.cam_pos.update(&self.gpu, self.orbit.translation());
+    }
+
+    // Leaves the camera as it is if there is nothing with bounds to frame.
+    // Like zooming, the distance stays within the orbit's range.
+    fn frame_all(&mut self, p: &mut Painter) {
+        let Some(bounds) = self.objects.bounds() else {
+            return;
+        };
+        let distance = framing_distance(bounds.radius, self.fov, self.aspect_ratio);
+        self.orbit.frame(bounds.center, distance * FRAME_MARGIN);
+        p.request_next_frame();
+    }
+
+    // Reports the name of the object under the cursor to the UI, `None` for
+    // the background, the grid, or a cursor outside the canvas.
+    fn pick_object(&self) {
+        let names = [
+            (self.cube, "cube"),
+            (self.moon, "moon"),
+            (self.ground, "ground"),
+        ];
+        let size = self.pick.size();
+        let draw = |pass: &mut wgpu::RenderPass| self.objects.draw_picks(pass, size);
+        picking::pick(&self.gpu, &self.pick, self.cursor, draw, move |value| {
+            let id = value.and_then(ObjectId::from_pick_value);
+            let name = names.iter().find(|(o, _)| Some(*o) == id).map(|(_, name)| *name);
+            log::info!("Picked {}", name.unwrap_or("nothing"));
+            feedback::send(Feedback::Picked(name));
+        });
+    }
+
+    fn input(&mut self, input: Input, p: &mut Painter) {
+        match input {
+            Input::KeyDown(KeyCode::KeyP) => self.screenshot(),
+            Input::KeyDown(key) => {
+                let Some(cube) = self.objects.get_mut(self.cube) else {
+                    return;
+                };
+                let transform = &mut cube.transform;
+                match key {
+                    KeyCode::ArrowLeft => transform.rotate_y(-KEY_ROTATE_STEP),
+                    KeyCode::ArrowRight => transform.rotate_y(KEY_ROTATE_STEP),
+                    KeyCode::ArrowUp => transform.rotate_x(-KEY_ROTATE_STEP),
+                    KeyCode::ArrowDown => transform.rotate_x(KEY_ROTATE_STEP),
+                    KeyCode::KeyW => transform.translation.y += KEY_TRANSLATE_STEP,
+                    KeyCode::KeyS => transform.translation.y -= KEY_TRANSLATE_STEP,
+                    KeyCode::KeyA => transform.translation.x -= KEY_TRANSLATE_STEP,
+                    KeyCode::KeyD => transform.translation.x += KEY_TRANSLATE_STEP,
+                    _ => return,
+                }
+                self.objects.update(&self.gpu);
+                p.request_next_frame();
+            }
+            Input::KeyUp(_) => {}
+            Input::MouseDown(MouseButton::Left) => {
+                self.orbit.start_drag();
+                self.press_pos = Some(self.cursor);
+            }
+            Input::MouseUp(MouseButton::Left) => {
+                self.orbit.end_drag();
+                let press_pos = self.press_pos.take();
+                if press_pos.is_some_and(|pos| pos.distance(self.cursor) <= CLICK_SLOP) {
+                    self.pick_object();
+                    if self.double_click.click(self.cursor, now_seconds()) {
+                        self.reset_camera(p);
+                    }
+                } else {
+                    self.double_click.cancel();
+                }
+            }
+            Input::MouseMove(pos) => {
+                self.cursor = pos;
+                self.orbit.pointer_move(pos);
+                p.request_next_frame();
+            }
+            Input::Scroll(delta) => {
+                self.orbit.zoom(delta);
+                p.request_next_frame();
+            }
+            Input::TouchStart(id, pos) => self.touches.start(id, pos),
+            Input::TouchEnd(id) => {
+                let tap = self.touches.end(id);
+                if tap.is_some_and(|pos| self.double_tap.click(pos, now_seconds())) {
+                    self.reset_camera(p);
+                }
+            }
+            Input::TouchMove(id, pos) => {
+                match self.touches.move_to(id, pos) {
+                    Some(Gesture::Orbit(delta)) => self.orbit.drag(delta),
+                    Some(Gesture::Zoom(delta)) => self.orbit.zoom(delta),
+                    None => return,
+                }
+                p.request_next_frame();
             }
             _ => {}
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Whether the triangle, in normalized device coordinates, covers the
+    // point, for either winding.
+    fn covers(t: [Vec3; 3], p: Vec2) -> bool {
+        let edge = |a: Vec3, b: Vec3| (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x);
+        let sides = [edge(t[0], t[1]), edge(t[1], t[2]), edge(t[2], t[0])];
+        sides.iter().all(|&s| s >= 0.0) || sides.iter().all(|&s| s <= 0.0)
+    }
+
+    #[test]
+    fn near_triangle_occludes_far() {
+        let orbit = Orbit::new(CAM_DISTANCE);
+        let vp_mat = view_proj_mat(Projection::Perspective, &orbit, CAM_FOV, 1.0);
+        // Two triangles facing the camera, one in front of the origin and one
+        // behind it.
+        let triangle = |z: f32| {
+            [vec3(-1.0, -1.0, z), vec3(1.0, -1.0, z), vec3(0.0, 1.0, z)]
+                .map(|v| vp_mat.project_point3(v))
+        };
+        let near = triangle(2.0);
+        let far = triangle(-2.0);
+        for t in [near, far] {
+            assert!(covers(t, Vec2::ZERO));
+            assert!((0.0..=1.0).contains(&t[0].z), "depth {} is clipped", t[0].z);
+        }
+
+        // Resolve the screen center like the target's depth test: cleared to
+        // 1.0, a fragment passes with `CompareFunction::Less`. The near
+        // triangle has to win in either draw order.
+        for order in [[(near, "near"), (far, "far")], [(far, "far"), (near, "near")]] {
+            let mut depth = 1.0;
+            let mut visible = "clear";
+            for (t, name) in order {
+                if t[0].z < depth {
+                    depth = t[0].z;
+                    visible = name;
+                }
+            }
+            assert_eq!(visible, "near");
+        }
+    }
+
+    // Width and height in normalized device coordinates of a unit square on
+    // the plane facing the camera at `z`.
+    fn projected_size(vp_mat: Mat4, z: f32) -> Vec2 {
+        let a = vp_mat.project_point3(vec3(-0.5, -0.5, z));
+        let b = vp_mat.project_point3(vec3(0.5, 0.5, z));
+        (b - a).truncate().abs()
+    }
+
+    #[test]
+    fn switching_projection_changes_matrix() {
+        let orbit = Orbit::new(CAM_DISTANCE);
+        let perspective = view_proj_mat(Projection::Perspective, &orbit, CAM_FOV, 1.5);
+        let ortho = view_proj_mat(Projection::Orthographic, &orbit, CAM_FOV, 1.5);
+        assert!(!perspective.abs_diff_eq(ortho, 1e-3));
+    }
+
+    #[test]
+    fn projection_follows_aspect_ratio() {
+        let orbit = Orbit::new(CAM_DISTANCE);
+        for projection in [Projection::Perspective, Projection::Orthographic] {
+            for aspect_ratio in [0.5, 1.0, 16.0 / 9.0] {
+                let vp_mat = view_proj_mat(projection, &orbit, CAM_FOV, aspect_ratio);
+                let size = projected_size(vp_mat, 0.0);
+                assert!(
+                    (size.x * aspect_ratio - size.y).abs() < 1e-4,
+                    "{projection:?} at {aspect_ratio}: {size}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn ortho_does_not_shrink_with_distance() {
+        // The camera looks down -z, so the square at +10 is closer.
+        let orbit = Orbit::new(CAM_DISTANCE);
+        let ortho = view_proj_mat(Projection::Orthographic, &orbit, CAM_FOV, 1.0);
+        let near = projected_size(ortho, 10.0);
+        let far = projected_size(ortho, -10.0);
+        assert!(near.abs_diff_eq(far, 1e-5), "{near} != {far}");
+
+        let perspective = view_proj_mat(Projection::Perspective, &orbit, CAM_FOV, 1.0);
+        let near = projected_size(perspective, 10.0);
+        let far = projected_size(perspective, -10.0);
+        assert!(far.x < near.x && far.y < near.y, "{far} is not smaller than {near}");
+    }
+
+    // Renders a frame without window. The cube in the center, without
+    // specular highlights, is tinted red, the top corners show the black
+    // clear color (the bottom ones may show the floor grid). At 50% alpha the
+    // cube blends to half its linear red over the black clear color.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn headless_frame_shows_red_cube_over_clear_color() {
+        use crate::screenshot::read_texture;
+
+        const WIDTH: u32 = 256;
+        const HEIGHT: u32 = 256;
+        // The frame is stored as 8 bit sRGB, so allow a few steps of rounding.
+        const BLEND_TOLERANCE: f32 = 0.02;
+        const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+        let Some(gpu) = Gpu::headless(wgpu::Features::empty()) else {
+            eprintln!("No adapter available, skipping");
+            return;
+        };
+        let mut app = SimpleApp::new(gpu.clone(), FORMAT, MSAA_SAMPLES);
+        app.specular_color.update(&gpu, Vec4::ZERO);
+        app.set_size(WIDTH, HEIGHT);
+        app.advance(0.0);
+
+        let render = |app: &SimpleApp| {
+            let target = Target::offscreen(FORMAT).create(&gpu, WIDTH, HEIGHT);
+            let mut encoder = gpu.encoder("Test Frame Encoder");
+            app.draw(&mut encoder, target.view());
+            gpu.queue.submit([encoder.finish()]);
+            read_texture(&gpu, target.texture().unwrap())
+        };
+        let pixel = |pixels: &[u8], x: u32, y: u32| {
+            let i = ((y * WIDTH + x) * 4) as usize;
+            [pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3]]
+        };
+        let pixels = render(&app);
+        let center = pixel(&pixels, WIDTH / 2, HEIGHT / 2);
+        assert!(center[0] > 0 && center[1] == 0 && center[2] == 0, "center {center:?}");
+        for corner in [pixel(&pixels, 0, 0), pixel(&pixels, WIDTH - 1, 0)] {
+            assert_eq!(corner, [0, 0, 0, 255]);
+        }
+
+        // Same frame with a half transparent cube, applied without transition.
+        let half_red = ColorEvent {
+            a: 0.5,
+            ..ColorEvent::default()
+        };
+        app.color_transition = 0.0;
+        app.set_color(half_red.to_linear());
+        app.advance(0.0);
+
+        // Blending happens on linear values: 0.5 * src + 0.5 * black.
+        let to_linear = |c: u8| srgb_to_linear(c as f32 / 255.0);
+        let blended = pixel(&render(&app), WIDTH / 2, HEIGHT / 2);
+        let expected = to_linear(center[0]) * 0.5;
+        assert!(
+            (to_linear(blended[0]) - expected).abs() <= BLEND_TOLERANCE,
+            "blended center {blended:?}, expected linear red {expected:.3}"
+        );
+        assert_eq!(blended[1..3], [0, 0]);
+    }
+}
@@ -0,0 +1,31 @@
+use trivalibs::painter::prelude::*;
+
+// One line summary of the adapter, backend and the limits most likely to
+// explain rendering differences between machines.
+pub fn renderer_info(p: &Painter) -> String {
+    let info = p.adapter.get_info();
+    let limits = p.device.limits();
+
+    let backend = match info.backend {
+        wgpu::Backend::Vulkan => "Vulkan",
+        wgpu::Backend::Metal => "Metal",
+        wgpu::Backend::Dx12 => "DX12",
+        // The fallback on wasm without WebGPU.
+        wgpu::Backend::Gl if cfg!(target_arch = "wasm32") => "WebGL2",
+        wgpu::Backend::Gl => "GL",
+        wgpu::Backend::BrowserWebGpu => "WebGPU",
+        wgpu::Backend::Noop => "None",
+    };
+    let name = if info.name.is_empty() {
+        "Unknown adapter"
+    } else {
+        &info.name
+    };
+
+    format!(
+        "{name} ({backend}), max texture {}px, max bind groups {}, max uniform buffer {} bytes",
+        limits.max_texture_dimension_2d,
+        limits.max_bind_groups,
+        limits.max_uniform_buffer_binding_size,
+    )
+}
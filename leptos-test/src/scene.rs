@@ -0,0 +1,350 @@
+use crate::bounds::{BoundedMesh, BoundingSphere};
+use crate::frustum::Frustum;
+use crate::gpu::{mat3_columns, DrawCall, Gpu, RenderStats, Uniform};
+use trivalibs::math::transform::Transform;
+use trivalibs::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectId(usize);
+
+impl ObjectId {
+    // The value written to the pick target for this object, never 0, which is
+    // the background.
+    pub fn pick_value(self) -> u32 {
+        self.0 as u32 + 1
+    }
+
+    pub fn from_pick_value(value: u32) -> Option<Self> {
+        value.checked_sub(1).map(|i| Self(i as usize))
+    }
+}
+
+// Where the draw of a scene object reads its model matrix from.
+#[derive(Clone)]
+pub enum ModelMat {
+    Uniform(Uniform<Mat4>),
+    // Vertex stage push constants at offset 0. Needs the `PUSH_CONSTANTS`
+    // feature, which WebGPU doesn't have, and a `max_push_constant_size` of at
+    // least 64 bytes.
+    PushConstant,
+}
+
+// A draw placed in the scene. The scene writes the object's world transform
+// to its model matrix.
+pub struct SceneObject {
+    pub transform: Transform,
+    parent: Option<ObjectId>,
+    draw: DrawCall,
+    model_mat: ModelMat,
+    normal_mat: Option<Uniform<[Vec4; 3]>>,
+    // Draws the object's pick value, written to the uniform by the scene.
+    pick: Option<(DrawCall, Uniform<u32>)>,
+    // In the space of the mesh's positions, before the world transform.
+    bounds: Option<BoundingSphere>,
+}
+
+// Transforms normals like `model_mat` transforms positions: the
+// inverse-transpose of its upper-left 3x3. Equal to that 3x3 up to scale as
+// long as the scale is uniform, but under non-uniform scale only this keeps
+// normals perpendicular to the transformed surface.
+pub fn normal_mat(model_mat: Mat4) -> Mat3 {
+    Mat3::from_mat4(model_mat).inverse().transpose()
+}
+
+impl SceneObject {
+    pub fn new(draw: DrawCall, model_mat: ModelMat, transform: Transform) -> Self {
+        Self {
+            transform,
+            parent: None,
+            draw,
+            model_mat,
+            normal_mat: None,
+            pick: None,
+            bounds: None,
+        }
+    }
+
+    pub fn draw(&self) -> &DrawCall {
+        &self.draw
+    }
+
+    // E.g. to change the pipeline state.
+    pub fn draw_mut(&mut self) -> &mut DrawCall {
+        &mut self.draw
+    }
+
+    // Draws `mesh` instead, with the same programs and bind groups, so the
+    // mesh must have the vertex layout of the old one. Its bounds replace the
+    // object's bounds.
+    pub fn set_mesh(&mut self, mesh: &BoundedMesh) {
+        self.draw.set_mesh(&mesh.mesh);
+        if let Some((pick, _)) = &mut self.pick {
+            pick.set_mesh(&mesh.mesh);
+        }
+        self.bounds = Some(mesh.bounds().sphere);
+    }
+
+    // The object can be picked, drawn with `pick` into the pick target, see
+    // `Scene::draw_picks`. `pick_id` is the pick draw's id uniform. With
+    // `ModelMat::PushConstant`, both draws get the push constants.
+    pub fn with_pick(mut self, pick: DrawCall, pick_id: Uniform<u32>) -> Self {
+        self.pick = Some((pick, pick_id));
+        self
+    }
+
+    // The object is skipped when its bounds are outside the camera frustum.
+    // Objects without bounds are always drawn.
+    pub fn with_bounds(mut self, bounds: BoundingSphere) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    // The scene also writes the normal matrix of the world transform to
+    // `normal_mat`, for draws with lighting.
+    pub fn with_normal_mat(mut self, normal_mat: Uniform<[Vec4; 3]>) -> Self {
+        self.normal_mat = Some(normal_mat);
+        self
+    }
+
+    // The object's transform becomes relative to the parent's world transform.
+    pub fn with_parent(mut self, parent: ObjectId) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+}
+
+// Objects drawn and skipped by the last `Scene::update`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CullStats {
+    pub drawn: u32,
+    pub culled: u32,
+}
+
+// Flat list of scene objects with parent-child transforms.
+//
+// Ids are never reused, and a parent has to be added before its children, so
+// a parent always comes first in `objects`. World matrices can then be
+// computed in a single pass, for any nesting depth.
+#[derive(Default)]
+pub struct Scene {
+    objects: Vec<Option<SceneObject>>,
+    // Set when objects were added, so their pick values need writing.
+    added: bool,
+    // Objects outside of it are not drawn. Picking draws all objects.
+    frustum: Option<Frustum>,
+    // Per entry of `objects`, as of the last `update`.
+    culled: Vec<bool>,
+}
+
+impl Scene {
+    pub fn add(&mut self, object: SceneObject) -> ObjectId {
+        if let Some(parent) = object.parent {
+            assert!(
+                self.get(parent).is_some(),
+                "parent {parent:?} is not in the scene"
+            );
+        }
+        self.objects.push(Some(object));
+        self.added = true;
+        ObjectId(self.objects.len() - 1)
+    }
+
+    // Removes the object and all its descendants.
+    pub fn remove(&mut self, id: ObjectId) {
+        if self.objects.get_mut(id.0).and_then(Option::take).is_none() {
+            return;
+        }
+        for i in id.0 + 1..self.objects.len() {
+            let orphaned = matches!(
+                &self.objects[i],
+                Some(o) if o.parent.is_some_and(|p| self.objects[p.0].is_none())
+            );
+            if orphaned {
+                self.objects[i] = None;
+            }
+        }
+    }
+
+    pub fn get(&self, id: ObjectId) -> Option<&SceneObject> {
+        self.objects.get(id.0)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: ObjectId) -> Option<&mut SceneObject> {
+        self.objects.get_mut(id.0)?.as_mut()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SceneObject> {
+        self.objects.iter().flatten()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut SceneObject> {
+        self.objects.iter_mut().flatten()
+    }
+
+    // The objects not culled by the last `update`.
+    pub fn drawn(&self) -> impl Iterator<Item = &SceneObject> {
+        self.objects
+            .iter()
+            .zip(self.culled.iter().chain(std::iter::repeat(&false)))
+            .filter_map(|(o, &culled)| o.as_ref().filter(|_| !culled))
+    }
+
+    pub fn stats(&self) -> CullStats {
+        let culled = self.culled.iter().filter(|&&c| c).count() as u32;
+        CullStats {
+            drawn: self.iter().count() as u32 - culled,
+            culled,
+        }
+    }
+
+    // Culls objects with bounds against the frustum of `view_proj` from the
+    // next `update` on.
+    pub fn set_view_proj(&mut self, view_proj: Mat4) {
+        self.frustum = Some(Frustum::from_view_proj(view_proj));
+    }
+
+    // World matrix per entry of `objects`, identity for removed ones.
+    fn world_mats(&self) -> Vec<Mat4> {
+        let mut world_mats = vec![Mat4::IDENTITY; self.objects.len()];
+        for (i, object) in self.objects.iter().enumerate() {
+            let Some(object) = object else { continue };
+            let local = object.transform.model_mat();
+            world_mats[i] = match object.parent {
+                Some(parent) => world_mats[parent.0] * local,
+                None => local,
+            };
+        }
+        world_mats
+    }
+
+    // Sphere containing all objects with bounds, culled or not, in world
+    // space. `None` if there are no such objects.
+    pub fn bounds(&self) -> Option<BoundingSphere> {
+        self.objects
+            .iter()
+            .zip(self.world_mats())
+            .filter_map(|(object, mat)| Some(object.as_ref()?.bounds?.transformed(mat)))
+            .reduce(|a, b| a.union(&b))
+    }
+
+    // Writes the world matrix of every object to its model matrix, and its
+    // normal matrix if it has one. The writes are staged by the queue and
+    // uploaded with the next submit. Pick values never change, so they are
+    // only written for objects added since the last call. Then culls the
+    // objects against the frustum.
+    pub fn update(&mut self, gpu: &Gpu) {
+        let world_mats = self.world_mats();
+        let added = std::mem::take(&mut self.added);
+        for (i, object) in self.objects.iter_mut().enumerate() {
+            let Some(object) = object else { continue };
+            let mat = world_mats[i];
+            match &object.model_mat {
+                ModelMat::Uniform(buffer) => buffer.update(gpu, mat),
+                // Recorded with the draw call, no upload needed.
+                ModelMat::PushConstant => {
+                    let picks = object.pick.iter_mut().map(|(pick, _)| pick);
+                    for draw in std::iter::once(&mut object.draw).chain(picks) {
+                        draw.set_push_constants(
+                            wgpu::ShaderStages::VERTEX,
+                            bytemuck::bytes_of(&mat),
+                        );
+                    }
+                }
+            }
+            if let Some(buffer) = &object.normal_mat {
+                buffer.update(gpu, mat3_columns(normal_mat(mat)));
+            }
+            if let (true, Some((_, pick_id))) = (added, &object.pick) {
+                pick_id.update(gpu, ObjectId(i).pick_value());
+            }
+        }
+
+        self.culled = self
+            .objects
+            .iter()
+            .zip(&world_mats)
+            .map(|(object, &mat)| {
+                let bounds = object.as_ref().and_then(|o| o.bounds);
+                match (self.frustum, bounds) {
+                    (Some(frustum), Some(bounds)) => {
+                        !frustum.intersects_sphere(&bounds.transformed(mat))
+                    }
+                    _ => false,
+                }
+            })
+            .collect();
+    }
+
+    // Draws the objects not culled, in the order they were added, into a
+    // pass of a target of `size`.
+    pub fn draw(&self, pass: &mut wgpu::RenderPass, size: (u32, u32)) {
+        for object in self.drawn() {
+            object.draw.draw(pass, size);
+        }
+    }
+
+    // What `draw` records for the objects not culled by the last `update`.
+    pub fn render_stats(&self) -> RenderStats {
+        self.drawn().map(|object| object.draw.stats()).sum()
+    }
+
+    // Draws the pick values of all objects that can be picked.
+    pub fn draw_picks(&self, pass: &mut wgpu::RenderPass, size: (u32, u32)) {
+        for (pick, _) in self.iter().filter_map(|o| o.pick.as_ref()) {
+            pick.draw(pass, size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::cube_with_normals;
+
+    // Cosine of the angle between a normal and the face edges, 0 when
+    // perpendicular.
+    const TOLERANCE: f32 = 1e-5;
+
+    // Rotated before the non-uniform scale, so the faces are sheared and not
+    // just stretched along their own axes.
+    fn stretched_mat() -> Mat4 {
+        Mat4::from_translation(vec3(1.0, -2.0, 0.5))
+            * Mat4::from_scale(vec3(3.0, 1.0, 0.25))
+            * Mat4::from_rotation_y(0.6)
+            * Mat4::from_rotation_x(0.4)
+    }
+
+    // Largest deviation from perpendicular over all faces of the transformed
+    // cube, as the cosine between normal and edge, or `None` if a normal
+    // points inwards.
+    fn max_error(model_mat: Mat4, transform_normal: impl Fn(Vec3) -> Vec3) -> Option<f32> {
+        let (positions, normals, indices) = cube_with_normals(1.0);
+        let center = model_mat.transform_point3(Vec3::ZERO);
+        let mut error: f32 = 0.0;
+        for triangle in indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| {
+                model_mat.transform_point3(positions[triangle[i] as usize])
+            });
+            let normal = transform_normal(normals[triangle[0] as usize]).normalize();
+            if normal.dot((a + b + c) / 3.0 - center) <= 0.0 {
+                return None;
+            }
+            for edge in [b - a, c - a] {
+                error = error.max(normal.dot(edge.normalize()).abs());
+            }
+        }
+        Some(error)
+    }
+
+    #[test]
+    fn normal_mat_keeps_normals_perpendicular() {
+        let model_mat = stretched_mat();
+        let normal_mat = normal_mat(model_mat);
+        let error = max_error(model_mat, |n| normal_mat * n);
+        assert!(error.is_some_and(|e| e <= TOLERANCE), "error {error:?}");
+
+        // Transforming normals with the model matrix itself gets them wrong.
+        let model_error = max_error(model_mat, |n| model_mat.transform_vector3(n));
+        assert!(model_error.is_none_or(|e| e > TOLERANCE), "error {model_error:?}");
+    }
+}
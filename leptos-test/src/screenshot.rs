@@ -0,0 +1,320 @@
+use crate::gpu::Gpu;
+
+// File written by native screenshots, relative to the working directory.
+#[cfg(not(target_arch = "wasm32"))]
+pub const SCREENSHOT_PATH: &str = "screenshot.png";
+
+// File name suggested to the browser for downloaded screenshots.
+#[cfg(target_arch = "wasm32")]
+pub const SCREENSHOT_FILE_NAME: &str = "screenshot.png";
+
+// Rows copied from a texture into a buffer must be padded to
+// `COPY_BYTES_PER_ROW_ALIGNMENT` (256) bytes.
+fn padded_bytes_per_row(width: u32) -> u32 {
+    let unpadded = width * 4;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    unpadded.div_ceil(align) * align
+}
+
+// Removes the row padding and converts bgra to rgba if needed.
+fn unpad_rows(data: &[u8], width: u32, height: u32, bytes_per_row: u32, bgra: bool) -> Vec<u8> {
+    let row_len = (width * 4) as usize;
+    let mut rgba = Vec::with_capacity(row_len * height as usize);
+    for row in data.chunks(bytes_per_row as usize).take(height as usize) {
+        rgba.extend_from_slice(&row[..row_len]);
+    }
+    if bgra {
+        for px in rgba.chunks_mut(4) {
+            px.swap(0, 2);
+        }
+    }
+    rgba
+}
+
+// Returns a single sampled texture with the contents of `texture`, which can
+// be copied to a buffer. Multisampled textures can't be copied directly, so
+// they are resolved into a new texture by an empty render pass that loads
+// the samples and writes the resolve target. This relies on the samples
+// having been stored by the pass that rendered them (`StoreOp::Store`).
+pub fn resolved_texture(gpu: &Gpu, texture: &wgpu::Texture) -> wgpu::Texture {
+    if texture.sample_count() == 1 {
+        return texture.clone();
+    }
+    let resolved = gpu.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Screenshot Resolve Texture"),
+        size: texture.size(),
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: texture.format(),
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+
+    let samples_view = texture.create_view(&Default::default());
+    let resolved_view = resolved.create_view(&Default::default());
+    let mut encoder = gpu.encoder("Screenshot Resolve Encoder");
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Screenshot Resolve Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: &samples_view,
+            depth_slice: None,
+            resolve_target: Some(&resolved_view),
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        ..Default::default()
+    });
+    gpu.queue.submit([encoder.finish()]);
+    resolved
+}
+
+// Copies the texture into a mappable buffer, resolving it first if it is
+// multisampled. The rows in the buffer are padded to `bytes_per_row`.
+struct Readback {
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    bytes_per_row: u32,
+    bgra: bool,
+}
+
+impl Readback {
+    fn start(gpu: &Gpu, texture: &wgpu::Texture) -> Self {
+        let texture = resolved_texture(gpu, texture);
+        let size = texture.size();
+        let (width, height) = (size.width, size.height);
+        let bgra = matches!(
+            texture.format(),
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        );
+
+        let bytes_per_row = padded_bytes_per_row(width);
+        let buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Screenshot Buffer"),
+            size: (bytes_per_row * height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = gpu.encoder("Screenshot Encoder");
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            size,
+        );
+        gpu.queue.submit([encoder.finish()]);
+
+        Self {
+            buffer,
+            width,
+            height,
+            bytes_per_row,
+            bgra,
+        }
+    }
+
+    // Maps the buffer and passes the unpadded rgba rows, width and height to
+    // `done`.
+    fn map(
+        self,
+        done: impl FnOnce(Result<Vec<u8>, wgpu::BufferAsyncError>, u32, u32)
+            + wgpu::WasmNotSend
+            + 'static,
+    ) {
+        let Readback {
+            buffer,
+            width,
+            height,
+            bytes_per_row,
+            bgra,
+        } = self;
+        let mapped = buffer.clone();
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let rgba = result.map(|()| {
+                    let rgba = {
+                        let data = mapped.slice(..).get_mapped_range();
+                        unpad_rows(&data, width, height, bytes_per_row, bgra)
+                    };
+                    mapped.unmap();
+                    rgba
+                });
+                done(rgba, width, height);
+            });
+    }
+}
+
+// Reads back the texture as tightly packed rgba rows, blocking until the copy
+// is done. Multisampled textures are resolved first.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_texture(gpu: &Gpu, texture: &wgpu::Texture) -> Vec<u8> {
+    use std::sync::{Arc, Mutex};
+
+    let result = Arc::new(Mutex::new(None));
+    let slot = result.clone();
+    Readback::start(gpu, texture).map(move |rgba, _, _| {
+        *slot.lock().unwrap() = Some(rgba);
+    });
+    let _ = gpu.device.poll(wgpu::PollType::Wait);
+    let rgba = result.lock().unwrap().take();
+    match rgba {
+        Some(Ok(rgba)) => rgba,
+        Some(Err(e)) => panic!("failed to read back texture: {e}"),
+        None => panic!("texture readback did not finish"),
+    }
+}
+
+// Reads back the texture and saves it as png, e.g. a frame rendered into an
+// offscreen target, as surface textures can't be copied from. On native the
+// file is written to `SCREENSHOT_PATH`, on wasm it is offered as a browser
+// download. The readback is asynchronous on wasm, so the file appears a few
+// frames later.
+pub fn capture(gpu: &Gpu, texture: &wgpu::Texture) {
+    Readback::start(gpu, texture).map(|rgba, width, height| {
+        let rgba = match rgba {
+            Ok(rgba) => rgba,
+            Err(e) => {
+                log::error!("Failed to read back screenshot: {e}");
+                return;
+            }
+        };
+        let png = match encode_png(width, height, &rgba) {
+            Ok(png) => png,
+            Err(e) => {
+                log::error!("Failed to encode screenshot: {e}");
+                return;
+            }
+        };
+        if let Err(e) = save(&png) {
+            log::error!("Failed to save screenshot: {e:?}");
+        }
+    });
+
+    // Blocks until the callback above ran on native, no-op on wasm.
+    let _ = gpu.device.poll(wgpu::PollType::Wait);
+}
+
+fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Result<Vec<u8>, png::EncodingError> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(rgba)?;
+    Ok(png)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save(png: &[u8]) -> std::io::Result<()> {
+    std::fs::write(SCREENSHOT_PATH, png)?;
+    log::info!("Saved screenshot to {SCREENSHOT_PATH}");
+    Ok(())
+}
+
+// Offers the png as a download through a temporary link. Fails without a
+// document.
+#[cfg(target_arch = "wasm32")]
+fn save(png: &[u8]) -> Result<(), wasm_bindgen::JsValue> {
+    use wasm_bindgen::JsCast;
+
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or("no document to download from")?;
+
+    let bytes = js_sys::Uint8Array::from(png);
+    let parts = js_sys::Array::of1(&bytes);
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type("image/png");
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+
+    let link = document
+        .create_element("a")?
+        .dyn_into::<web_sys::HtmlAnchorElement>()?;
+    link.set_href(&url);
+    link.set_download(SCREENSHOT_FILE_NAME);
+    link.click();
+
+    web_sys::Url::revoke_object_url(&url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Clears a single sampled and a 4x multisampled texture to a known color
+    // and reads both back, which has to resolve the multisampled one first.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn readback_resolves_multisampled_textures() {
+        const WIDTH: u32 = 70;
+        const HEIGHT: u32 = 30;
+        // Not sRGB, so the stored bytes are the clear color scaled to 255.
+        const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+        const CLEAR: wgpu::Color = wgpu::Color {
+            r: 0.25,
+            g: 0.5,
+            b: 1.0,
+            a: 1.0,
+        };
+        const EXPECTED: [u8; 4] = [64, 128, 255, 255];
+
+        let Some(gpu) = Gpu::headless(wgpu::Features::empty()) else {
+            eprintln!("No adapter available, skipping");
+            return;
+        };
+
+        let cleared_texture = |sample_count: u32| {
+            let texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Screenshot Test Texture"),
+                size: wgpu::Extent3d {
+                    width: WIDTH,
+                    height: HEIGHT,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format: FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            });
+            let view = texture.create_view(&Default::default());
+            let mut encoder = gpu.device.create_command_encoder(&Default::default());
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Screenshot Test Clear"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(CLEAR),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                ..Default::default()
+            });
+            gpu.queue.submit([encoder.finish()]);
+            texture
+        };
+
+        for sample_count in [1, 4] {
+            let rgba = read_texture(&gpu, &cleared_texture(sample_count));
+            assert_eq!(rgba.len(), (WIDTH * HEIGHT * 4) as usize, "{sample_count}x");
+            let mismatch = rgba
+                .chunks(4)
+                .position(|px| px.iter().zip(EXPECTED).any(|(&a, b)| a.abs_diff(b) > 1));
+            assert_eq!(mismatch, None, "{sample_count}x: expected {EXPECTED:?}");
+        }
+    }
+}
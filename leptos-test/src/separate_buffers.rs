@@ -0,0 +1,118 @@
+use crate::gpu::{shader, DrawCall, Gpu, Mesh, Program, Uniform, UNIFORM_VERT};
+use crate::msaa::{msaa_samples, MSAA_SAMPLES};
+use crate::primitives::cube;
+use crate::surface::{self, Frame};
+use crate::target::Target;
+use crate::vertex_layout::{validate_buffers, VertexLayout};
+use trivalibs::math::transform::Transform;
+use trivalibs::painter::app::Event;
+use trivalibs::painter::prelude::*;
+use trivalibs::prelude::*;
+use trivalibs::rendering::camera::{CamProps, PerspectiveCamera};
+
+const CUBE_SIZE: f32 = 2.0;
+
+// A cube colored by its corner positions, with positions and colors in two
+// vertex buffers, as a mesh loader returning one array per attribute would
+// give them. The mesh is drawn with `colored_vertex`, which reads location
+// 0 from the first buffer and location 1 from the second, without
+// interleaving them first.
+pub struct SeparateBuffersApp {
+    gpu: Gpu,
+    cam: PerspectiveCamera,
+    transform: Transform,
+    model_mat: Uniform<Mat4>,
+    vp_mat: Uniform<Mat4>,
+
+    canvas: Target,
+    draw: DrawCall,
+}
+
+impl CanvasApp<()> for SeparateBuffersApp {
+    fn init(p: &mut Painter) -> Self {
+        let gpu = Gpu::new(p);
+        let (positions, indices) = cube(CUBE_SIZE);
+        let colors: Vec<Vec3> = positions.iter().map(|&p| p / CUBE_SIZE + 0.5).collect();
+
+        let position_layout = VertexLayout::single(Float32x3, 0);
+        let color_layout = VertexLayout::single(Float32x3, 1);
+        validate_buffers(&[
+            (&position_layout, positions.len()),
+            (&color_layout, colors.len()),
+        ])
+        .expect("one color per position");
+
+        let program = Program::build(&gpu, shader!("colored_vertex"))
+            .with_fragment(shader!("colored_fragment"))
+            .with_vertex_layout(position_layout)
+            .with_vertex_layout(color_layout)
+            .with_bindings(&[UNIFORM_VERT, UNIFORM_VERT])
+            .create();
+
+        let mesh = Mesh::new(&gpu, &positions)
+            .with_vertex_buffer(&gpu, &colors)
+            .with_indices(&gpu, &indices);
+
+        let model_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let vp_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+
+        let canvas = Target::canvas(surface::view_format(p))
+            .with_samples(msaa_samples(p, MSAA_SAMPLES))
+            .with_depth()
+            .create(&gpu, 1, 1);
+        let bind_group = program.bind_group(&gpu, 0, &[vp_mat.binding(), model_mat.binding()]);
+        let draw = DrawCall::new(
+            &gpu,
+            &program,
+            &mesh,
+            vec![bind_group],
+            canvas.pipeline_state(),
+        );
+
+        Self {
+            gpu,
+            cam: PerspectiveCamera::create(CamProps {
+                fov: Some(0.6),
+                translation: Some(vec3(0.0, 0.0, 7.0)),
+                ..default()
+            }),
+            transform: Transform::from_translation(Vec3::ZERO),
+            model_mat,
+            vp_mat,
+
+            canvas,
+            draw,
+        }
+    }
+
+    fn resize(&mut self, _p: &mut Painter, width: u32, height: u32) {
+        self.cam.set_aspect_ratio(width as f32 / height as f32);
+        self.canvas.resize(&self.gpu, width, height);
+
+        self.vp_mat.update(&self.gpu, self.cam.view_proj_mat());
+    }
+
+    fn update(&mut self, p: &mut Painter, tpf: f32) {
+        self.transform.rotate_y(tpf * 0.5);
+        self.transform.rotate_x(tpf * 0.3);
+        self.model_mat
+            .update(&self.gpu, self.transform.model_mat());
+        p.request_next_frame();
+    }
+
+    fn render(&self, p: &mut Painter) -> Result<(), SurfaceError> {
+        let Some(frame) = Frame::acquire(p)? else {
+            return Ok(());
+        };
+        let mut encoder = self.gpu.encoder("Separate Buffers Encoder");
+        self.draw.draw(
+            &mut self.canvas.pass(&mut encoder, Some(&frame.view)),
+            self.canvas.size(),
+        );
+        self.gpu.queue.submit([encoder.finish()]);
+        frame.present();
+        Ok(())
+    }
+
+    fn event(&mut self, _e: Event<()>, _p: &mut Painter) {}
+}
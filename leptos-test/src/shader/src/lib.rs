@@ -1,23 +1,726 @@
 #![no_std]
 #![allow(unexpected_cfgs)]
 
-use spirv_std::glam::{Mat4, Vec3, Vec4};
-use spirv_std::spirv;
+use spirv_std::glam::{vec2, Mat3A, Mat4, UVec3, Vec2, Vec3, Vec4};
+#[cfg(target_arch = "spirv")]
+use spirv_std::num_traits::Float;
+use spirv_std::{spirv, Image, Sampler};
 
 #[spirv(vertex)]
 pub fn vertex(
     position: Vec3,
+    color: Vec3,
+    normal: Vec3,
     #[spirv(uniform, descriptor_set = 0, binding = 0)] vp_mat: &Mat4,
     #[spirv(uniform, descriptor_set = 0, binding = 1)] model_mat: &Mat4,
+    #[spirv(uniform, descriptor_set = 0, binding = 5)] normal_mat: &Mat3A,
+    #[spirv(position)] clip_pos: &mut Vec4,
+    out_color: &mut Vec3,
+    out_normal: &mut Vec3,
+    out_world_pos: &mut Vec3,
+) {
+    let world_pos = *model_mat * position.extend(1.0);
+    *clip_pos = *vp_mat * world_pos;
+    *out_color = color;
+    *out_normal = normal_mat.mul_vec3(normal);
+    *out_world_pos = world_pos.truncate();
+}
+
+// Like `vertex`, with the model matrix passed as push constant instead of a
+// uniform buffer, for backends with the `PUSH_CONSTANTS` feature. Binding 1
+// stays reserved, so the fragment bindings are the same for both.
+#[spirv(vertex)]
+pub fn vertex_push(
+    position: Vec3,
+    color: Vec3,
+    normal: Vec3,
+    #[spirv(uniform, descriptor_set = 0, binding = 0)] vp_mat: &Mat4,
+    #[spirv(push_constant)] model_mat: &Mat4,
+    #[spirv(uniform, descriptor_set = 0, binding = 5)] normal_mat: &Mat3A,
+    #[spirv(position)] clip_pos: &mut Vec4,
+    out_color: &mut Vec3,
+    out_normal: &mut Vec3,
+    out_world_pos: &mut Vec3,
+) {
+    let world_pos = *model_mat * position.extend(1.0);
+    *clip_pos = *vp_mat * world_pos;
+    *out_color = color;
+    *out_normal = normal_mat.mul_vec3(normal);
+    *out_world_pos = world_pos.truncate();
+}
+
+// Positions only, for the pick target, which needs no varyings.
+#[spirv(vertex)]
+pub fn pick_vertex(
+    position: Vec3,
+    #[spirv(uniform, descriptor_set = 0, binding = 0)] vp_mat: &Mat4,
+    #[spirv(uniform, descriptor_set = 0, binding = 1)] model_mat: &Mat4,
+    #[spirv(position)] clip_pos: &mut Vec4,
+) {
+    *clip_pos = *vp_mat * *model_mat * position.extend(1.0);
+}
+
+// `pick_vertex` with the model matrix as push constant, see `vertex_push`.
+#[spirv(vertex)]
+pub fn pick_vertex_push(
+    position: Vec3,
+    #[spirv(uniform, descriptor_set = 0, binding = 0)] vp_mat: &Mat4,
+    #[spirv(push_constant)] model_mat: &Mat4,
     #[spirv(position)] clip_pos: &mut Vec4,
 ) {
     *clip_pos = *vp_mat * *model_mat * position.extend(1.0);
 }
 
+// Writes the object's id into an `R32Uint` target, drawn with `pick_vertex`
+// or `pick_vertex_push` and the camera's view projection. 0 is left for the
+// background.
+#[spirv(fragment)]
+pub fn pick_fragment(
+    #[spirv(uniform, descriptor_set = 0, binding = 2)] id: &u32,
+    out_id: &mut u32,
+) {
+    *out_id = *id;
+}
+
+const AMBIENT: f32 = 0.2;
+const PULSE_AMOUNT: f32 = 0.15;
+
+// Point lights passed to `fragment`. Must match `PointLight` in the app's
+// `render.rs`. Two `Vec4` fields give an array stride of 32 bytes, a multiple
+// of 16 as std140 requires, so the `repr(C)` layout matches without padding.
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct PointLight {
+    // xyz: position in world space, w: range, beyond which it adds nothing.
+    pub position: Vec4,
+    // rgb: linear color, w: intensity.
+    pub color: Vec4,
+}
+
+pub const MAX_POINT_LIGHTS: usize = 4;
+
+// Blinn-Phong diffuse (x) and specular (y) factors for a normalized direction
+// towards the light. Faces turned away from the light get no highlight, even
+// if the half vector between light and view direction still points out of
+// them. `powf` is undefined for a zero exponent at 0, and below 1 the
+// highlight covers the whole lit side, so the exponent is at least 1.
+fn blinn_phong(normal: Vec3, view_dir: Vec3, light_dir: Vec3, shininess: f32) -> Vec2 {
+    let n_dot_l = normal.dot(light_dir);
+    if n_dot_l <= 0.0 {
+        return Vec2::ZERO;
+    }
+    let half = (light_dir + view_dir).normalize_or_zero();
+    vec2(n_dot_l, normal.dot(half).max(0.0).powf(shininess.max(1.0)))
+}
+
+// All fragment shaders write linear colors. The surface and targets use sRGB
+// formats, so the hardware encodes them on write.
 #[spirv(fragment)]
 pub fn fragment(
+    in_color: Vec3,
+    in_normal: Vec3,
+    in_world_pos: Vec3,
     #[spirv(uniform, descriptor_set = 0, binding = 2)] color: &Vec4,
+    // xyz: normalized direction from the surface towards the light, in world space.
+    // w: light intensity.
+    #[spirv(uniform, descriptor_set = 0, binding = 3)] light: &Vec4,
+    // Elapsed seconds, wrapped to [0, 2π).
+    #[spirv(uniform, descriptor_set = 0, binding = 4)] time: &f32,
+    // Camera position in world space, for view dependent shading.
+    #[spirv(uniform, descriptor_set = 0, binding = 6)] cam_pos: &Vec3,
+    // rgb: linear highlight color, added on top of the diffuse color.
+    #[spirv(uniform, descriptor_set = 0, binding = 7)] specular_color: &Vec4,
+    // Blinn-Phong exponent.
+    #[spirv(uniform, descriptor_set = 0, binding = 8)] shininess: &f32,
+    #[spirv(uniform, descriptor_set = 0, binding = 9)]
+    point_lights: &[PointLight; MAX_POINT_LIGHTS],
+    // Number of active point lights, the slots after them are ignored.
+    #[spirv(uniform, descriptor_set = 0, binding = 10)] point_light_count: &u32,
+    frag_color: &mut Vec4,
+) {
+    let normal = in_normal.normalize();
+    let view_dir = (*cam_pos - in_world_pos).normalize_or_zero();
+
+    let sun = blinn_phong(normal, view_dir, light.truncate(), *shininess) * light.w;
+    let mut diffuse = Vec3::splat(sun.x);
+    let mut specular = Vec3::splat(sun.y);
+
+    let count = (*point_light_count as usize).min(MAX_POINT_LIGHTS);
+    let mut i = 0;
+    while i < count {
+        let point = point_lights[i];
+        let to_light = point.position.truncate() - in_world_pos;
+        let distance = to_light.length();
+        // Smooth falloff reaching 0 at the range.
+        let falloff = (1.0 - distance / point.position.w.max(0.0001)).max(0.0);
+        let factors = blinn_phong(normal, view_dir, to_light.normalize_or_zero(), *shininess);
+        let light_color = point.color.truncate() * point.color.w * falloff * falloff;
+        diffuse += light_color * factors.x;
+        specular += light_color * factors.y;
+        i += 1;
+    }
+
+    let light = Vec3::splat(AMBIENT) + (1.0 - AMBIENT) * diffuse;
+    let pulse = 1.0 - PULSE_AMOUNT + PULSE_AMOUNT * time.sin();
+    *frag_color = (in_color * light * pulse).extend(1.0) * *color
+        + (specular_color.truncate() * specular).extend(0.0);
+}
+
+#[spirv(vertex)]
+pub fn instanced_vertex(
+    position: Vec3,
+    #[spirv(instance_index)] instance_index: u32,
+    #[spirv(uniform, descriptor_set = 0, binding = 0)] vp_mat: &Mat4,
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] model_mats: &[Mat4],
+    #[spirv(uniform, descriptor_set = 0, binding = 2)] instance_count: &u32,
+    #[spirv(position)] clip_pos: &mut Vec4,
+    out_color: &mut Vec3,
+) {
+    let model_mat = model_mats[instance_index as usize];
+    *clip_pos = *vp_mat * model_mat * position.extend(1.0);
+
+    let t = instance_index as f32 / *instance_count as f32;
+    *out_color = Vec3::new(t, 1.0 - t, 0.5);
+}
+
+#[spirv(fragment)]
+pub fn instanced_fragment(in_color: Vec3, frag_color: &mut Vec4) {
+    *frag_color = in_color.extend(1.0);
+}
+
+// Two triangles covering clip space, indexed by the vertex index, so the
+// quad needs no vertex buffer.
+const QUAD_CORNERS: [Vec2; 6] = [
+    vec2(-1.0, -1.0),
+    vec2(1.0, -1.0),
+    vec2(1.0, 1.0),
+    vec2(-1.0, -1.0),
+    vec2(1.0, 1.0),
+    vec2(-1.0, 1.0),
+];
+
+#[spirv(vertex)]
+pub fn gradient_vertex(
+    #[spirv(vertex_index)] vertex_index: i32,
+    #[spirv(position)] clip_pos: &mut Vec4,
+) {
+    *clip_pos = QUAD_CORNERS[vertex_index as usize].extend(0.0).extend(1.0);
+}
+
+// One triangle covering all of clip space, generated from the vertex index,
+// so it needs no vertex buffer. The uv is (0, 0) at the top left and (1, 1)
+// at the bottom right of the viewport.
+#[spirv(vertex)]
+pub fn fullscreen_vertex(
+    #[spirv(vertex_index)] vertex_index: i32,
+    #[spirv(position)] clip_pos: &mut Vec4,
+    out_uv: &mut Vec2,
+) {
+    let uv = vec2(((vertex_index << 1) & 2) as f32, (vertex_index & 2) as f32);
+    *clip_pos = Vec4::new(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    *out_uv = uv;
+}
+
+#[spirv(fragment)]
+pub fn gradient_fragment(
+    #[spirv(frag_coord)] frag_coord: Vec4,
+    // Viewport size in physical pixels.
+    #[spirv(uniform, descriptor_set = 0, binding = 0)] resolution: &Vec2,
+    // Mouse position normalized to [0, 1], origin top left like `frag_coord`.
+    #[spirv(uniform, descriptor_set = 0, binding = 1)] mouse: &Vec2,
+    frag_color: &mut Vec4,
+) {
+    let uv = vec2(frag_coord.x, frag_coord.y) / *resolution;
+    let gradient = Vec3::new(uv.x, uv.y, 1.0 - uv.x);
+    let tint = Vec3::new(mouse.x, mouse.y, 1.0 - mouse.y);
+    let aspect = vec2(resolution.x / resolution.y, 1.0);
+    let glow = (1.0 - ((uv - *mouse) * aspect).length() * 2.0).max(0.0);
+    *frag_color = (gradient * tint + glow * 0.5).extend(1.0);
+}
+
+const LUMA: Vec3 = Vec3::new(0.2126, 0.7152, 0.0722);
+
+// Draws the offscreen scene target onto the canvas, applying a post effect.
+// Textures are bound in descriptor set 1, value bindings like samplers in set 0.
+#[spirv(fragment)]
+pub fn composite_fragment(
+    in_uv: Vec2,
+    #[spirv(descriptor_set = 0, binding = 0)] sampler: &Sampler,
+    // 0: passthrough, 1: grayscale, 2: invert
+    #[spirv(uniform, descriptor_set = 0, binding = 1)] mode: &u32,
+    #[spirv(descriptor_set = 1, binding = 0)] scene: &Image!(2D, type=f32, sampled),
+    frag_color: &mut Vec4,
+) {
+    let color: Vec4 = scene.sample(*sampler, in_uv);
+    *frag_color = post_effect(color.truncate(), *mode).extend(color.w);
+}
+
+// 0: passthrough, 1: grayscale, 2: invert
+fn post_effect(rgb: Vec3, mode: u32) -> Vec3 {
+    match mode {
+        1 => Vec3::splat(rgb.dot(LUMA)),
+        2 => Vec3::ONE - rgb,
+        _ => rgb,
+    }
+}
+
+const FXAA_REDUCE_MIN: f32 = 1.0 / 128.0;
+const FXAA_REDUCE_MUL: f32 = 1.0 / 8.0;
+const FXAA_SPAN_MIN: f32 = 2.0;
+const FXAA_SPAN_MAX: f32 = 8.0;
+
+// Like `composite_fragment`, smoothing edges with FXAA first, as an
+// alternative to rendering the scene multisampled.
+//
+// The edge direction is estimated from the luma of the four diagonal
+// neighbors, and the scene is sampled along it, blurring across the edge.
+// If the wider blur lands outside the local luma range, it crossed another
+// edge and the narrower one is used. `quality` in [0, 1] scales the
+// farthest sampling distance along the edge, from 2 to 8 pixels: longer
+// spans smooth shallow edges better, but blur fine detail like text more.
+#[spirv(fragment)]
+pub fn composite_fxaa_fragment(
+    in_uv: Vec2,
+    #[spirv(descriptor_set = 0, binding = 0)] sampler: &Sampler,
+    #[spirv(uniform, descriptor_set = 0, binding = 1)] mode: &u32,
+    #[spirv(uniform, descriptor_set = 0, binding = 2)] quality: &f32,
+    // Size of the scene in pixels.
+    #[spirv(uniform, descriptor_set = 0, binding = 3)] viewport: &Vec2,
+    #[spirv(descriptor_set = 1, binding = 0)] scene: &Image!(2D, type=f32, sampled),
+    frag_color: &mut Vec4,
+) {
+    let texel = Vec2::ONE / *viewport;
+    let sample = |offset: Vec2| -> Vec4 { scene.sample(*sampler, in_uv + offset * texel) };
+    let luma = |c: Vec4| c.truncate().dot(LUMA);
+
+    let center = sample(Vec2::ZERO);
+    let nw = luma(sample(vec2(-1.0, -1.0)));
+    let ne = luma(sample(vec2(1.0, -1.0)));
+    let sw = luma(sample(vec2(-1.0, 1.0)));
+    let se = luma(sample(vec2(1.0, 1.0)));
+    let m = luma(center);
+    let luma_min = m.min(nw.min(ne).min(sw.min(se)));
+    let luma_max = m.max(nw.max(ne).max(sw.max(se)));
+
+    let dir = vec2(-((nw + ne) - (sw + se)), (nw + sw) - (ne + se));
+    let reduce = ((nw + ne + sw + se) * 0.25 * FXAA_REDUCE_MUL).max(FXAA_REDUCE_MIN);
+    let scale = 1.0 / (dir.x.abs().min(dir.y.abs()) + reduce);
+    let span = FXAA_SPAN_MIN + (FXAA_SPAN_MAX - FXAA_SPAN_MIN) * quality.clamp(0.0, 1.0);
+    let dir = (dir * scale).clamp(Vec2::splat(-span), Vec2::splat(span));
+
+    // All samples are taken before choosing, so they stay in uniform
+    // control flow.
+    let narrow = (sample(dir * (1.0 / 3.0 - 0.5)) + sample(dir * (2.0 / 3.0 - 0.5))) * 0.5;
+    let wide = narrow * 0.5 + (sample(dir * -0.5) + sample(dir * 0.5)) * 0.25;
+    let lw = luma(wide);
+    let color = if lw < luma_min || lw > luma_max {
+        narrow
+    } else {
+        wide
+    };
+    *frag_color = post_effect(color.truncate(), *mode).extend(center.w);
+}
+
+#[spirv(vertex)]
+pub fn textured_vertex(
+    position: Vec3,
+    uv: Vec2,
+    #[spirv(uniform, descriptor_set = 0, binding = 0)] vp_mat: &Mat4,
+    #[spirv(uniform, descriptor_set = 0, binding = 1)] model_mat: &Mat4,
+    #[spirv(position)] clip_pos: &mut Vec4,
+    out_uv: &mut Vec2,
+) {
+    *clip_pos = *vp_mat * *model_mat * position.extend(1.0);
+    *out_uv = uv;
+}
+
+#[spirv(fragment)]
+pub fn textured_fragment(
+    in_uv: Vec2,
+    #[spirv(descriptor_set = 0, binding = 2)] sampler: &Sampler,
+    #[spirv(descriptor_set = 0, binding = 3)] texture: &Image!(2D, type=f32, sampled),
+    frag_color: &mut Vec4,
+) {
+    *frag_color = texture.sample(*sampler, in_uv);
+}
+
+// Lit with a tangent-space normal map. The tangent is transformed by the model
+// matrix, as it lies in the surface like a position difference, the normal by
+// the normal matrix.
+#[spirv(vertex)]
+pub fn normal_map_vertex(
+    position: Vec3,
+    normal: Vec3,
+    uv: Vec2,
+    // xyz: direction of increasing u, w: handedness of the bitangent.
+    tangent: Vec4,
+    #[spirv(uniform, descriptor_set = 0, binding = 0)] vp_mat: &Mat4,
+    #[spirv(uniform, descriptor_set = 0, binding = 1)] model_mat: &Mat4,
+    #[spirv(uniform, descriptor_set = 0, binding = 2)] normal_mat: &Mat3A,
+    #[spirv(position)] clip_pos: &mut Vec4,
+    out_normal: &mut Vec3,
+    out_tangent: &mut Vec4,
+    out_uv: &mut Vec2,
+) {
+    *clip_pos = *vp_mat * *model_mat * position.extend(1.0);
+    *out_normal = normal_mat.mul_vec3(normal);
+    *out_tangent = (*model_mat * tangent.truncate().extend(0.0))
+        .truncate()
+        .extend(tangent.w);
+    *out_uv = uv;
+}
+
+const NORMAL_MAP_ALBEDO: Vec3 = Vec3::new(0.8, 0.75, 0.7);
+
+// The normal map stores tangent-space normals as `n * 0.5 + 0.5`, x along the
+// tangent, y along the bitangent and z along the surface normal. It has to
+// be a linear (not sRGB) texture.
+#[spirv(fragment)]
+pub fn normal_map_fragment(
+    in_normal: Vec3,
+    in_tangent: Vec4,
+    in_uv: Vec2,
+    #[spirv(descriptor_set = 0, binding = 3)] sampler: &Sampler,
+    #[spirv(descriptor_set = 0, binding = 4)] normal_map: &Image!(2D, type=f32, sampled),
+    // Same as in `fragment`.
+    #[spirv(uniform, descriptor_set = 0, binding = 5)] light: &Vec4,
+    // Repetitions of the normal map along u and v.
+    #[spirv(uniform, descriptor_set = 0, binding = 6)] tiling: &Vec2,
+    frag_color: &mut Vec4,
+) {
+    // Interpolation denormalizes the vectors and the tangent drifts off
+    // perpendicular, so the TBN basis is rebuilt per fragment.
+    let n = in_normal.normalize();
+    let t = in_tangent.truncate();
+    let t = (t - n * n.dot(t)).normalize();
+    let b = n.cross(t) * in_tangent.w;
+
+    let mapped = normal_map.sample(*sampler, in_uv * *tiling).truncate() * 2.0 - Vec3::ONE;
+    let normal = (t * mapped.x + b * mapped.y + n * mapped.z).normalize();
+
+    let diffuse = normal.dot(light.truncate()).max(0.0) * light.w;
+    let light = AMBIENT + (1.0 - AMBIENT) * diffuse;
+    *frag_color = (NORMAL_MAP_ALBEDO * light).extend(1.0);
+}
+
+#[spirv(vertex)]
+pub fn reflect_vertex(
+    position: Vec3,
+    normal: Vec3,
+    #[spirv(uniform, descriptor_set = 0, binding = 0)] vp_mat: &Mat4,
+    #[spirv(uniform, descriptor_set = 0, binding = 1)] model_mat: &Mat4,
+    #[spirv(uniform, descriptor_set = 0, binding = 2)] normal_mat: &Mat3A,
+    #[spirv(position)] clip_pos: &mut Vec4,
+    out_world_pos: &mut Vec3,
+    out_normal: &mut Vec3,
+) {
+    let world_pos = *model_mat * position.extend(1.0);
+    *clip_pos = *vp_mat * world_pos;
+    *out_world_pos = world_pos.truncate();
+    *out_normal = normal_mat.mul_vec3(normal);
+}
+
+// Slightly darkens reflections, like a metal that doesn't reflect all light.
+const REFLECT_TINT: Vec3 = Vec3::new(0.9, 0.9, 0.95);
+
+// Mirror reflection of the environment cube map, looked up with the view
+// direction reflected at the surface normal, both in world space.
+#[spirv(fragment)]
+pub fn reflect_fragment(
+    in_world_pos: Vec3,
+    in_normal: Vec3,
+    #[spirv(descriptor_set = 0, binding = 3)] sampler: &Sampler,
+    #[spirv(descriptor_set = 0, binding = 4)] env: &Image!(cube, type=f32, sampled),
+    // xyz: camera position in world space.
+    #[spirv(uniform, descriptor_set = 0, binding = 5)] cam_pos: &Vec4,
+    frag_color: &mut Vec4,
+) {
+    let n = in_normal.normalize();
+    let incident = (in_world_pos - cam_pos.truncate()).normalize();
+    let reflected = incident - 2.0 * n.dot(incident) * n;
+    let color = env.sample(*sampler, reflected).truncate();
+    *frag_color = (color * REFLECT_TINT).extend(1.0);
+}
+
+// Environment cube map as background, drawn with `fullscreen_vertex`. The
+// view direction of each fragment is unprojected from its uv.
+#[spirv(fragment)]
+pub fn skybox_fragment(
+    in_uv: Vec2,
+    // Inverse of the view projection matrix, without the camera translation.
+    #[spirv(uniform, descriptor_set = 0, binding = 0)] inv_vp_mat: &Mat4,
+    #[spirv(descriptor_set = 0, binding = 1)] sampler: &Sampler,
+    #[spirv(descriptor_set = 0, binding = 2)] env: &Image!(cube, type=f32, sampled),
+    frag_color: &mut Vec4,
+) {
+    let ndc = vec2(in_uv.x * 2.0 - 1.0, 1.0 - in_uv.y * 2.0);
+    let dir = *inv_vp_mat * ndc.extend(1.0).extend(1.0);
+    *frag_color = env.sample(*sampler, dir.truncate() / dir.w);
+}
+
+// Bitmap font glyphs, white on transparent, tinted with the text color.
+#[spirv(fragment)]
+pub fn text_fragment(
+    in_uv: Vec2,
+    #[spirv(descriptor_set = 0, binding = 2)] sampler: &Sampler,
+    #[spirv(descriptor_set = 0, binding = 3)] texture: &Image!(2D, type=f32, sampled),
+    #[spirv(uniform, descriptor_set = 0, binding = 4)] color: &Vec4,
+    frag_color: &mut Vec4,
+) {
+    *frag_color = texture.sample(*sampler, in_uv) * *color;
+}
+
+// Fullscreen fill with a uniform color, for fading a target that keeps its
+// contents between frames.
+#[spirv(fragment)]
+pub fn fade_fragment(
+    #[spirv(uniform, descriptor_set = 0, binding = 0)] color: &Vec4,
+    frag_color: &mut Vec4,
+) {
+    *frag_color = *color;
+}
+
+// Unlit shading with per-vertex colors.
+#[spirv(vertex)]
+pub fn colored_vertex(
+    position: Vec3,
+    color: Vec3,
+    #[spirv(uniform, descriptor_set = 0, binding = 0)] vp_mat: &Mat4,
+    #[spirv(uniform, descriptor_set = 0, binding = 1)] model_mat: &Mat4,
+    #[spirv(position)] clip_pos: &mut Vec4,
+    out_color: &mut Vec3,
+) {
+    *clip_pos = *vp_mat * *model_mat * position.extend(1.0);
+    *out_color = color;
+}
+
+#[spirv(fragment)]
+pub fn colored_fragment(in_color: Vec3, frag_color: &mut Vec4) {
+    *frag_color = in_color.extend(1.0);
+}
+
+// Unlit shading with a single color, for debug and helper geometry.
+#[spirv(vertex)]
+pub fn flat_vertex(
+    position: Vec3,
+    #[spirv(uniform, descriptor_set = 0, binding = 0)] vp_mat: &Mat4,
+    #[spirv(uniform, descriptor_set = 0, binding = 1)] model_mat: &Mat4,
+    #[spirv(position)] clip_pos: &mut Vec4,
+) {
+    *clip_pos = *vp_mat * *model_mat * position.extend(1.0);
+}
+
+#[spirv(fragment)]
+pub fn flat_fragment(
+    #[spirv(uniform, descriptor_set = 0, binding = 2)] color: &Vec4,
+    frag_color: &mut Vec4,
+) {
+    *frag_color = *color;
+}
+
+// Uniforms of `flat_struct_vertex`, packed in one buffer. Must match
+// `ShapeUniforms` in the app's `two_shapes.rs`. Only 16 byte aligned fields,
+// so the std140 layout equals the `repr(C)` layout without padding.
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct ShapeUniforms {
+    pub model_mat: Mat4,
+    pub vp_mat: Mat4,
+    pub color: Vec4,
+}
+
+// Like `flat_vertex`/`flat_fragment`, with all uniforms read from a single
+// struct. The color is passed on to the fragment stage, so the buffer is
+// only bound to the vertex stage.
+#[spirv(vertex)]
+pub fn flat_struct_vertex(
+    position: Vec3,
+    #[spirv(uniform, descriptor_set = 0, binding = 0)] uniforms: &ShapeUniforms,
+    #[spirv(position)] clip_pos: &mut Vec4,
+    #[spirv(flat)] out_color: &mut Vec4,
+) {
+    *clip_pos = uniforms.vp_mat * uniforms.model_mat * position.extend(1.0);
+    *out_color = uniforms.color;
+}
+
+#[spirv(fragment)]
+pub fn flat_struct_fragment(#[spirv(flat)] in_color: Vec4, frag_color: &mut Vec4) {
+    *frag_color = in_color;
+}
+
+// Grid lines drawn as quads (see `line_quads`), `line_width` pixels wide plus
+// one pixel on each side for antialiasing. Lines are expanded in screen space,
+// so segments crossing the camera plane are not supported.
+#[spirv(vertex)]
+pub fn grid_vertex(
+    start: Vec3,
+    end: Vec3,
+    corner: Vec2,
+    #[spirv(uniform, descriptor_set = 0, binding = 0)] vp_mat: &Mat4,
+    #[spirv(uniform, descriptor_set = 0, binding = 1)] model_mat: &Mat4,
+    // Render target size in pixels.
+    #[spirv(uniform, descriptor_set = 0, binding = 2)] viewport: &Vec2,
+    #[spirv(uniform, descriptor_set = 0, binding = 3)] line_width: &f32,
+    #[spirv(position)] clip_pos: &mut Vec4,
+    out_local: &mut Vec2,
+    out_edge: &mut f32,
+    out_half_width: &mut f32,
+) {
+    let mvp = *vp_mat * *model_mat;
+    let clip_start = mvp * start.extend(1.0);
+    let clip_end = mvp * end.extend(1.0);
+
+    let screen = |clip: Vec4| vec2(clip.x, clip.y) / clip.w * *viewport;
+    let dir = (screen(clip_end) - screen(clip_start)).normalize_or_zero();
+    let normal = vec2(-dir.y, dir.x);
+
+    let half_width = *line_width * 0.5;
+    let edge = corner.y * (half_width + 1.0);
+    let clip = if corner.x < 0.5 { clip_start } else { clip_end };
+    let offset = normal * edge * 2.0 / *viewport * clip.w;
+    *clip_pos = clip + Vec4::new(offset.x, offset.y, 0.0, 0.0);
+
+    let local = start.lerp(end, corner.x);
+    *out_local = vec2(local.x, local.z);
+    *out_edge = edge;
+    *out_half_width = half_width;
+}
+
+const GRID_COLOR: Vec3 = Vec3::new(0.5, 0.5, 0.5);
+// Lines start fading at this fraction of the extent.
+const GRID_FADE_START: f32 = 0.3;
+
+// Unlit grid lines, with smooth edges and fading out towards the edge of the
+// grid.
+#[spirv(fragment)]
+pub fn grid_fragment(
+    in_local: Vec2,
+    // Distance from the line center in pixels.
+    in_edge: f32,
+    in_half_width: f32,
+    // Distance from the grid center at which lines are fully transparent.
+    #[spirv(uniform, descriptor_set = 0, binding = 4)] extent: &f32,
+    frag_color: &mut Vec4,
+) {
+    let coverage = (in_half_width + 0.5 - in_edge.abs()).clamp(0.0, 1.0);
+    let t = (in_local.length() / *extent - GRID_FADE_START) / (1.0 - GRID_FADE_START);
+    let fade = 1.0 - t.clamp(0.0, 1.0);
+    *frag_color = GRID_COLOR.extend(fade * fade * coverage);
+}
+
+// Must match `Particle` in the app's `particles.rs`.
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct Particle {
+    pub position: Vec4,
+    pub velocity: Vec4,
+}
+
+const ATTRACTION: f32 = 40.0;
+// Keeps the pull finite for particles passing close to the center.
+const ATTRACTION_SOFTENING: f32 = 25.0;
+// Pulls every particle towards the origin and moves it by its velocity, with
+// semi-implicit Euler integration. `tpf` is clamped, so a long frame, e.g.
+// after the tab was hidden, doesn't fling particles away. The thread count
+// must match `WORKGROUP_SIZE` in the app's `particles.rs`.
+#[spirv(compute(threads(64)))]
+pub fn particles_compute(
+    #[spirv(global_invocation_id)] id: UVec3,
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] particles: &mut [Particle],
+    #[spirv(uniform, descriptor_set = 0, binding = 1)] tpf: &f32,
+) {
+    let i = id.x as usize;
+    if i >= particles.len() {
+        return;
+    }
+    let dt = tpf.min(0.1);
+    let p = &mut particles[i];
+    let pos = p.position.truncate();
+    let pull = -pos * ATTRACTION / (pos.length_squared() + ATTRACTION_SOFTENING);
+    let velocity = p.velocity.truncate() + pull * dt;
+    p.velocity = velocity.extend(0.0);
+    p.position = (pos + velocity * dt).extend(1.0);
+}
+
+// Corners of the two triangles of a particle sprite.
+const SPRITE_CORNERS: [Vec2; 6] = [
+    vec2(-1.0, -1.0),
+    vec2(1.0, -1.0),
+    vec2(1.0, 1.0),
+    vec2(-1.0, -1.0),
+    vec2(1.0, 1.0),
+    vec2(-1.0, 1.0),
+];
+
+// One quad per instance, centered on the particle. The corner offset is
+// added in clip space and scaled by w, so after the perspective divide the
+// sprite has the same size in normalized device coordinates at any distance
+// and resolution.
+#[spirv(vertex)]
+pub fn particles_vertex(
+    #[spirv(vertex_index)] vertex_index: u32,
+    #[spirv(instance_index)] instance_index: u32,
+    #[spirv(uniform, descriptor_set = 0, binding = 0)] vp_mat: &Mat4,
+    // Half size in normalized device coordinates, x corrected for the aspect ratio.
+    #[spirv(uniform, descriptor_set = 0, binding = 1)] sprite_size: &Vec2,
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 2)] particles: &[Particle],
+    #[spirv(position)] clip_pos: &mut Vec4,
+    out_corner: &mut Vec2,
+    out_color: &mut Vec3,
+) {
+    let particle = particles[instance_index as usize];
+    let corner = SPRITE_CORNERS[vertex_index as usize];
+    let center = *vp_mat * particle.position;
+    let offset = corner * *sprite_size * center.w;
+    *clip_pos = center + offset.extend(0.0).extend(0.0);
+    *out_corner = corner;
+    // Slow particles are blue, fast ones orange.
+    let t = (particle.velocity.truncate().length() / 15.0).min(1.0);
+    *out_color = Vec3::new(0.2, 0.4, 1.0).lerp(Vec3::new(1.0, 0.5, 0.1), t) * 0.6;
+}
+
+// Round sprites fading towards their edge.
+#[spirv(fragment)]
+pub fn particles_fragment(in_corner: Vec2, in_color: Vec3, frag_color: &mut Vec4) {
+    let falloff = (1.0 - in_corner.length()).max(0.0);
+    *frag_color = (in_color * falloff).extend(1.0);
+}
+
+// Lit and textured, tinted by a per-vertex color.
+#[spirv(vertex)]
+pub fn lit_textured_vertex(
+    position: Vec3,
+    normal: Vec3,
+    uv: Vec2,
+    // From `Unorm8x4`, in [0, 1].
+    color: Vec4,
+    #[spirv(uniform, descriptor_set = 0, binding = 0)] vp_mat: &Mat4,
+    #[spirv(uniform, descriptor_set = 0, binding = 1)] model_mat: &Mat4,
+    #[spirv(uniform, descriptor_set = 0, binding = 2)] normal_mat: &Mat3A,
+    #[spirv(position)] clip_pos: &mut Vec4,
+    out_normal: &mut Vec3,
+    out_uv: &mut Vec2,
+    out_color: &mut Vec4,
+) {
+    *clip_pos = *vp_mat * *model_mat * position.extend(1.0);
+    *out_normal = normal_mat.mul_vec3(normal);
+    *out_uv = uv;
+    *out_color = color;
+}
+
+#[spirv(fragment)]
+pub fn lit_textured_fragment(
+    in_normal: Vec3,
+    in_uv: Vec2,
+    in_color: Vec4,
+    #[spirv(descriptor_set = 0, binding = 3)] sampler: &Sampler,
+    #[spirv(descriptor_set = 0, binding = 4)] texture: &Image!(2D, type=f32, sampled),
+    // Same as in `fragment`.
+    #[spirv(uniform, descriptor_set = 0, binding = 5)] light: &Vec4,
     frag_color: &mut Vec4,
 ) {
-    *frag_color = *color * 1.0;
+    let diffuse = in_normal.normalize().dot(light.truncate()).max(0.0) * light.w;
+    let light = AMBIENT + (1.0 - AMBIENT) * diffuse;
+    let albedo = texture.sample(*sampler, in_uv) * in_color;
+    *frag_color = (albedo.truncate() * light).extend(albedo.w);
 }
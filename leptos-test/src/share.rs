@@ -0,0 +1,88 @@
+use crate::render::{CameraEvent, CAM_FOV_MAX_DEGREES, CAM_FOV_MIN_DEGREES};
+use web_sys::{Url, UrlSearchParams};
+
+// Scene parameters encoded in the page's query string, e.g.
+// `?r=1&g=0.5&b=0&a=0.8&fov=40&yaw=0.3&pitch=0.2&dist=80`. Each group is only set
+// if all of its params are present and valid.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SharedParams {
+    pub color: Option<(f32, f32, f32)>,
+    pub alpha: Option<f32>,
+    // Degrees.
+    pub fov: Option<f32>,
+    pub camera: Option<CameraEvent>,
+}
+
+// Parses a param as a finite number. Anything else, like `nan`, `inf` or
+// text, counts as missing.
+fn number(params: &UrlSearchParams, name: &str) -> Option<f32> {
+    params
+        .get(name)?
+        .parse::<f32>()
+        .ok()
+        .filter(|v| v.is_finite())
+}
+
+impl SharedParams {
+    // Reads the params of the current page. Out of range values are clamped,
+    // malformed ones ignored.
+    pub fn from_location() -> Self {
+        let Some(search) = web_sys::window().and_then(|w| w.location().search().ok()) else {
+            return Self::default();
+        };
+        let Ok(params) = UrlSearchParams::new_with_str(&search) else {
+            return Self::default();
+        };
+
+        let channel = |name| number(&params, name).map(|c| c.clamp(0.0, 1.0));
+        let color = (|| Some((channel("r")?, channel("g")?, channel("b")?)))();
+        let alpha = channel("a");
+
+        let fov = number(&params, "fov").map(|f| f.clamp(CAM_FOV_MIN_DEGREES, CAM_FOV_MAX_DEGREES));
+
+        // Pitch and distance are clamped by the orbit itself.
+        let camera = (|| {
+            Some(CameraEvent {
+                yaw: number(&params, "yaw")?,
+                pitch: number(&params, "pitch")?,
+                distance: number(&params, "dist")?,
+            })
+        })();
+
+        Self {
+            color,
+            alpha,
+            fov,
+            camera,
+        }
+    }
+
+    // The current page URL with its query string replaced by these params.
+    pub fn to_url(&self) -> Option<String> {
+        let href = web_sys::window()?.location().href().ok()?;
+        let url = Url::new(&href).ok()?;
+
+        let params = UrlSearchParams::new().ok()?;
+        let set = |name: &str, value: f32| params.set(name, &format!("{value:.3}"));
+        if let Some((r, g, b)) = self.color {
+            set("r", r);
+            set("g", g);
+            set("b", b);
+        }
+        if let Some(a) = self.alpha {
+            set("a", a);
+        }
+        if let Some(fov) = self.fov {
+            set("fov", fov);
+        }
+        if let Some(camera) = self.camera {
+            set("yaw", camera.yaw);
+            set("pitch", camera.pitch);
+            set("dist", camera.distance);
+        }
+
+        url.set_search(&String::from(params.to_string()));
+        url.set_hash("");
+        Some(url.href())
+    }
+}
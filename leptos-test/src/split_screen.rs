@@ -0,0 +1,177 @@
+use crate::gpu::{shader, DrawCall, Gpu, Mesh, Program, Uniform, Viewport, UNIFORM_VERT};
+use crate::msaa::{msaa_samples, MSAA_SAMPLES};
+use crate::primitives::cube;
+use crate::surface::{self, Frame};
+use crate::target::Target;
+use trivalibs::painter::app::Event;
+use trivalibs::painter::prelude::*;
+use trivalibs::prelude::*;
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: Vec3,
+    color: Vec3,
+}
+
+const CUBE_SIZE: f32 = 2.0;
+// Cubes along a diagonal, at increasing distance from the camera's start
+// position, so the perspective view shrinks the far ones and the
+// orthographic one doesn't.
+const CUBE_COUNT: usize = 5;
+const CUBE_STEP: Vec3 = vec3(3.0, 0.0, -4.0);
+
+const CAM_FOV: f32 = 0.8;
+const CAM_DISTANCE: f32 = 18.0;
+const CAM_HEIGHT: f32 = 6.0;
+// Radians per second.
+const CAM_SPEED: f32 = 0.15;
+
+// The same cubes seen by two cameras side by side: perspective on the left,
+// orthographic on the right, from the same position. Both halves are drawn in
+// one pass of the canvas, by two draws sharing mesh and program, each with its
+// own view projection and viewport.
+pub struct SplitScreenApp {
+    gpu: Gpu,
+    left_vp_mat: Uniform<Mat4>,
+    right_vp_mat: Uniform<Mat4>,
+    left: DrawCall,
+    right: DrawCall,
+    // Of each half, not of the whole canvas.
+    aspect_ratio: f32,
+    yaw: f32,
+
+    canvas: Target,
+}
+
+// All cubes in one mesh, each corner colored by its position in the cube.
+fn cubes() -> (Vec<Vertex>, Vec<u32>) {
+    let (positions, cube_indices) = cube(CUBE_SIZE);
+    let first = -CUBE_STEP * (CUBE_COUNT - 1) as f32 * 0.5;
+    let mut vertices = Vec::with_capacity(positions.len() * CUBE_COUNT);
+    let mut indices = Vec::with_capacity(cube_indices.len() * CUBE_COUNT);
+    for i in 0..CUBE_COUNT {
+        let offset = first + CUBE_STEP * i as f32;
+        let base = vertices.len() as u32;
+        vertices.extend(positions.iter().map(|&position| Vertex {
+            position: position + offset,
+            color: position / CUBE_SIZE + 0.5,
+        }));
+        indices.extend(cube_indices.iter().map(|&index| base + index));
+    }
+    (vertices, indices)
+}
+
+impl CanvasApp<()> for SplitScreenApp {
+    fn init(p: &mut Painter) -> Self {
+        let gpu = Gpu::new(p);
+        let program = Program::build(&gpu, shader!("colored_vertex"))
+            .with_fragment(shader!("colored_fragment"))
+            .with_vertex(&[Float32x3, Float32x3])
+            .with_bindings(&[UNIFORM_VERT, UNIFORM_VERT])
+            .create();
+
+        let (vertices, indices) = cubes();
+        let mesh = Mesh::new(&gpu, &vertices).with_indices(&gpu, &indices);
+
+        let model_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let left_vp_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let right_vp_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+
+        let canvas = Target::canvas(surface::view_format(p))
+            .with_samples(msaa_samples(p, MSAA_SAMPLES))
+            .with_depth()
+            .create(&gpu, 1, 1);
+
+        // Viewports are set in `resize`, once the canvas size is known.
+        let half = |vp_mat: &Uniform<Mat4>| {
+            let bind_group =
+                program.bind_group(&gpu, 0, &[vp_mat.binding(), model_mat.binding()]);
+            DrawCall::new(&gpu, &program, &mesh, vec![bind_group], canvas.pipeline_state())
+        };
+        let left = half(&left_vp_mat);
+        let right = half(&right_vp_mat);
+
+        Self {
+            gpu,
+            left_vp_mat,
+            right_vp_mat,
+            left,
+            right,
+            aspect_ratio: 1.0,
+            yaw: 0.0,
+
+            canvas,
+        }
+    }
+
+    fn resize(&mut self, _p: &mut Painter, width: u32, height: u32) {
+        self.canvas.resize(&self.gpu, width, height);
+        // An odd pixel goes to the right half.
+        let left_width = width / 2;
+        self.left.set_viewport(Some(Viewport {
+            x: 0.0,
+            y: 0.0,
+            width: left_width as f32,
+            height: height as f32,
+        }));
+        self.right.set_viewport(Some(Viewport {
+            x: left_width as f32,
+            y: 0.0,
+            width: (width - left_width) as f32,
+            height: height as f32,
+        }));
+        self.aspect_ratio = left_width.max(1) as f32 / height.max(1) as f32;
+        self.update_cam();
+    }
+
+    fn update(&mut self, p: &mut Painter, tpf: f32) {
+        self.yaw += tpf * CAM_SPEED;
+        self.update_cam();
+        p.request_next_frame();
+    }
+
+    fn render(&self, p: &mut Painter) -> Result<(), SurfaceError> {
+        let Some(frame) = Frame::acquire(p)? else {
+            return Ok(());
+        };
+        let mut encoder = self.gpu.encoder("Split Screen Encoder");
+        {
+            let mut pass = self.canvas.pass(&mut encoder, Some(&frame.view));
+            self.left.draw(&mut pass, self.canvas.size());
+            self.right.draw(&mut pass, self.canvas.size());
+        }
+        self.gpu.queue.submit([encoder.finish()]);
+        frame.present();
+        Ok(())
+    }
+
+    fn event(&mut self, _e: Event<()>, _p: &mut Painter) {}
+}
+
+impl SplitScreenApp {
+    fn update_cam(&self) {
+        let eye = vec3(
+            self.yaw.sin() * CAM_DISTANCE,
+            CAM_HEIGHT,
+            self.yaw.cos() * CAM_DISTANCE,
+        );
+        let view = Mat4::look_at_rh(eye, Vec3::ZERO, Vec3::Y);
+        let perspective = Mat4::perspective_rh(CAM_FOV, self.aspect_ratio, 0.1, 100.0);
+        // Shows the same area at the origin as the perspective camera.
+        let half_height = eye.length() * (CAM_FOV * 0.5).tan();
+        let half_width = half_height * self.aspect_ratio;
+        let orthographic = Mat4::orthographic_rh(
+            -half_width,
+            half_width,
+            -half_height,
+            half_height,
+            0.1,
+            100.0,
+        );
+
+        self.left_vp_mat.update(&self.gpu, perspective * view);
+        self.right_vp_mat
+            .update(&self.gpu, orthographic * view);
+    }
+}
@@ -0,0 +1,278 @@
+use trivalibs::painter::prelude::*;
+
+// What to do after a frame failed with a surface error.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Recovery {
+    // The surface no longer matches the window, e.g. after a tab resize, or
+    // was lost with the GPU. Reconfigure it and render the frame again.
+    Reconfigure,
+    // Transient, e.g. a timeout acquiring the texture. Try the next frame.
+    Skip,
+    // Nothing left to render with.
+    Stop,
+}
+
+pub fn recovery(e: &SurfaceError) -> Recovery {
+    match e {
+        SurfaceError::Lost | SurfaceError::Outdated => Recovery::Reconfigure,
+        SurfaceError::OutOfMemory => Recovery::Stop,
+        _ => Recovery::Skip,
+    }
+}
+
+pub trait RenderTarget {
+    // Configures the surface again with the last known size.
+    fn reconfigure(&mut self);
+    fn request_next_frame(&mut self);
+}
+
+// The painter has no surface and configuration without a window, e.g. before
+// the canvas is attached. Everything here is a no-op then.
+impl RenderTarget for Painter {
+    fn reconfigure(&mut self) {
+        if let (Some(surface), Some(config)) = (&self.surface, &self.config) {
+            surface.configure(&self.device, config);
+        }
+    }
+
+    fn request_next_frame(&mut self) {
+        Painter::request_next_frame(self);
+    }
+}
+
+// `requested` if the surface supports it, otherwise `Fifo`, which every
+// surface supports.
+pub fn choose_present_mode(
+    requested: wgpu::PresentMode,
+    supported: &[wgpu::PresentMode],
+) -> wgpu::PresentMode {
+    if supported.contains(&requested) {
+        requested
+    } else {
+        wgpu::PresentMode::Fifo
+    }
+}
+
+// Reconfigures the surface with `requested`, or `Fifo` if it isn't supported.
+// Mailbox and immediate don't wait for vsync, e.g. to measure the uncapped
+// frame rate.
+pub fn set_present_mode(p: &mut Painter, requested: wgpu::PresentMode) {
+    let (Some(surface), Some(config)) = (&p.surface, &mut p.config) else {
+        return;
+    };
+    let supported = surface.get_capabilities(&p.adapter).present_modes;
+    let mode = choose_present_mode(requested, &supported);
+    if mode != requested {
+        log::warn!("Present mode {requested:?} not supported, supported: {supported:?}");
+    }
+    log::info!("Present mode: {mode:?}");
+    config.present_mode = mode;
+    p.reconfigure();
+}
+
+// `requested` if the surface supports it, otherwise `Opaque`, or the first
+// supported mode if even that is missing.
+pub fn choose_alpha_mode(
+    requested: wgpu::CompositeAlphaMode,
+    supported: &[wgpu::CompositeAlphaMode],
+) -> wgpu::CompositeAlphaMode {
+    [requested, wgpu::CompositeAlphaMode::Opaque]
+        .into_iter()
+        .find(|mode| supported.contains(mode))
+        .or(supported.first().copied())
+        .unwrap_or(wgpu::CompositeAlphaMode::Opaque)
+}
+
+// Reconfigures how the surface is composited with the page. On WebGPU,
+// `PreMultiplied` configures the canvas context with
+// `alphaMode: "premultiplied"`, so where the app writes alpha below 1 the
+// page behind the canvas shows through, and colors have to be premultiplied
+// by alpha. See `is_premultiplied` for the mode in use.
+pub fn set_alpha_mode(p: &mut Painter, requested: wgpu::CompositeAlphaMode) {
+    let (Some(surface), Some(config)) = (&p.surface, &mut p.config) else {
+        return;
+    };
+    let supported = surface.get_capabilities(&p.adapter).alpha_modes;
+    let mode = choose_alpha_mode(requested, &supported);
+    if mode != requested {
+        log::warn!("Alpha mode {requested:?} not supported, supported: {supported:?}");
+    }
+    log::info!("Alpha mode: {mode:?}");
+    config.alpha_mode = mode;
+    p.reconfigure();
+}
+
+// Whether the surface is composited with premultiplied alpha, see
+// `set_alpha_mode`.
+pub fn is_premultiplied(p: &Painter) -> bool {
+    p.config
+        .as_ref()
+        .is_some_and(|config| config.alpha_mode == wgpu::CompositeAlphaMode::PreMultiplied)
+}
+
+// The format demos rendering with wgpu directly view the surface texture in:
+// the sRGB variant of the surface format, which the painter renders in too.
+// Without surface nothing is presented, and targets get `Rgba8UnormSrgb`.
+pub fn view_format(p: &Painter) -> wgpu::TextureFormat {
+    p.config.as_ref().map_or(wgpu::TextureFormat::Rgba8UnormSrgb, |config| {
+        config.format.add_srgb_suffix()
+    })
+}
+
+// The surface texture of a frame rendered with wgpu directly, instead of
+// `paint_and_show`.
+pub struct Frame {
+    texture: wgpu::SurfaceTexture,
+    pub view: wgpu::TextureView,
+}
+
+impl Frame {
+    // Adds the view format to the surface configuration if it's missing, so
+    // the texture can be viewed in it. `None` without surface, there is
+    // nothing to render into then.
+    pub fn acquire(p: &mut Painter) -> Result<Option<Self>, SurfaceError> {
+        let format = view_format(p);
+        let Some(config) = &mut p.config else {
+            return Ok(None);
+        };
+        if format != config.format && !config.view_formats.contains(&format) {
+            config.view_formats.push(format);
+            p.reconfigure();
+        }
+        let Some(surface) = &p.surface else {
+            return Ok(None);
+        };
+        let texture = surface.get_current_texture()?;
+        let view = texture.texture.create_view(&wgpu::TextureViewDescriptor {
+            format: Some(format),
+            ..Default::default()
+        });
+        Ok(Some(Self { texture, view }))
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.texture.texture.width(), self.texture.texture.height())
+    }
+
+    // After the commands rendering into the view are submitted.
+    pub fn present(self) {
+        self.texture.present();
+    }
+}
+
+// Runs `render`, recovering from surface errors: a lost or outdated surface
+// is reconfigured and the frame rendered once more, a frame failing again is
+// skipped like one that timed out, with the next frame requested. Only an
+// error that can't be recovered from is returned.
+pub fn render_recovering<T: RenderTarget>(
+    target: &mut T,
+    mut render: impl FnMut(&mut T) -> Result<(), SurfaceError>,
+) -> Result<(), SurfaceError> {
+    let e = match render(target) {
+        Ok(()) => return Ok(()),
+        Err(e) => e,
+    };
+    let e = match recovery(&e) {
+        Recovery::Stop => return Err(e),
+        Recovery::Skip => e,
+        Recovery::Reconfigure => {
+            log::warn!("Surface {e}, reconfiguring");
+            target.reconfigure();
+            match render(target) {
+                Ok(()) => return Ok(()),
+                Err(e) if recovery(&e) == Recovery::Stop => return Err(e),
+                Err(e) => e,
+            }
+        }
+    };
+    log::warn!("Skipping frame: {e}");
+    target.request_next_frame();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Fails the first frames with the given errors, then presents.
+    #[derive(Default)]
+    struct FakeSurface {
+        errors: Vec<SurfaceError>,
+        reconfigured: u32,
+        frames_requested: u32,
+        presented: u32,
+    }
+
+    impl RenderTarget for FakeSurface {
+        fn reconfigure(&mut self) {
+            self.reconfigured += 1;
+        }
+
+        fn request_next_frame(&mut self) {
+            self.frames_requested += 1;
+        }
+    }
+
+    fn render(surface: &mut FakeSurface) -> Result<(), SurfaceError> {
+        if surface.errors.is_empty() {
+            surface.presented += 1;
+            Ok(())
+        } else {
+            Err(surface.errors.remove(0))
+        }
+    }
+
+    #[test]
+    fn unsupported_present_mode_falls_back_to_fifo() {
+        use wgpu::PresentMode::*;
+        assert_eq!(choose_present_mode(Mailbox, &[Fifo, Mailbox]), Mailbox);
+        assert_eq!(choose_present_mode(Immediate, &[Fifo, Mailbox]), Fifo);
+        assert_eq!(choose_present_mode(Immediate, &[]), Fifo);
+    }
+
+    #[test]
+    fn unsupported_alpha_mode_falls_back_to_opaque() {
+        use wgpu::CompositeAlphaMode::*;
+        assert_eq!(choose_alpha_mode(PreMultiplied, &[Opaque, PreMultiplied]), PreMultiplied);
+        assert_eq!(choose_alpha_mode(PreMultiplied, &[Auto, Opaque]), Opaque);
+        assert_eq!(choose_alpha_mode(PreMultiplied, &[Inherit]), Inherit);
+    }
+
+    #[test]
+    fn outdated_surface_is_reconfigured_and_frame_retried() {
+        let mut surface = FakeSurface {
+            errors: vec![SurfaceError::Outdated],
+            ..Default::default()
+        };
+        assert!(render_recovering(&mut surface, render).is_ok());
+        assert_eq!(surface.reconfigured, 1);
+        assert_eq!(surface.presented, 1);
+        assert_eq!(surface.frames_requested, 0);
+    }
+
+    #[test]
+    fn failed_retry_skips_frame() {
+        let mut surface = FakeSurface {
+            errors: vec![SurfaceError::Lost, SurfaceError::Lost],
+            ..Default::default()
+        };
+        assert!(render_recovering(&mut surface, render).is_ok());
+        assert_eq!(surface.reconfigured, 1);
+        assert_eq!(surface.presented, 0);
+        assert_eq!(surface.frames_requested, 1);
+    }
+
+    #[test]
+    fn out_of_memory_stops() {
+        let mut surface = FakeSurface {
+            errors: vec![SurfaceError::OutOfMemory],
+            ..Default::default()
+        };
+        assert!(matches!(
+            render_recovering(&mut surface, render),
+            Err(SurfaceError::OutOfMemory)
+        ));
+        assert_eq!(surface.reconfigured, 0);
+        assert_eq!(surface.frames_requested, 0);
+    }
+}
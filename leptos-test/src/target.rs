@@ -0,0 +1,234 @@
+use crate::gpu::{Gpu, PipelineState};
+
+pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24Plus;
+
+// Attachments of render passes, like the painter's layers. Offscreen targets
+// own their color texture, which later passes can bind. Canvas targets get
+// the view of the frame's surface texture passed to `pass`.
+//
+// With a sample count above 1, passes render into a multisampled texture,
+// which is resolved into the color view at the end of the pass. The depth
+// texture has the same sample count.
+pub struct Target {
+    format: wgpu::TextureFormat,
+    offscreen: bool,
+    depth: bool,
+    sample_count: u32,
+    clear_color: Option<wgpu::Color>,
+    size: (u32, u32),
+    color: Option<(wgpu::Texture, wgpu::TextureView)>,
+    msaa: Option<wgpu::TextureView>,
+    depth_view: Option<wgpu::TextureView>,
+}
+
+pub struct TargetBuilder {
+    format: wgpu::TextureFormat,
+    offscreen: bool,
+    depth: bool,
+    sample_count: u32,
+    clear_color: Option<wgpu::Color>,
+}
+
+impl Target {
+    pub fn canvas(format: wgpu::TextureFormat) -> TargetBuilder {
+        TargetBuilder {
+            format,
+            offscreen: false,
+            depth: false,
+            sample_count: 1,
+            clear_color: Some(wgpu::Color::BLACK),
+        }
+    }
+
+    // Can be bound as texture, and copied from for readbacks.
+    pub fn offscreen(format: wgpu::TextureFormat) -> TargetBuilder {
+        TargetBuilder {
+            offscreen: true,
+            ..Self::canvas(format)
+        }
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    // The formats of draws into this target, without blending and culling
+    // back faces, depth tested with `Less` if it has depth.
+    pub fn pipeline_state(&self) -> PipelineState {
+        PipelineState {
+            sample_count: self.sample_count,
+            depth_format: self.depth.then_some(DEPTH_FORMAT),
+            ..PipelineState::new(Some(self.format))
+        }
+    }
+
+    // The resolved color of an offscreen target.
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.color.as_ref().expect("canvas targets have no texture").1
+    }
+
+    pub fn texture(&self) -> Option<&wgpu::Texture> {
+        self.color.as_ref().map(|(texture, _)| texture)
+    }
+
+    // `None` keeps the contents of the last pass.
+    pub fn set_clear_color(&mut self, color: Option<wgpu::Color>) {
+        self.clear_color = color;
+    }
+
+    // Recreates the textures, which lose their contents. Sizes below 1 are
+    // raised to 1, like the surface.
+    pub fn resize(&mut self, gpu: &Gpu, width: u32, height: u32) {
+        let size = (width.max(1), height.max(1));
+        if size != self.size {
+            self.size = size;
+            self.create_textures(gpu);
+        }
+    }
+
+    // Draws into this target need a pipeline state with the new count.
+    pub fn set_sample_count(&mut self, gpu: &Gpu, sample_count: u32) {
+        if sample_count != self.sample_count {
+            self.sample_count = sample_count;
+            self.create_textures(gpu);
+        }
+    }
+
+    fn create_textures(&mut self, gpu: &Gpu) {
+        let (width, height) = self.size;
+        let texture = |label: &str, format, sample_count, usage| {
+            gpu.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage,
+                view_formats: &[],
+            })
+        };
+        self.color = self.offscreen.then(|| {
+            let color = texture(
+                "Target Texture",
+                self.format,
+                1,
+                wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_SRC,
+            );
+            let view = color.create_view(&Default::default());
+            (color, view)
+        });
+        self.msaa = (self.sample_count > 1).then(|| {
+            texture(
+                "Target MSAA Texture",
+                self.format,
+                self.sample_count,
+                wgpu::TextureUsages::RENDER_ATTACHMENT,
+            )
+            .create_view(&Default::default())
+        });
+        self.depth_view = self.depth.then(|| {
+            texture(
+                "Target Depth Texture",
+                DEPTH_FORMAT,
+                self.sample_count,
+                wgpu::TextureUsages::RENDER_ATTACHMENT,
+            )
+            .create_view(&Default::default())
+        });
+    }
+
+    // Begins a pass rendering into the target, or for canvas targets into
+    // `output`, the view of the frame. Depth is cleared to 1.0.
+    pub fn pass<'e>(
+        &self,
+        encoder: &'e mut wgpu::CommandEncoder,
+        output: Option<&wgpu::TextureView>,
+    ) -> wgpu::RenderPass<'e> {
+        let output = output
+            .or(self.color.as_ref().map(|(_, view)| view))
+            .expect("canvas targets need the frame's view");
+        let (view, resolve_target) = match &self.msaa {
+            Some(msaa) => (msaa, Some(output)),
+            None => (output, None),
+        };
+        let load = match self.clear_color {
+            Some(color) => wgpu::LoadOp::Clear(color),
+            None => wgpu::LoadOp::Load,
+        };
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Target Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                depth_slice: None,
+                resolve_target,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: self.depth_view.as_ref().map(|view| {
+                wgpu::RenderPassDepthStencilAttachment {
+                    view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }
+            }),
+            ..Default::default()
+        })
+    }
+}
+
+impl TargetBuilder {
+    // A `Depth24Plus` depth texture, for depth testing.
+    pub fn with_depth(mut self) -> Self {
+        self.depth = true;
+        self
+    }
+
+    // See `msaa::msaa_samples` for a count the adapter supports.
+    pub fn with_samples(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    // Black by default. `None` keeps the contents of the last pass, e.g. to
+    // fade them out over several frames.
+    pub fn with_clear_color(mut self, color: Option<wgpu::Color>) -> Self {
+        self.clear_color = color;
+        self
+    }
+
+    pub fn create(self, gpu: &Gpu, width: u32, height: u32) -> Target {
+        let mut target = Target {
+            format: self.format,
+            offscreen: self.offscreen,
+            depth: self.depth,
+            sample_count: self.sample_count,
+            clear_color: self.clear_color,
+            size: (width.max(1), height.max(1)),
+            color: None,
+            msaa: None,
+            depth_view: None,
+        };
+        target.create_textures(gpu);
+        target
+    }
+}
@@ -0,0 +1,232 @@
+use crate::gpu::{
+    sampler, shader, DrawCall, Gpu, Mesh, Program, Uniform, SAMPLER_FRAG, TEXTURE_FRAG,
+    UNIFORM_FRAG, UNIFORM_VERT,
+};
+use crate::overlay::Overlay;
+use crate::texture::texture_from_rgba;
+use bytemuck::Zeroable;
+use trivalibs::prelude::*;
+
+// 5x7 pixel glyphs for the printable ASCII range, ' ' to '~'. One byte per
+// column, left to right, with bit 0 as the top row.
+const FIRST_GLYPH: char = ' ';
+const GLYPHS: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x5F, 0x00, 0x00], // '!'
+    [0x00, 0x07, 0x00, 0x07, 0x00], // '"'
+    [0x14, 0x7F, 0x14, 0x7F, 0x14], // '#'
+    [0x24, 0x2A, 0x7F, 0x2A, 0x12], // '$'
+    [0x23, 0x13, 0x08, 0x64, 0x62], // '%'
+    [0x36, 0x49, 0x56, 0x20, 0x50], // '&'
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '\''
+    [0x00, 0x1C, 0x22, 0x41, 0x00], // '('
+    [0x00, 0x41, 0x22, 0x1C, 0x00], // ')'
+    [0x2A, 0x1C, 0x7F, 0x1C, 0x2A], // '*'
+    [0x08, 0x08, 0x3E, 0x08, 0x08], // '+'
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ','
+    [0x08, 0x08, 0x08, 0x08, 0x08], // '-'
+    [0x00, 0x60, 0x60, 0x00, 0x00], // '.'
+    [0x20, 0x10, 0x08, 0x04, 0x02], // '/'
+    [0x3E, 0x51, 0x49, 0x45, 0x3E], // '0'
+    [0x00, 0x42, 0x7F, 0x40, 0x00], // '1'
+    [0x72, 0x49, 0x49, 0x49, 0x46], // '2'
+    [0x21, 0x41, 0x49, 0x4D, 0x33], // '3'
+    [0x18, 0x14, 0x12, 0x7F, 0x10], // '4'
+    [0x27, 0x45, 0x45, 0x45, 0x39], // '5'
+    [0x3C, 0x4A, 0x49, 0x49, 0x31], // '6'
+    [0x41, 0x21, 0x11, 0x09, 0x07], // '7'
+    [0x36, 0x49, 0x49, 0x49, 0x36], // '8'
+    [0x46, 0x49, 0x49, 0x29, 0x1E], // '9'
+    [0x00, 0x36, 0x36, 0x00, 0x00], // ':'
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ';'
+    [0x08, 0x14, 0x22, 0x41, 0x00], // '<'
+    [0x14, 0x14, 0x14, 0x14, 0x14], // '='
+    [0x00, 0x41, 0x22, 0x14, 0x08], // '>'
+    [0x02, 0x01, 0x59, 0x09, 0x06], // '?'
+    [0x3E, 0x41, 0x5D, 0x59, 0x4E], // '@'
+    [0x7C, 0x12, 0x11, 0x12, 0x7C], // 'A'
+    [0x7F, 0x49, 0x49, 0x49, 0x36], // 'B'
+    [0x3E, 0x41, 0x41, 0x41, 0x22], // 'C'
+    [0x7F, 0x41, 0x41, 0x41, 0x3E], // 'D'
+    [0x7F, 0x49, 0x49, 0x49, 0x41], // 'E'
+    [0x7F, 0x09, 0x09, 0x09, 0x01], // 'F'
+    [0x3E, 0x41, 0x41, 0x51, 0x73], // 'G'
+    [0x7F, 0x08, 0x08, 0x08, 0x7F], // 'H'
+    [0x00, 0x41, 0x7F, 0x41, 0x00], // 'I'
+    [0x20, 0x40, 0x41, 0x3F, 0x01], // 'J'
+    [0x7F, 0x08, 0x14, 0x22, 0x41], // 'K'
+    [0x7F, 0x40, 0x40, 0x40, 0x40], // 'L'
+    [0x7F, 0x02, 0x1C, 0x02, 0x7F], // 'M'
+    [0x7F, 0x04, 0x08, 0x10, 0x7F], // 'N'
+    [0x3E, 0x41, 0x41, 0x41, 0x3E], // 'O'
+    [0x7F, 0x09, 0x09, 0x09, 0x06], // 'P'
+    [0x3E, 0x41, 0x51, 0x21, 0x5E], // 'Q'
+    [0x7F, 0x09, 0x19, 0x29, 0x46], // 'R'
+    [0x26, 0x49, 0x49, 0x49, 0x32], // 'S'
+    [0x03, 0x01, 0x7F, 0x01, 0x03], // 'T'
+    [0x3F, 0x40, 0x40, 0x40, 0x3F], // 'U'
+    [0x1F, 0x20, 0x40, 0x20, 0x1F], // 'V'
+    [0x3F, 0x40, 0x38, 0x40, 0x3F], // 'W'
+    [0x63, 0x14, 0x08, 0x14, 0x63], // 'X'
+    [0x03, 0x04, 0x78, 0x04, 0x03], // 'Y'
+    [0x61, 0x59, 0x49, 0x4D, 0x43], // 'Z'
+    [0x00, 0x7F, 0x41, 0x41, 0x41], // '['
+    [0x02, 0x04, 0x08, 0x10, 0x20], // '\\'
+    [0x41, 0x41, 0x41, 0x7F, 0x00], // ']'
+    [0x04, 0x02, 0x01, 0x02, 0x04], // '^'
+    [0x40, 0x40, 0x40, 0x40, 0x40], // '_'
+    [0x00, 0x01, 0x02, 0x04, 0x00], // '`'
+    [0x20, 0x54, 0x54, 0x54, 0x78], // 'a'
+    [0x7F, 0x28, 0x44, 0x44, 0x38], // 'b'
+    [0x38, 0x44, 0x44, 0x44, 0x28], // 'c'
+    [0x38, 0x44, 0x44, 0x28, 0x7F], // 'd'
+    [0x38, 0x54, 0x54, 0x54, 0x18], // 'e'
+    [0x00, 0x08, 0x7E, 0x09, 0x02], // 'f'
+    [0x0C, 0x52, 0x52, 0x52, 0x3E], // 'g'
+    [0x7F, 0x08, 0x04, 0x04, 0x78], // 'h'
+    [0x00, 0x44, 0x7D, 0x40, 0x00], // 'i'
+    [0x20, 0x40, 0x40, 0x3D, 0x00], // 'j'
+    [0x7F, 0x10, 0x28, 0x44, 0x00], // 'k'
+    [0x00, 0x41, 0x7F, 0x40, 0x00], // 'l'
+    [0x7C, 0x04, 0x78, 0x04, 0x78], // 'm'
+    [0x7C, 0x08, 0x04, 0x04, 0x78], // 'n'
+    [0x38, 0x44, 0x44, 0x44, 0x38], // 'o'
+    [0x7C, 0x14, 0x14, 0x14, 0x08], // 'p'
+    [0x08, 0x14, 0x14, 0x18, 0x7C], // 'q'
+    [0x7C, 0x08, 0x04, 0x04, 0x08], // 'r'
+    [0x48, 0x54, 0x54, 0x54, 0x24], // 's'
+    [0x04, 0x3F, 0x44, 0x40, 0x20], // 't'
+    [0x3C, 0x40, 0x40, 0x20, 0x7C], // 'u'
+    [0x1C, 0x20, 0x40, 0x20, 0x1C], // 'v'
+    [0x3C, 0x40, 0x30, 0x40, 0x3C], // 'w'
+    [0x44, 0x28, 0x10, 0x28, 0x44], // 'x'
+    [0x0C, 0x50, 0x50, 0x50, 0x3C], // 'y'
+    [0x44, 0x64, 0x54, 0x4C, 0x44], // 'z'
+    [0x00, 0x08, 0x36, 0x41, 0x00], // '{'
+    [0x00, 0x00, 0x7F, 0x00, 0x00], // '|'
+    [0x00, 0x41, 0x36, 0x08, 0x00], // '}'
+    [0x08, 0x04, 0x08, 0x10, 0x08], // '~'
+];
+
+// Each glyph is placed in a 6x8 cell of the atlas, which leaves one empty
+// column and row as spacing between characters and lines.
+const CELL_WIDTH: u32 = 6;
+const CELL_HEIGHT: u32 = 8;
+const ATLAS_COLUMNS: u32 = 16;
+const ATLAS_ROWS: u32 = (GLYPHS.len() as u32).div_ceil(ATLAS_COLUMNS);
+const ATLAS_WIDTH: u32 = ATLAS_COLUMNS * CELL_WIDTH;
+const ATLAS_HEIGHT: u32 = ATLAS_ROWS * CELL_HEIGHT;
+
+// White glyphs on a transparent background, tinted by the text color.
+fn atlas_rgba() -> Vec<u8> {
+    let mut data = vec![0; (ATLAS_WIDTH * ATLAS_HEIGHT * 4) as usize];
+    for (i, glyph) in GLYPHS.iter().enumerate() {
+        let cell_x = i as u32 % ATLAS_COLUMNS * CELL_WIDTH;
+        let cell_y = i as u32 / ATLAS_COLUMNS * CELL_HEIGHT;
+        for (x, column) in glyph.iter().enumerate() {
+            for y in 0..7 {
+                if column >> y & 1 == 1 {
+                    let pixel = (cell_y + y) * ATLAS_WIDTH + cell_x + x as u32;
+                    data[pixel as usize * 4..][..4].copy_from_slice(&[255; 4]);
+                }
+            }
+        }
+    }
+    data
+}
+
+// Index into `GLYPHS`, `None` for anything outside printable ASCII.
+fn glyph_index(c: char) -> Option<u32> {
+    let i = (c as u32).checked_sub(FIRST_GLYPH as u32)?;
+    (i < GLYPHS.len() as u32).then_some(i)
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct TextVertex {
+    position: Vec3,
+    uv: Vec2,
+}
+
+// A line of text drawn in the 2D overlay, from a built-in bitmap font. Each
+// character is a textured quad, and changing the text rewrites the vertices
+// of its mesh.
+pub struct Text {
+    pub draw: DrawCall,
+    mesh: Mesh,
+}
+
+impl Text {
+    pub fn new(gpu: &Gpu, overlay: &Overlay, color: Vec4) -> Self {
+        let program = Program::build(gpu, shader!("textured_vertex"))
+            .with_fragment(shader!("text_fragment"))
+            .with_vertex(&[wgpu::VertexFormat::Float32x3, wgpu::VertexFormat::Float32x2])
+            .with_bindings(&[
+                UNIFORM_VERT,
+                UNIFORM_VERT,
+                SAMPLER_FRAG,
+                TEXTURE_FRAG,
+                UNIFORM_FRAG,
+            ])
+            .create();
+
+        let atlas = texture_from_rgba(gpu, ATLAS_WIDTH, ATLAS_HEIGHT, &atlas_rgba());
+        // Glyphs are scaled by whole pixels, nearest sampling keeps them sharp.
+        let sampler = sampler(gpu, wgpu::FilterMode::Nearest, wgpu::AddressMode::ClampToEdge);
+        let model_mat = Uniform::new(gpu, Mat4::IDENTITY);
+        let color_buffer = Uniform::new(gpu, color);
+        let bind_group = program.bind_group(
+            gpu,
+            0,
+            &[
+                overlay.proj_mat().binding(),
+                model_mat.binding(),
+                wgpu::BindingResource::Sampler(&sampler),
+                wgpu::BindingResource::TextureView(&atlas),
+                color_buffer.binding(),
+            ],
+        );
+
+        // A single empty quad, until the first `set`.
+        let mesh = Mesh::new(gpu, &[TextVertex::zeroed(); 6]);
+        let draw = DrawCall::new(gpu, &program, &mesh, vec![bind_group], overlay.state());
+
+        Self { draw, mesh }
+    }
+
+    // Lays out `text` from `pos`, the top left corner in pixels. `size` is the
+    // line height in pixels, rounded to a whole multiple of the 8 pixel font
+    // cell. Characters without a glyph, i.e. anything but printable ASCII,
+    // are skipped.
+    pub fn set(&mut self, gpu: &Gpu, text: &str, pos: Vec2, size: f32) {
+        let scale = (size / CELL_HEIGHT as f32).round().max(1.0);
+        let cell = vec2(CELL_WIDTH as f32, CELL_HEIGHT as f32) * scale;
+        let uv_cell = vec2(1.0 / ATLAS_COLUMNS as f32, 1.0 / ATLAS_ROWS as f32);
+
+        let mut vertices = Vec::with_capacity(text.len() * 6);
+        let mut x = pos.x.round();
+        let y = pos.y.round();
+        for i in text.chars().filter_map(glyph_index) {
+            let uv = vec2((i % ATLAS_COLUMNS) as f32, (i / ATLAS_COLUMNS) as f32) * uv_cell;
+            let vert = |dx: f32, dy: f32| TextVertex {
+                position: vec3(x + dx * cell.x, y + dy * cell.y, 0.0),
+                uv: uv + vec2(dx, dy) * uv_cell,
+            };
+            vertices.extend([
+                vert(0.0, 0.0),
+                vert(1.0, 0.0),
+                vert(1.0, 1.0),
+                vert(0.0, 0.0),
+                vert(1.0, 1.0),
+                vert(0.0, 1.0),
+            ]);
+            x += cell.x;
+        }
+        // Keep a degenerate quad instead of an empty vertex buffer.
+        if vertices.is_empty() {
+            vertices.resize(6, TextVertex::zeroed());
+        }
+        self.mesh.update_vertices(gpu, &vertices);
+        self.draw.set_mesh(&self.mesh);
+    }
+}
@@ -0,0 +1,126 @@
+use crate::gpu::Gpu;
+use std::fmt;
+use wgpu::util::DeviceExt;
+
+#[derive(Debug)]
+pub enum ImageError {
+    Decode(png::DecodingError),
+    UnsupportedFormat(png::ColorType, png::BitDepth),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::Decode(e) => write!(f, "failed to decode png: {e}"),
+            ImageError::UnsupportedFormat(color, depth) => {
+                write!(f, "unsupported png format {color:?} {depth:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+// Decoded 8 bit rgba image, as expected by `texture_from_rgba`.
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+// Decodes an 8 bit png into rgba. Palette and low bit depth images are
+// expanded, rgb and gray images get an opaque alpha channel. The dimensions
+// don't need to be powers of two.
+pub fn decode_png(bytes: &[u8]) -> Result<RgbaImage, ImageError> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder.read_info().map_err(ImageError::Decode)?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(ImageError::Decode)?;
+    buf.truncate(info.buffer_size());
+
+    let data = match (info.color_type, info.bit_depth) {
+        (png::ColorType::Rgba, png::BitDepth::Eight) => buf,
+        (png::ColorType::Rgb, png::BitDepth::Eight) => buf
+            .chunks(3)
+            .flat_map(|c| [c[0], c[1], c[2], 255])
+            .collect(),
+        (png::ColorType::GrayscaleAlpha, png::BitDepth::Eight) => buf
+            .chunks(2)
+            .flat_map(|c| [c[0], c[0], c[0], c[1]])
+            .collect(),
+        (png::ColorType::Grayscale, png::BitDepth::Eight) => {
+            buf.iter().flat_map(|&c| [c, c, c, 255]).collect()
+        }
+        (color, depth) => return Err(ImageError::UnsupportedFormat(color, depth)),
+    };
+
+    Ok(RgbaImage {
+        width: info.width,
+        height: info.height,
+        data,
+    })
+}
+
+// Uploads `layers` images of 8 bit rgba pixels, one after another in `data`,
+// and returns a view of `dimension` to bind, e.g. with `TEXTURE_FRAG`.
+pub fn upload_rgba(
+    gpu: &Gpu,
+    (width, height, layers): (u32, u32, u32),
+    data: &[u8],
+    format: wgpu::TextureFormat,
+    dimension: wgpu::TextureViewDimension,
+) -> wgpu::TextureView {
+    assert_eq!(data.len(), (width * height * layers * 4) as usize, "rgba data size mismatch");
+    gpu.device
+        .create_texture_with_data(
+            &gpu.queue,
+            &wgpu::TextureDescriptor {
+                label: None,
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: layers,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            data,
+        )
+        .create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(dimension),
+            ..Default::default()
+        })
+}
+
+// A texture of sRGB colors, sampled as linear values.
+pub fn texture_from_rgba(gpu: &Gpu, width: u32, height: u32, data: &[u8]) -> wgpu::TextureView {
+    upload_rgba(
+        gpu,
+        (width, height, 1),
+        data,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+        wgpu::TextureViewDimension::D2,
+    )
+}
+
+// A texture of data like normals, sampled as stored.
+pub fn texture_from_rgba_linear(
+    gpu: &Gpu,
+    width: u32,
+    height: u32,
+    data: &[u8],
+) -> wgpu::TextureView {
+    upload_rgba(
+        gpu,
+        (width, height, 1),
+        data,
+        wgpu::TextureFormat::Rgba8Unorm,
+        wgpu::TextureViewDimension::D2,
+    )
+}
@@ -0,0 +1,138 @@
+use crate::gpu::{sampler, shader, DrawCall, Gpu, Mesh, PipelineState, Program, Uniform};
+use crate::gpu::{SAMPLER_FRAG, TEXTURE_FRAG, UNIFORM_VERT};
+use crate::msaa::{msaa_samples, MSAA_SAMPLES};
+use crate::surface::{self, Frame};
+use crate::target::Target;
+use crate::texture::{decode_png, texture_from_rgba};
+use trivalibs::math::transform::Transform;
+use trivalibs::painter::app::Event;
+use trivalibs::painter::prelude::*;
+use trivalibs::prelude::*;
+use trivalibs::rendering::camera::{CamProps, PerspectiveCamera};
+
+// 100x60 pixels, to exercise non power of two textures.
+const IMAGE: &[u8] = include_bytes!("../assets/checker.png");
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: Vec3,
+    uv: Vec2,
+}
+
+const fn vert(x: f32, y: f32, u: f32, v: f32) -> Vertex {
+    Vertex {
+        position: vec3(x, y, 0.0),
+        uv: vec2(u, v),
+    }
+}
+
+const INDICES: &[u32] = &[0, 1, 2, 0, 2, 3];
+
+// Draws a png onto a quad, sampled with per-vertex uvs.
+pub struct TexturedQuadApp {
+    gpu: Gpu,
+    cam: PerspectiveCamera,
+    transform: Transform,
+    model_mat: Uniform<Mat4>,
+    vp_mat: Uniform<Mat4>,
+
+    canvas: Target,
+    draw: DrawCall,
+}
+
+impl CanvasApp<()> for TexturedQuadApp {
+    fn init(p: &mut Painter) -> Self {
+        let gpu = Gpu::new(p);
+        let program = Program::build(&gpu, shader!("textured_vertex"))
+            .with_fragment(shader!("textured_fragment"))
+            .with_vertex(&[Float32x3, Float32x2])
+            .with_bindings(&[UNIFORM_VERT, UNIFORM_VERT, SAMPLER_FRAG, TEXTURE_FRAG])
+            .create();
+
+        let image = decode_png(IMAGE).expect("embedded image is a valid png");
+        let texture = texture_from_rgba(&gpu, image.width, image.height, &image.data);
+
+        // Keep the image's aspect ratio, uv (0, 0) is the top left of the image.
+        let w = image.width as f32 / image.height as f32;
+        let vertices = [
+            vert(-w, -1.0, 0.0, 1.0),
+            vert(w, -1.0, 1.0, 1.0),
+            vert(w, 1.0, 1.0, 0.0),
+            vert(-w, 1.0, 0.0, 0.0),
+        ];
+        let mesh = Mesh::new(&gpu, &vertices).with_indices(&gpu, INDICES);
+
+        let model_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let vp_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let sampler = sampler(&gpu, wgpu::FilterMode::Linear, wgpu::AddressMode::ClampToEdge);
+
+        let canvas = Target::canvas(surface::view_format(p))
+            .with_samples(msaa_samples(p, MSAA_SAMPLES))
+            .create(&gpu, 1, 1);
+        let bind_group = program.bind_group(
+            &gpu,
+            0,
+            &[
+                vp_mat.binding(),
+                model_mat.binding(),
+                wgpu::BindingResource::Sampler(&sampler),
+                wgpu::BindingResource::TextureView(&texture),
+            ],
+        );
+        let draw = DrawCall::new(
+            &gpu,
+            &program,
+            &mesh,
+            vec![bind_group],
+            PipelineState {
+                cull_mode: None,
+                ..canvas.pipeline_state()
+            },
+        );
+
+        Self {
+            gpu,
+            cam: PerspectiveCamera::create(CamProps {
+                fov: Some(0.6),
+                translation: Some(vec3(0.0, 0.0, 6.0)),
+                ..default()
+            }),
+            transform: Transform::from_translation(Vec3::ZERO),
+            model_mat,
+            vp_mat,
+
+            canvas,
+            draw,
+        }
+    }
+
+    fn resize(&mut self, _p: &mut Painter, width: u32, height: u32) {
+        self.cam.set_aspect_ratio(width as f32 / height as f32);
+        self.canvas.resize(&self.gpu, width, height);
+
+        self.vp_mat.update(&self.gpu, self.cam.view_proj_mat());
+    }
+
+    fn update(&mut self, p: &mut Painter, tpf: f32) {
+        self.transform.rotate_y(tpf * 0.5);
+        self.model_mat.update(&self.gpu, self.transform.model_mat());
+        p.request_next_frame();
+    }
+
+    fn render(&self, p: &mut Painter) -> Result<(), SurfaceError> {
+        let Some(frame) = Frame::acquire(p)? else {
+            return Ok(());
+        };
+        let mut encoder = self.gpu.encoder("Textured Quad Encoder");
+        self.draw.draw(
+            &mut self.canvas.pass(&mut encoder, Some(&frame.view)),
+            self.canvas.size(),
+        );
+        self.gpu.queue.submit([encoder.finish()]);
+        frame.present();
+        Ok(())
+    }
+
+    fn event(&mut self, _e: Event<()>, _p: &mut Painter) {}
+}
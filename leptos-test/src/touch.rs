@@ -0,0 +1,101 @@
+use trivalibs::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    // Movement of a single finger in pixels.
+    Orbit(Vec2),
+    // Change of the distance between two fingers in pixels, positive when
+    // spreading them apart.
+    Zoom(f32),
+}
+
+// A single finger lifted within this many pixels of where it touched down is
+// a tap, further away it was an orbit.
+pub const TAP_SLOP: f32 = 10.0;
+
+// Turns raw touch points into camera gestures: one finger orbits, two
+// fingers pinch to zoom, and lifting a finger that barely moved is a tap.
+//
+// Gestures are measured against the previous touch move. Whenever a finger
+// is added or lifted, this reference is reset to the new set of fingers, so
+// the camera doesn't jump when e.g. going from pinch back to orbit.
+#[derive(Default)]
+pub struct TouchGestures {
+    touches: Vec<(u64, Vec2)>,
+    last_center: Option<Vec2>,
+    last_spread: Option<f32>,
+    // Finger and start position of a possible tap, until it moves too far or
+    // another finger joins.
+    tap: Option<(u64, Vec2)>,
+}
+
+impl TouchGestures {
+    pub fn start(&mut self, id: u64, pos: Vec2) {
+        self.touches.retain(|(t, _)| *t != id);
+        self.touches.push((id, pos));
+        self.tap = (self.touches.len() == 1).then_some((id, pos));
+        self.reset_reference();
+    }
+
+    // Returns the position of the tap if the finger was lifted as one.
+    pub fn end(&mut self, id: u64) -> Option<Vec2> {
+        self.touches.retain(|(t, _)| *t != id);
+        self.reset_reference();
+        let (tap_id, pos) = self.tap.take()?;
+        (tap_id == id).then_some(pos)
+    }
+
+    pub fn move_to(&mut self, id: u64, pos: Vec2) -> Option<Gesture> {
+        let touch = self.touches.iter_mut().find(|(t, _)| *t == id)?;
+        touch.1 = pos;
+        if self.tap.is_some_and(|(t, start)| t == id && start.distance(pos) > TAP_SLOP) {
+            self.tap = None;
+        }
+
+        match self.touches.len() {
+            1 => {
+                let center = self.touches[0].1;
+                let last = self.last_center.replace(center)?;
+                Some(Gesture::Orbit(center - last))
+            }
+            2 => {
+                let spread = self.spread()?;
+                let last = self.last_spread.replace(spread)?;
+                Some(Gesture::Zoom(spread - last))
+            }
+            _ => None,
+        }
+    }
+
+    fn spread(&self) -> Option<f32> {
+        match self.touches.as_slice() {
+            [(_, a), (_, b), ..] => Some(a.distance(*b)),
+            _ => None,
+        }
+    }
+
+    fn reset_reference(&mut self) {
+        self.last_center = self.touches.first().map(|(_, p)| *p);
+        self.last_spread = self.spread();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A touch only counts as a tap if it barely moved.
+    #[test]
+    fn tap_must_stay_within_slop() {
+        let pos = vec2(100.0, 100.0);
+        let mut touches = TouchGestures::default();
+        touches.start(1, pos);
+        touches.move_to(1, pos + vec2(TAP_SLOP * 0.5, 0.0));
+        assert!(touches.end(1).is_some());
+
+        // Moved far enough to orbit.
+        touches.start(2, pos);
+        touches.move_to(2, pos + vec2(TAP_SLOP * 2.0, 0.0));
+        assert!(touches.end(2).is_none());
+    }
+}
@@ -0,0 +1,198 @@
+use crate::fullscreen::{fullscreen_mesh, fullscreen_program, fullscreen_state};
+use crate::gpu::{sampler, shader, DrawCall, Gpu, Mesh, PipelineState, Program, Uniform};
+use crate::gpu::{SAMPLER_FRAG, TEXTURE_FRAG, UNIFORM_FRAG, UNIFORM_VERT};
+use crate::surface::{self, Frame};
+use crate::target::Target;
+use std::f32::consts::TAU;
+use trivalibs::painter::app::Event;
+use trivalibs::painter::prelude::*;
+use trivalibs::prelude::*;
+use trivalibs::rendering::camera::{CamProps, PerspectiveCamera};
+
+const VERTICES: &[Vec3] = &[vec3(0.0, 5.0, 0.0), vec3(-2.5, 0., 0.0), vec3(2.5, 0., 0.0)];
+
+// Path of the triangle: a circle, wobbling in and out.
+const ORBIT_RADIUS: f32 = 20.0;
+const ORBIT_SPEED: f32 = 1.5;
+const WOBBLE: f32 = 6.0;
+
+// Per frame, the previous frame keeps `1 - FADE_ALPHA` of its brightness,
+// minus `FADE_FLOOR`. Without the floor, 8 bit targets round dim trails to the
+// same value forever instead of reaching black.
+const FADE_ALPHA: f32 = 0.08;
+const FADE_FLOOR: f32 = 0.004;
+
+// A triangle leaving a motion trail. The `trail` target is not cleared
+// between frames: each frame first darkens what is already there with a
+// fullscreen fade, then draws the triangle at its new position on top. The
+// result is drawn onto the canvas by a fullscreen composite, since the surface
+// texture doesn't keep its contents between frames.
+pub struct TrailsApp {
+    gpu: Gpu,
+    cam: PerspectiveCamera,
+    vp_mat: Uniform<Mat4>,
+    model_mat: Uniform<Mat4>,
+    time: f32,
+
+    trail: Target,
+    fade: DrawCall,
+    triangle: DrawCall,
+    canvas: Target,
+    // Samples the trail's texture with bind group 1, which is replaced when
+    // the texture is recreated on resize.
+    composite: DrawCall,
+}
+
+impl CanvasApp<()> for TrailsApp {
+    fn init(p: &mut Painter) -> Self {
+        let gpu = Gpu::new(p);
+        let format = surface::view_format(p);
+
+        // Keeps its contents between frames. No multisampling, which would
+        // need the samples stored and resolved again every frame.
+        let trail = Target::offscreen(format)
+            .with_clear_color(None)
+            .create(&gpu, 1, 1);
+
+        let fade_program = fullscreen_program(&gpu, shader!("fade_fragment"))
+            .with_bindings(&[UNIFORM_FRAG])
+            .create();
+        let fade_color = Uniform::new(&gpu, vec4(FADE_FLOOR, FADE_FLOOR, FADE_FLOOR, FADE_ALPHA));
+        // `dst * (1 - a) - src`, clamped at 0 by the unorm target.
+        let fade_state = PipelineState {
+            blend: Some(wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::ReverseSubtract,
+                },
+                alpha: wgpu::BlendComponent::REPLACE,
+            }),
+            ..fullscreen_state(trail.pipeline_state())
+        };
+        let fade = DrawCall::new(
+            &gpu,
+            &fade_program,
+            &fullscreen_mesh(),
+            vec![fade_program.bind_group(&gpu, 0, &[fade_color.binding()])],
+            fade_state,
+        );
+
+        let program = Program::build(&gpu, shader!("flat_vertex"))
+            .with_fragment(shader!("flat_fragment"))
+            .with_vertex(&[Float32x3])
+            .with_bindings(&[UNIFORM_VERT, UNIFORM_VERT, UNIFORM_FRAG])
+            .create();
+
+        let mesh = Mesh::new(&gpu, VERTICES);
+        let vp_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let model_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let color = Uniform::new(&gpu, vec4(0.2, 1.0, 0.6, 1.0));
+        let bind_group = program.bind_group(
+            &gpu,
+            0,
+            &[vp_mat.binding(), model_mat.binding(), color.binding()],
+        );
+        let triangle = DrawCall::new(
+            &gpu,
+            &program,
+            &mesh,
+            vec![bind_group],
+            PipelineState {
+                cull_mode: None,
+                ..trail.pipeline_state()
+            },
+        );
+
+        let composite_program = fullscreen_program(&gpu, shader!("composite_fragment"))
+            .with_bindings(&[SAMPLER_FRAG, UNIFORM_FRAG])
+            .with_bindings(&[TEXTURE_FRAG])
+            .create();
+        let sampler = sampler(&gpu, wgpu::FilterMode::Linear, wgpu::AddressMode::ClampToEdge);
+        let post_mode = Uniform::new(&gpu, 0u32);
+        let canvas = Target::canvas(format).create(&gpu, 1, 1);
+        let composite = DrawCall::new(
+            &gpu,
+            &composite_program,
+            &fullscreen_mesh(),
+            vec![
+                composite_program.bind_group(
+                    &gpu,
+                    0,
+                    &[wgpu::BindingResource::Sampler(&sampler), post_mode.binding()],
+                ),
+                composite_program.bind_group(
+                    &gpu,
+                    1,
+                    &[wgpu::BindingResource::TextureView(trail.view())],
+                ),
+            ],
+            fullscreen_state(canvas.pipeline_state()),
+        );
+
+        Self {
+            gpu,
+            cam: PerspectiveCamera::create(CamProps {
+                fov: Some(0.6),
+                translation: Some(vec3(0.0, 0.0, 80.0)),
+                ..default()
+            }),
+            vp_mat,
+            model_mat,
+            time: 0.0,
+
+            trail,
+            fade,
+            triangle,
+            canvas,
+            composite,
+        }
+    }
+
+    fn resize(&mut self, _p: &mut Painter, width: u32, height: u32) {
+        self.cam.set_aspect_ratio(width as f32 / height as f32);
+        self.trail.resize(&self.gpu, width, height);
+        self.canvas.resize(&self.gpu, width, height);
+        let texture = wgpu::BindingResource::TextureView(self.trail.view());
+        let bind_group = self
+            .composite
+            .program()
+            .bind_group(&self.gpu, 1, &[texture]);
+        self.composite.set_bind_group(1, bind_group);
+
+        self.vp_mat.update(&self.gpu, self.cam.view_proj_mat());
+    }
+
+    fn update(&mut self, p: &mut Painter, tpf: f32) {
+        self.time = (self.time + tpf * ORBIT_SPEED) % TAU;
+        let radius = ORBIT_RADIUS + (self.time * 3.0).sin() * WOBBLE;
+        let (sin, cos) = self.time.sin_cos();
+        self.model_mat.update(
+            &self.gpu,
+            Mat4::from_translation(vec3(cos * radius, sin * radius, 0.0))
+                * Mat4::from_rotation_z(self.time),
+        );
+        p.request_next_frame();
+    }
+
+    fn render(&self, p: &mut Painter) -> Result<(), SurfaceError> {
+        let Some(frame) = Frame::acquire(p)? else {
+            return Ok(());
+        };
+        let mut encoder = self.gpu.encoder("Trails Encoder");
+        {
+            let mut pass = self.trail.pass(&mut encoder, None);
+            self.fade.draw(&mut pass, self.trail.size());
+            self.triangle.draw(&mut pass, self.trail.size());
+        }
+        self.composite.draw(
+            &mut self.canvas.pass(&mut encoder, Some(&frame.view)),
+            self.canvas.size(),
+        );
+        self.gpu.queue.submit([encoder.finish()]);
+        frame.present();
+        Ok(())
+    }
+
+    fn event(&mut self, _e: Event<()>, _p: &mut Painter) {}
+}
@@ -0,0 +1,142 @@
+use crate::gpu::{shader, DrawCall, Gpu, Mesh, PipelineState, Program, Uniform, UNIFORM_VERT};
+use crate::msaa::{msaa_samples, MSAA_SAMPLES};
+use crate::surface::{self, Frame};
+use crate::target::Target;
+use bytemuck::Zeroable;
+use trivalibs::math::transform::Transform;
+use trivalibs::painter::app::Event;
+use trivalibs::painter::prelude::*;
+use trivalibs::prelude::*;
+use trivalibs::rendering::camera::{CamProps, PerspectiveCamera};
+
+const VERTICES: &[Vec3] = &[vec3(0.0, 5.0, 0.0), vec3(-2.5, 0., 0.0), vec3(2.5, 0., 0.0)];
+
+// Must match `ShapeUniforms` in the shader crate. All fields are 16 byte
+// aligned, so the std140 layout has no padding.
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct ShapeUniforms {
+    model_mat: Mat4,
+    vp_mat: Mat4,
+    color: Vec4,
+}
+
+const _: () = assert!(std::mem::size_of::<ShapeUniforms>() == 144);
+
+// Two triangles in one pass, sharing program and mesh, each with its own
+// uniform struct holding model matrix, view projection and color. The draws
+// are recorded in order, switching bind groups in between.
+pub struct TwoShapesApp {
+    gpu: Gpu,
+    cam: PerspectiveCamera,
+    left: Transform,
+    right: Transform,
+    left_color: Vec4,
+    right_color: Vec4,
+    left_uniforms: Uniform<ShapeUniforms>,
+    right_uniforms: Uniform<ShapeUniforms>,
+
+    canvas: Target,
+    shapes: [DrawCall; 2],
+}
+
+impl CanvasApp<()> for TwoShapesApp {
+    fn init(p: &mut Painter) -> Self {
+        let gpu = Gpu::new(p);
+        let program = Program::build(&gpu, shader!("flat_struct_vertex"))
+            .with_fragment(shader!("flat_struct_fragment"))
+            .with_vertex(&[Float32x3])
+            .with_bindings(&[UNIFORM_VERT])
+            .create();
+
+        let mesh = Mesh::new(&gpu, VERTICES);
+
+        let left_uniforms = Uniform::new(&gpu, ShapeUniforms::zeroed());
+        let right_uniforms = Uniform::new(&gpu, ShapeUniforms::zeroed());
+
+        let canvas = Target::canvas(surface::view_format(p))
+            .with_samples(msaa_samples(p, MSAA_SAMPLES))
+            .create(&gpu, 1, 1);
+        let state = PipelineState {
+            cull_mode: None,
+            ..canvas.pipeline_state()
+        };
+        let shape = |uniforms: &Uniform<ShapeUniforms>| {
+            let bind_group = program.bind_group(&gpu, 0, &[uniforms.binding()]);
+            DrawCall::new(&gpu, &program, &mesh, vec![bind_group], state)
+        };
+        let shapes = [shape(&left_uniforms), shape(&right_uniforms)];
+
+        Self {
+            cam: PerspectiveCamera::create(CamProps {
+                fov: Some(0.6),
+                translation: Some(vec3(0.0, 0.0, 80.0)),
+                ..default()
+            }),
+            left: Transform::from_translation(vec3(-15.0, -10.0, 0.0)).with_scale(Vec3::splat(4.0)),
+            right: Transform::from_translation(vec3(15.0, -10.0, 0.0)).with_scale(Vec3::splat(4.0)),
+            left_color: vec4(1.0, 0.2, 0.2, 1.0),
+            right_color: vec4(0.2, 0.4, 1.0, 1.0),
+            left_uniforms,
+            right_uniforms,
+            gpu,
+
+            canvas,
+            shapes,
+        }
+    }
+
+    fn resize(&mut self, _p: &mut Painter, width: u32, height: u32) {
+        self.cam.set_aspect_ratio(width as f32 / height as f32);
+        self.canvas.resize(&self.gpu, width, height);
+        self.update_uniforms();
+    }
+
+    fn update(&mut self, p: &mut Painter, tpf: f32) {
+        self.left.rotate_y(tpf * 0.5);
+        self.right.rotate_y(-tpf * 0.5);
+        self.update_uniforms();
+        p.request_next_frame();
+    }
+
+    fn render(&self, p: &mut Painter) -> Result<(), SurfaceError> {
+        let Some(frame) = Frame::acquire(p)? else {
+            return Ok(());
+        };
+        let mut encoder = self.gpu.encoder("Two Shapes Encoder");
+        {
+            let mut pass = self.canvas.pass(&mut encoder, Some(&frame.view));
+            for shape in &self.shapes {
+                shape.draw(&mut pass, self.canvas.size());
+            }
+        }
+        self.gpu.queue.submit([encoder.finish()]);
+        frame.present();
+        Ok(())
+    }
+
+    fn event(&mut self, _e: Event<()>, _p: &mut Painter) {}
+}
+
+impl TwoShapesApp {
+    // One write per shape, the view projection is repeated in both structs.
+    fn update_uniforms(&self) {
+        let vp_mat = self.cam.view_proj_mat();
+        self.left_uniforms.update(
+            &self.gpu,
+            ShapeUniforms {
+                model_mat: self.left.model_mat(),
+                vp_mat,
+                color: self.left_color,
+            },
+        );
+        self.right_uniforms.update(
+            &self.gpu,
+            ShapeUniforms {
+                model_mat: self.right.model_mat(),
+                vp_mat,
+                color: self.right_color,
+            },
+        );
+    }
+}
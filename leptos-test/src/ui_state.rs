@@ -0,0 +1,78 @@
+use crate::render::{ColorEvent, CAM_FOV, LIGHT_DIR, LIGHT_INTENSITY, ROTATION_SPEED, SHININESS};
+use serde::{Deserialize, Serialize};
+
+const STORAGE_KEY: &str = "wasm-graphics-test:ui-state";
+
+// UI settings kept in `localStorage` across reloads. Fields missing in the
+// stored JSON, e.g. after adding a setting, get their default value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    pub color_r: f32,
+    pub color_g: f32,
+    pub color_b: f32,
+    pub color_a: f32,
+    pub light_x: f32,
+    pub light_y: f32,
+    pub light_z: f32,
+    pub light_intensity: f32,
+    pub shininess: f32,
+    // Number of point lights turned on.
+    pub point_lights: u32,
+    // Degrees.
+    pub fov: f32,
+    pub rotation_speed: f32,
+}
+
+impl Default for UiState {
+    fn default() -> Self {
+        let color = ColorEvent::default();
+        Self {
+            color_r: color.r,
+            color_g: color.g,
+            color_b: color.b,
+            color_a: color.a,
+            light_x: LIGHT_DIR.x,
+            light_y: LIGHT_DIR.y,
+            light_z: LIGHT_DIR.z,
+            light_intensity: LIGHT_INTENSITY,
+            shininess: SHININESS,
+            point_lights: 0,
+            fov: CAM_FOV.to_degrees().round(),
+            rotation_speed: ROTATION_SPEED,
+        }
+    }
+}
+
+fn storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+impl UiState {
+    // Reads the saved state. Falls back to the defaults if nothing was saved,
+    // storage is unavailable (e.g. disabled in private browsing), or the
+    // saved value can't be parsed.
+    pub fn load() -> Self {
+        let Some(json) = storage().and_then(|s| s.get_item(STORAGE_KEY).ok().flatten()) else {
+            return Self::default();
+        };
+        serde_json::from_str(&json).unwrap_or_else(|e| {
+            log::warn!("Ignoring saved UI state: {e}");
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) {
+        let Some(storage) = storage() else {
+            return;
+        };
+        match serde_json::to_string(self) {
+            Ok(json) => {
+                if storage.set_item(STORAGE_KEY, &json).is_err() {
+                    log::warn!("Could not save UI state");
+                }
+            }
+            Err(e) => log::warn!("Could not serialize UI state: {e}"),
+        }
+    }
+}
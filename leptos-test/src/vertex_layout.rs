@@ -0,0 +1,306 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum VertexLayoutError {
+    // WebGPU requires strides to be multiples of 4 bytes, and offsets too,
+    // or of the format size if it is smaller.
+    UnalignedStride { stride: u64 },
+    UnalignedOffset { location: u32, offset: u64 },
+    AttributesExceedStride { size: u64, stride: u64 },
+    OutOfBounds { location: u32, end: u64, stride: u64 },
+    Overlap { location: u32, other: u32 },
+    // Between the buffers of one form.
+    DuplicateLocation { location: u32 },
+    VertexCountMismatch { buffer: usize, count: usize, expected: usize },
+}
+
+impl fmt::Display for VertexLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VertexLayoutError::UnalignedStride { stride } => {
+                write!(f, "stride {stride} is not a multiple of 4")
+            }
+            VertexLayoutError::UnalignedOffset { location, offset } => {
+                write!(f, "offset {offset} of attribute {location} is not aligned")
+            }
+            VertexLayoutError::AttributesExceedStride { size, stride } => {
+                write!(f, "attributes take {size} bytes, more than the stride of {stride}")
+            }
+            VertexLayoutError::OutOfBounds { location, end, stride } => {
+                write!(f, "attribute {location} ends at byte {end}, past the stride of {stride}")
+            }
+            VertexLayoutError::Overlap { location, other } => {
+                write!(f, "attribute {location} overlaps attribute {other}")
+            }
+            VertexLayoutError::DuplicateLocation { location } => {
+                write!(f, "shader location {location} is used by more than one attribute")
+            }
+            VertexLayoutError::VertexCountMismatch {
+                buffer,
+                count,
+                expected,
+            } => {
+                write!(f, "buffer {buffer} has {count} vertices, expected {expected}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for VertexLayoutError {}
+
+// Describes a vertex buffer with explicit attribute offsets, for vertices
+// whose fields are not simply packed one after another in the order of the
+// shader inputs, e.g. with padding or mixed formats. Attributes get shader
+// locations in the order they are added, like the inputs of the vertex
+// shader, following the previous attribute. Pass it to
+// `ProgramBuilder::with_vertex_layout`.
+#[derive(Debug, Clone)]
+pub struct VertexLayout {
+    stride: u64,
+    attributes: Vec<wgpu::VertexAttribute>,
+}
+
+impl VertexLayout {
+    pub fn new(stride: u64) -> Self {
+        Self {
+            stride,
+            attributes: Vec::new(),
+        }
+    }
+
+    // Stride of one `T`, e.g. a `#[repr(C)]` vertex struct.
+    pub fn of<T>() -> Self {
+        Self::new(std::mem::size_of::<T>() as u64)
+    }
+
+    // A buffer holding only one attribute, tightly packed, e.g. one of the
+    // per-attribute arrays of a mesh loader. `location` is the attribute's
+    // shader location, which continues from the other buffers of the form.
+    pub fn single(format: wgpu::VertexFormat, location: u32) -> Self {
+        Self {
+            stride: format.size(),
+            attributes: vec![wgpu::VertexAttribute {
+                format,
+                offset: 0,
+                shader_location: location,
+            }],
+        }
+    }
+
+    // Attributes packed one after another, at shader locations from
+    // `first_location` on.
+    pub fn packed(formats: &[wgpu::VertexFormat], first_location: u32) -> Self {
+        let mut offset = 0;
+        let attributes = formats
+            .iter()
+            .zip(first_location..)
+            .map(|(&format, shader_location)| {
+                let attribute = wgpu::VertexAttribute {
+                    format,
+                    offset,
+                    shader_location,
+                };
+                offset += format.size();
+                attribute
+            })
+            .collect();
+        Self {
+            stride: offset,
+            attributes,
+        }
+    }
+
+    pub fn with_attribute(mut self, format: wgpu::VertexFormat, offset: u64) -> Self {
+        let shader_location = self.attributes.last().map_or(0, |a| a.shader_location + 1);
+        self.attributes.push(wgpu::VertexAttribute {
+            format,
+            offset,
+            shader_location,
+        });
+        self
+    }
+
+    pub fn stride(&self) -> u64 {
+        self.stride
+    }
+
+    pub fn last_location(&self) -> Option<u32> {
+        self.attributes.iter().map(|a| a.shader_location).max()
+    }
+
+    // Checks that all attributes fit into the stride without overlapping.
+    pub fn validate(&self) -> Result<(), VertexLayoutError> {
+        let stride = self.stride;
+        if stride % 4 != 0 {
+            return Err(VertexLayoutError::UnalignedStride { stride });
+        }
+        let size = self.attributes.iter().map(|a| a.format.size()).sum();
+        if size > stride {
+            return Err(VertexLayoutError::AttributesExceedStride { size, stride });
+        }
+        for (i, a) in self.attributes.iter().enumerate() {
+            let location = a.shader_location;
+            if a.offset % a.format.size().min(4) != 0 {
+                let offset = a.offset;
+                return Err(VertexLayoutError::UnalignedOffset { location, offset });
+            }
+            let end = a.offset + a.format.size();
+            if end > stride {
+                return Err(VertexLayoutError::OutOfBounds { location, end, stride });
+            }
+            let overlapping = |b: &wgpu::VertexAttribute| {
+                a.offset < b.offset + b.format.size() && b.offset < end
+            };
+            if let Some(b) = self.attributes[..i].iter().find(|b| overlapping(b)) {
+                let other = b.shader_location;
+                return Err(VertexLayoutError::Overlap { location, other });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn buffer_layout(&self) -> Result<wgpu::VertexBufferLayout<'_>, VertexLayoutError> {
+        self.validate()?;
+        Ok(wgpu::VertexBufferLayout {
+            array_stride: self.stride,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &self.attributes,
+        })
+    }
+}
+
+// Checks the vertex buffers of one form, given with their vertex counts: each
+// layout has to be valid, all buffers need as many vertices as the first, as
+// a draw reads the same vertex index from each of them, and no shader
+// location may be used twice.
+pub fn validate_buffers(buffers: &[(&VertexLayout, usize)]) -> Result<(), VertexLayoutError> {
+    let Some(&(_, expected)) = buffers.first() else {
+        return Ok(());
+    };
+    let mut locations = Vec::new();
+    for (buffer, &(layout, count)) in buffers.iter().enumerate() {
+        layout.validate()?;
+        if count != expected {
+            return Err(VertexLayoutError::VertexCountMismatch {
+                buffer,
+                count,
+                expected,
+            });
+        }
+        for a in &layout.attributes {
+            let location = a.shader_location;
+            if locations.contains(&location) {
+                return Err(VertexLayoutError::DuplicateLocation { location });
+            }
+            locations.push(location);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wgpu::VertexFormat::{Float32x2, Float32x3, Unorm8x4};
+
+    #[test]
+    fn fitting_layout_keeps_offsets_and_locations() {
+        // Position, normal, uv and a packed color: 12 + 12 + 8 + 4 bytes.
+        let full = VertexLayout::new(36)
+            .with_attribute(Float32x3, 0)
+            .with_attribute(Float32x3, 12)
+            .with_attribute(Float32x2, 24)
+            .with_attribute(Unorm8x4, 32);
+        let layout = full.buffer_layout().unwrap();
+        let locations: Vec<_> = layout.attributes.iter().map(|a| a.shader_location).collect();
+        assert_eq!(layout.array_stride, 36);
+        assert_eq!(locations, [0, 1, 2, 3]);
+
+        let padded = VertexLayout::new(48)
+            .with_attribute(Float32x3, 16)
+            .with_attribute(Float32x3, 0);
+        assert_eq!(padded.validate(), Ok(()));
+    }
+
+    #[test]
+    fn packed_layout_continues_locations() {
+        let layout = VertexLayout::packed(&[Float32x3, Float32x2, Unorm8x4], 2);
+        let buffer = layout.buffer_layout().unwrap();
+        let attributes: Vec<_> = buffer
+            .attributes
+            .iter()
+            .map(|a| (a.offset, a.shader_location))
+            .collect();
+        assert_eq!(buffer.array_stride, 24);
+        assert_eq!(attributes, [(0, 2), (12, 3), (20, 4)]);
+        assert_eq!(layout.last_location(), Some(4));
+    }
+
+    #[test]
+    fn invalid_layouts_are_rejected() {
+        let too_small = VertexLayout::new(20)
+            .with_attribute(Float32x3, 0)
+            .with_attribute(Float32x3, 12);
+        assert_eq!(
+            too_small.validate(),
+            Err(VertexLayoutError::AttributesExceedStride {
+                size: 24,
+                stride: 20,
+            })
+        );
+
+        let past_the_end = VertexLayout::new(24)
+            .with_attribute(Float32x2, 0)
+            .with_attribute(Float32x3, 16);
+        assert_eq!(
+            past_the_end.validate(),
+            Err(VertexLayoutError::OutOfBounds {
+                location: 1,
+                end: 28,
+                stride: 24,
+            })
+        );
+
+        let overlapping = VertexLayout::new(32)
+            .with_attribute(Float32x3, 0)
+            .with_attribute(Float32x3, 8);
+        assert_eq!(
+            overlapping.validate(),
+            Err(VertexLayoutError::Overlap {
+                location: 1,
+                other: 0,
+            })
+        );
+
+        assert_eq!(
+            VertexLayout::new(16).with_attribute(Float32x2, 2).validate(),
+            Err(VertexLayoutError::UnalignedOffset {
+                location: 0,
+                offset: 2,
+            })
+        );
+        assert_eq!(
+            VertexLayout::new(14).with_attribute(Float32x3, 0).validate(),
+            Err(VertexLayoutError::UnalignedStride { stride: 14 })
+        );
+    }
+
+    #[test]
+    fn separate_buffers_need_equal_counts_and_distinct_locations() {
+        let positions = VertexLayout::single(Float32x3, 0);
+        let colors = VertexLayout::single(Unorm8x4, 1);
+        assert_eq!(validate_buffers(&[(&positions, 8), (&colors, 8)]), Ok(()));
+        assert_eq!(
+            validate_buffers(&[(&positions, 8), (&colors, 6)]),
+            Err(VertexLayoutError::VertexCountMismatch {
+                buffer: 1,
+                count: 6,
+                expected: 8,
+            })
+        );
+        assert_eq!(
+            validate_buffers(&[(&positions, 8), (&positions, 8)]),
+            Err(VertexLayoutError::DuplicateLocation { location: 0 })
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use crate::gpu::{shader, DrawCall, Gpu, Mesh, PipelineState, Program, Uniform};
+use crate::gpu::{UNIFORM_FRAG, UNIFORM_VERT};
+use crate::msaa::{msaa_samples, MSAA_SAMPLES};
+use crate::surface::{self, Frame};
+use crate::target::Target;
+use std::f32::consts::TAU;
+use trivalibs::painter::app::Event;
+use trivalibs::painter::prelude::*;
+use trivalibs::prelude::*;
+use trivalibs::rendering::camera::{CamProps, PerspectiveCamera};
+
+const VERTICES: [Vec3; 3] = [vec3(0.0, 5.0, 0.0), vec3(-2.5, 0., 0.0), vec3(2.5, 0., 0.0)];
+
+// Sideways swing of the top vertex, and its frequency in Hz.
+const WAVE_AMPLITUDE: f32 = 2.0;
+const WAVE_FREQUENCY: f32 = 0.5;
+
+// A triangle whose top vertex swings from side to side, by writing new
+// vertex data into the existing mesh every frame.
+//
+// This uploads the whole vertex buffer each frame, which is fine for a few
+// vertices, but grows with the mesh size and costs a queue write per mesh.
+// For deformations that can be computed per vertex, like this one, doing it
+// in the vertex shader from a time uniform is cheaper: only 4 bytes are
+// uploaded, and the GPU does the work in parallel. CPU side updates are for
+// changes a shader can't express, e.g. results of a simulation.
+pub struct WaveApp {
+    gpu: Gpu,
+    cam: PerspectiveCamera,
+    vp_mat: Uniform<Mat4>,
+    // Shares its vertex buffer with the draw's mesh, so writes show up there.
+    mesh: Mesh,
+    vertices: [Vec3; 3],
+    time: f32,
+
+    canvas: Target,
+    draw: DrawCall,
+}
+
+impl CanvasApp<()> for WaveApp {
+    fn init(p: &mut Painter) -> Self {
+        let gpu = Gpu::new(p);
+        let program = Program::build(&gpu, shader!("flat_vertex"))
+            .with_fragment(shader!("flat_fragment"))
+            .with_vertex(&[Float32x3])
+            .with_bindings(&[UNIFORM_VERT, UNIFORM_VERT, UNIFORM_FRAG])
+            .create();
+
+        let mesh = Mesh::new(&gpu, &VERTICES);
+
+        let vp_mat = Uniform::new(&gpu, Mat4::IDENTITY);
+        let model_mat = Uniform::new(
+            &gpu,
+            Mat4::from_translation(vec3(0.0, -10.0, 0.0)) * Mat4::from_scale(Vec3::splat(4.0)),
+        );
+        let color = Uniform::new(&gpu, vec4(1.0, 0.6, 0.1, 1.0));
+
+        let canvas = Target::canvas(surface::view_format(p))
+            .with_samples(msaa_samples(p, MSAA_SAMPLES))
+            .create(&gpu, 1, 1);
+        let bind_group = program.bind_group(
+            &gpu,
+            0,
+            &[vp_mat.binding(), model_mat.binding(), color.binding()],
+        );
+        let draw = DrawCall::new(
+            &gpu,
+            &program,
+            &mesh,
+            vec![bind_group],
+            PipelineState {
+                cull_mode: None,
+                ..canvas.pipeline_state()
+            },
+        );
+
+        Self {
+            gpu,
+            cam: PerspectiveCamera::create(CamProps {
+                fov: Some(0.6),
+                translation: Some(vec3(0.0, 0.0, 80.0)),
+                ..default()
+            }),
+            vp_mat,
+            mesh,
+            vertices: VERTICES,
+            time: 0.0,
+
+            canvas,
+            draw,
+        }
+    }
+
+    fn resize(&mut self, _p: &mut Painter, width: u32, height: u32) {
+        self.cam.set_aspect_ratio(width as f32 / height as f32);
+        self.canvas.resize(&self.gpu, width, height);
+
+        self.vp_mat.update(&self.gpu, self.cam.view_proj_mat());
+    }
+
+    fn update(&mut self, p: &mut Painter, tpf: f32) {
+        self.time = (self.time + tpf * WAVE_FREQUENCY) % 1.0;
+        self.vertices[0].x = VERTICES[0].x + (self.time * TAU).sin() * WAVE_AMPLITUDE;
+        self.mesh.update_vertices(&self.gpu, &self.vertices);
+        p.request_next_frame();
+    }
+
+    fn render(&self, p: &mut Painter) -> Result<(), SurfaceError> {
+        let Some(frame) = Frame::acquire(p)? else {
+            return Ok(());
+        };
+        let mut encoder = self.gpu.encoder("Wave Encoder");
+        self.draw.draw(
+            &mut self.canvas.pass(&mut encoder, Some(&frame.view)),
+            self.canvas.size(),
+        );
+        self.gpu.queue.submit([encoder.finish()]);
+        frame.present();
+        Ok(())
+    }
+
+    fn event(&mut self, _e: Event<()>, _p: &mut Painter) {}
+}
@@ -0,0 +1,59 @@
+use crate::gpu::OPTIONAL_FEATURES;
+use wasm_bindgen::JsCast;
+
+// Whether the browser exposes WebGPU. Looks up `navigator.gpu` by reflection,
+// since the typed `Navigator::gpu` getter assumes it exists. An adapter
+// request can still fail later, which the app runner reports separately.
+pub fn is_webgpu_supported() -> bool {
+    let Some(window) = web_sys::window() else {
+        return false;
+    };
+    js_sys::Reflect::get(&window.navigator(), &"gpu".into())
+        .map(|gpu| !gpu.is_undefined() && !gpu.is_null())
+        .unwrap_or(false)
+}
+
+// Whether a WebGL2 context can be created, the other backend wgpu has in the
+// browser.
+// Checked on a detached canvas, as a canvas only ever gets one kind of
+// context.
+pub fn is_webgl2_supported() -> bool {
+    let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+        return false;
+    };
+    document
+        .create_element("canvas")
+        .ok()
+        .and_then(|c| c.dyn_into::<web_sys::HtmlCanvasElement>().ok())
+        .and_then(|c| c.get_context("webgl2").ok().flatten())
+        .is_some()
+}
+
+// Why the app could not be started, shown in the UI instead of a blank
+// canvas.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StartError {
+    NoAdapter,
+    RequestDevice(String),
+}
+
+// Requests a WebGPU adapter and device like the app runner will, and drops
+// them again, so failures the runner would only log can be shown. Returns the
+// `OPTIONAL_FEATURES` the adapter supports, for `AppConfig::features`. WebGL2
+// adapters need the canvas, which the runner takes over, so that path isn't
+// probed.
+pub async fn probe_webgpu() -> Result<wgpu::Features, StartError> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::BROWSER_WEBGPU,
+        ..Default::default()
+    });
+    let adapter = instance
+        .request_adapter(&wgpu::RequestAdapterOptions::default())
+        .await
+        .map_err(|_| StartError::NoAdapter)?;
+    adapter
+        .request_device(&wgpu::DeviceDescriptor::default())
+        .await
+        .map_err(|e| StartError::RequestDevice(e.to_string()))?;
+    Ok(OPTIONAL_FEATURES & adapter.features())
+}
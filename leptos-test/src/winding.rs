@@ -0,0 +1,67 @@
+use trivalibs::prelude::*;
+
+// Whether a triangle is front facing under `front_face`, like the
+// rasterizer decides it: by the winding of its corners after projection, in
+// normalized device coordinates with y up. Degenerate triangles face neither
+// way.
+pub fn is_front_facing(ndc: [Vec2; 3], front_face: wgpu::FrontFace) -> bool {
+    let [a, b, c] = ndc;
+    let area = (b - a).perp_dot(c - a);
+    match front_face {
+        wgpu::FrontFace::Ccw => area > 0.0,
+        wgpu::FrontFace::Cw => area < 0.0,
+    }
+}
+
+// Whether a triangle survives culling with the given front face and cull
+// mode, as set in `PipelineState::front_face` and `cull_mode`.
+pub fn is_drawn(
+    ndc: [Vec2; 3],
+    front_face: wgpu::FrontFace,
+    cull_mode: Option<wgpu::Face>,
+) -> bool {
+    let back_face = match front_face {
+        wgpu::FrontFace::Ccw => wgpu::FrontFace::Cw,
+        wgpu::FrontFace::Cw => wgpu::FrontFace::Ccw,
+    };
+    match cull_mode {
+        None => true,
+        Some(wgpu::Face::Back) => is_front_facing(ndc, front_face),
+        Some(wgpu::Face::Front) => is_front_facing(ndc, back_face),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::cube;
+
+    // A cube seen from a corner with back face culling: with counter
+    // clockwise front faces the 3 faces towards the camera are drawn, and
+    // clockwise draws exactly the other 3.
+    #[test]
+    fn winding_selects_faces_towards_camera() {
+        const EYE: Vec3 = vec3(3.0, 4.0, 5.0);
+
+        let (positions, indices) = cube(2.0);
+        let view_proj = Mat4::perspective_rh(0.8, 1.0, 0.1, 100.0)
+            * Mat4::look_at_rh(EYE, Vec3::ZERO, Vec3::Y);
+
+        let mut drawn_ccw = 0;
+        for triangle in indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| positions[triangle[i] as usize]);
+            let ndc = [a, b, c].map(|p| view_proj.project_point3(p).truncate());
+            let ccw = is_drawn(ndc, wgpu::FrontFace::Ccw, Some(wgpu::Face::Back));
+            let cw = is_drawn(ndc, wgpu::FrontFace::Cw, Some(wgpu::Face::Back));
+            // Counter clockwise on screen means that the normal by the right
+            // hand rule points to the eye, independent of the projection.
+            let towards_eye = (b - a).cross(c - a).dot(EYE - a) > 0.0;
+
+            assert_ne!(ccw, cw, "triangle {triangle:?}");
+            assert_eq!(ccw, towards_eye, "triangle {triangle:?}");
+            drawn_ccw += ccw as usize;
+        }
+        // 3 faces, 6 triangles.
+        assert_eq!(drawn_ccw, 6);
+    }
+}